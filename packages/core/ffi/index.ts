@@ -3,11 +3,16 @@ import { existsSync } from "node:fs";
 import { join } from "node:path";
 
 type CoreEventHandler = (eventName: string, payload: unknown) => void;
+type CoreLogHandler = (level: string, message: string) => void;
+type CoreLogLevel = "off" | "error" | "warn" | "info" | "debug";
 
 type Symbols = {
   core_configure: (appDataDir: Uint8Array, resourceDir: Uint8Array) => number;
   core_set_event_callback: (callback: Pointer) => void;
+  core_set_log_level: (level: Uint8Array) => number;
+  core_set_log_callback: (callback: Pointer | null) => void;
   core_invoke_json: (request: Uint8Array) => Pointer;
+  core_invoke_batch_json: (requests: Uint8Array) => Pointer;
   core_free_str: (ptr: Pointer) => void;
 };
 
@@ -16,6 +21,8 @@ type LoadCoreOptions = {
   appDataDir: string;
   resourceDir?: string;
   onEvent?: CoreEventHandler;
+  logLevel?: CoreLogLevel;
+  onLog?: CoreLogHandler;
 };
 
 type InvokeResponse = {
@@ -75,7 +82,10 @@ export function loadCore(options: LoadCoreOptions) {
   const symbolDefinition = {
     core_configure: { args: ["ptr", "ptr"], returns: "i32" },
     core_set_event_callback: { args: ["function"], returns: "void" },
+    core_set_log_level: { args: ["ptr"], returns: "i32" },
+    core_set_log_callback: { args: ["function"], returns: "void" },
     core_invoke_json: { args: ["ptr"], returns: "ptr" },
+    core_invoke_batch_json: { args: ["ptr"], returns: "ptr" },
     core_free_str: { args: ["ptr"], returns: "void" }
   } as const;
 
@@ -106,6 +116,38 @@ export function loadCore(options: LoadCoreOptions) {
 
   symbols.core_set_event_callback(callback as unknown as Pointer);
 
+  const logCallback = options.onLog
+    ? new JSCallback(
+        (levelPtr, messagePtr) => {
+          try {
+            const level = new CString(levelPtr as Pointer).toString();
+            const message = new CString(messagePtr as Pointer).toString();
+            options.onLog?.(level, message);
+          } catch (error) {
+            console.error("Failed to process core log callback", error);
+          }
+        },
+        {
+          args: ["ptr", "ptr"],
+          returns: "void",
+          threadsafe: true
+        }
+      )
+    : null;
+  if (logCallback) {
+    symbols.core_set_log_callback(logCallback as unknown as Pointer);
+  }
+
+  function setLogLevel(level: CoreLogLevel) {
+    if (symbols.core_set_log_level(toCStringBuffer(level)) !== 1) {
+      throw new Error(`Unknown core log level: ${level}`);
+    }
+  }
+
+  if (options.logLevel) {
+    setLogLevel(options.logLevel);
+  }
+
   const configured = symbols.core_configure(
     toCStringBuffer(options.appDataDir),
     toCStringBuffer(options.resourceDir ?? "")
@@ -132,8 +174,37 @@ export function loadCore(options: LoadCoreOptions) {
     return parsed.value as T;
   }
 
+  function invokeBatch(requests: { command: string; args?: Record<string, unknown> }[]) {
+    const payload = JSON.stringify(
+      requests.map(({ command, args }) => ({ command, args: args ?? {} }))
+    );
+    const responsePtr = symbols.core_invoke_batch_json(toCStringBuffer(payload));
+    if (!responsePtr) {
+      throw new Error("No response pointer returned by Rust core for batch invoke");
+    }
+
+    const raw = new CString(responsePtr).toString();
+    symbols.core_free_str(responsePtr);
+
+    let parsed: unknown;
+    try {
+      parsed = JSON.parse(raw);
+    } catch {
+      throw new Error(`Invalid response JSON from core: ${raw.slice(0, 200)}`);
+    }
+    if (!Array.isArray(parsed)) {
+      const failure = parsed as InvokeResponse;
+      throw new Error(failure.error ?? "Unknown core error while invoking batch");
+    }
+
+    return parsed as InvokeResponse[];
+  }
+
   return {
     invoke,
-    callback
+    invokeBatch,
+    setLogLevel,
+    callback,
+    logCallback
   };
 }
@@ -1,4 +1,7 @@
-use crate::types::{ParsedChunk, ParsedParagraph};
+use std::collections::HashMap;
+
+use crate::docx_parse::build_heading_ranges;
+use crate::types::{ParsedChunk, ParsedComment, ParsedParagraph};
 use crate::util::is_probable_author_line;
 
 const BASE_CHUNK_MIN_CHARS: usize = 700;
@@ -7,6 +10,10 @@ const BASE_CHUNK_OVERLAP_CHARS: usize = 220;
 const LARGE_SECTION_THRESHOLD_CHARS: usize = 40_000;
 const HUGE_SECTION_THRESHOLD_CHARS: usize = 180_000;
 const MAX_CHUNKS_PER_SECTION: usize = 384;
+const PARAGRAPH_UNIT_MIN_CHARS: usize = 40;
+pub(crate) const SHORT_HEADING_MERGE_MAX_CHARS: usize = 48;
+pub(crate) const ARGUMENT_TYPE_CARD: &str = "card";
+pub(crate) const ARGUMENT_TYPE_ANALYTIC: &str = "analytic";
 
 #[derive(Clone, Copy)]
 struct ChunkProfile {
@@ -154,6 +161,7 @@ pub(crate) fn build_chunks(paragraphs: &[ParsedParagraph]) -> Vec<ParsedChunk> {
         for chunk_text in split_text_into_chunks(&section_text) {
             chunks.push(ParsedChunk {
                 chunk_order: *chunk_order,
+                kind: "chunk".to_string(),
                 heading_order,
                 heading_level,
                 heading_text: heading_text.clone(),
@@ -189,6 +197,7 @@ pub(crate) fn build_chunks(paragraphs: &[ParsedParagraph]) -> Vec<ParsedChunk> {
             // Keep structure searchable even when body text is short.
             chunks.push(ParsedChunk {
                 chunk_order,
+                kind: "chunk".to_string(),
                 heading_order: current_heading_order,
                 heading_level: current_heading_level,
                 heading_text: current_heading_text.clone(),
@@ -217,3 +226,344 @@ pub(crate) fn build_chunks(paragraphs: &[ParsedParagraph]) -> Vec<ParsedChunk> {
 
     chunks
 }
+
+// Joins runs of short same-level headings with nothing between them (tags split across
+// paragraphs) while the joined text stays short. The merged heading keeps the first
+// fragment's order.
+pub(crate) fn merge_short_adjacent_headings(
+    paragraphs: Vec<ParsedParagraph>,
+    max_chars: usize,
+) -> Vec<ParsedParagraph> {
+    let is_short = |paragraph: &ParsedParagraph| {
+        let count = paragraph.text.trim().chars().count();
+        count > 0 && count < max_chars
+    };
+
+    let mut merged: Vec<ParsedParagraph> = Vec::with_capacity(paragraphs.len());
+    let mut previous_was_short_heading = false;
+    for paragraph in paragraphs {
+        if paragraph.text.trim().is_empty() {
+            merged.push(paragraph);
+            continue;
+        }
+
+        let short_heading = paragraph.heading_level.is_some() && is_short(&paragraph);
+        let previous = merged
+            .iter_mut()
+            .rev()
+            .find(|candidate| !candidate.text.trim().is_empty());
+        if let Some(previous) = previous {
+            let joined = format!("{} {}", previous.text.trim(), paragraph.text.trim());
+            if short_heading
+                && previous_was_short_heading
+                && previous.heading_level == paragraph.heading_level
+                && joined.chars().count() < max_chars
+            {
+                previous.text = joined;
+                continue;
+            }
+        }
+
+        previous_was_short_heading = short_heading;
+        merged.push(paragraph);
+    }
+
+    merged
+}
+
+fn unit_dedup_key(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// Emits standalone rows for body paragraphs that no single chunk holds whole, i.e. ones a
+// long section cut across a chunk boundary, so a phrase split between chunks stays
+// directly searchable. Paragraphs inside a chunk are not repeated.
+pub(crate) fn append_paragraph_units(
+    paragraphs: &[ParsedParagraph],
+    chunks: &mut Vec<ParsedChunk>,
+) {
+    let mut section_texts = HashMap::<Option<i64>, Vec<String>>::new();
+    for chunk in chunks.iter() {
+        section_texts
+            .entry(chunk.heading_order)
+            .or_default()
+            .push(unit_dedup_key(&chunk.chunk_text));
+    }
+    let mut chunk_order = chunks
+        .iter()
+        .map(|chunk| chunk.chunk_order)
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    let mut current_heading_order: Option<i64> = None;
+    let mut current_heading_level: Option<i64> = None;
+    let mut current_heading_text: Option<String> = None;
+    let mut units = Vec::new();
+
+    for paragraph in paragraphs {
+        let text = paragraph.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        if let Some(level) = paragraph.heading_level {
+            current_heading_order = Some(paragraph.order);
+            current_heading_level = Some(level);
+            current_heading_text = Some(text.to_string());
+            continue;
+        }
+
+        if text.chars().count() < PARAGRAPH_UNIT_MIN_CHARS {
+            continue;
+        }
+        let key = unit_dedup_key(text);
+        let inside_chunk = section_texts
+            .get(&current_heading_order)
+            .is_some_and(|texts| texts.iter().any(|chunk_text| chunk_text.contains(&key)));
+        if inside_chunk {
+            continue;
+        }
+
+        units.push(ParsedChunk {
+            chunk_order,
+            kind: "paragraph".to_string(),
+            heading_order: current_heading_order,
+            heading_level: current_heading_level,
+            heading_text: current_heading_text.clone(),
+            author_text: None,
+            chunk_text: text.to_string(),
+        });
+        chunk_order += 1;
+    }
+
+    chunks.extend(units);
+}
+
+// Emits one row per review comment, tied to the heading whose section holds the
+// commented paragraph.
+pub(crate) fn append_comment_units(
+    paragraphs: &[ParsedParagraph],
+    comments: &[ParsedComment],
+    chunks: &mut Vec<ParsedChunk>,
+) {
+    let mut chunk_order = chunks
+        .iter()
+        .map(|chunk| chunk.chunk_order)
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    for comment in comments {
+        let text = comment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let heading = paragraphs
+            .iter()
+            .take_while(|paragraph| paragraph.order <= comment.paragraph_order)
+            .filter(|paragraph| paragraph.heading_level.is_some())
+            .last();
+        chunks.push(ParsedChunk {
+            chunk_order,
+            kind: "comment".to_string(),
+            heading_order: heading.map(|paragraph| paragraph.order),
+            heading_level: heading.and_then(|paragraph| paragraph.heading_level),
+            heading_text: heading.map(|paragraph| paragraph.text.trim().to_string()),
+            author_text: None,
+            chunk_text: text.to_string(),
+        });
+        chunk_order += 1;
+    }
+}
+
+pub(crate) fn collapse_chunk_whitespace(chunks: &mut [ParsedChunk]) {
+    for chunk in chunks {
+        chunk.chunk_text = chunk
+            .chunk_text
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+    }
+}
+
+// Only a heading's own body counts; citations under its child headings belong to them.
+pub(crate) fn section_argument_types(paragraphs: &[ParsedParagraph]) -> HashMap<i64, String> {
+    build_heading_ranges(paragraphs)
+        .into_iter()
+        .filter_map(|range| {
+            let own_body = paragraphs
+                .get(range.start_index + 1..range.end_index)
+                .unwrap_or_default()
+                .iter()
+                .take_while(|paragraph| paragraph.heading_level.is_none())
+                .filter(|paragraph| !paragraph.text.trim().is_empty())
+                .collect::<Vec<&ParsedParagraph>>();
+            // Containers whose only content is nested sections are neither cards nor analytics.
+            if own_body.is_empty() {
+                return None;
+            }
+            let has_citation = own_body
+                .iter()
+                .any(|paragraph| paragraph.is_f8_cite || is_probable_author_line(&paragraph.text));
+            let argument_type = if has_citation {
+                ARGUMENT_TYPE_CARD
+            } else {
+                ARGUMENT_TYPE_ANALYTIC
+            };
+            Some((range.order, argument_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        append_paragraph_units, build_chunks, merge_short_adjacent_headings, section_argument_types,
+    };
+    use crate::types::ParsedParagraph;
+
+    fn paragraph(order: i64, text: &str, heading_level: Option<i64>) -> ParsedParagraph {
+        ParsedParagraph {
+            order,
+            text: text.to_string(),
+            heading_level,
+            style_label: None,
+            is_f8_cite: false,
+        }
+    }
+
+    #[test]
+    fn paragraph_units_expose_paragraphs_split_across_chunks() {
+        let opening = "Opening body paragraph that sets the context. ".repeat(28);
+        let split = format!(
+            "The zebra tariff spiral collapses global shipping. {}",
+            "Tariffs compound. ".repeat(20)
+        );
+        let paragraphs = vec![
+            paragraph(1, "Economy DA", Some(1)),
+            paragraph(2, opening.trim(), None),
+            paragraph(3, split.trim(), None),
+            paragraph(4, "Too short", None),
+            paragraph(5, "Politics DA", Some(1)),
+            paragraph(6, "A lone card body that is already its own chunk.", None),
+        ];
+
+        let mut chunks = build_chunks(&paragraphs);
+        assert!(chunks.iter().all(|chunk| chunk.kind == "chunk"));
+
+        append_paragraph_units(&paragraphs, &mut chunks);
+        let units = chunks
+            .iter()
+            .filter(|chunk| chunk.kind == "paragraph")
+            .collect::<Vec<_>>();
+
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].chunk_text, split.trim());
+        assert_eq!(units[0].heading_text.as_deref(), Some("Economy DA"));
+
+        let mut orders = chunks
+            .iter()
+            .map(|chunk| chunk.chunk_order)
+            .collect::<Vec<i64>>();
+        orders.dedup();
+        assert_eq!(orders.len(), chunks.len());
+    }
+
+    #[test]
+    fn short_adjacent_headings_merge_into_one_tag() {
+        let paragraphs = vec![
+            paragraph(1, "Politics DA", Some(1)),
+            paragraph(2, "Midterms shift —", Some(4)),
+            paragraph(3, "plan costs capital", Some(4)),
+            paragraph(4, "Card body text.", None),
+            paragraph(5, "Second tag", Some(4)),
+        ];
+
+        let merged = merge_short_adjacent_headings(paragraphs, 48);
+        let summary = merged
+            .iter()
+            .map(|paragraph| (paragraph.order, paragraph.text.as_str()))
+            .collect::<Vec<(i64, &str)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Politics DA"),
+                (2, "Midterms shift — plan costs capital"),
+                (4, "Card body text."),
+                (5, "Second tag"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merged_heading_stops_growing_at_max_chars() {
+        let paragraphs = vec![
+            paragraph(1, "Midterms shift —", Some(4)),
+            paragraph(2, "plan costs capital —", Some(4)),
+            paragraph(3, "which Dems need", Some(4)),
+            paragraph(4, "for the agenda", Some(4)),
+        ];
+
+        let merged = merge_short_adjacent_headings(paragraphs, 48);
+        let summary = merged
+            .iter()
+            .map(|paragraph| (paragraph.order, paragraph.text.as_str()))
+            .collect::<Vec<(i64, &str)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Midterms shift — plan costs capital —"),
+                (3, "which Dems need for the agenda"),
+            ]
+        );
+        assert!(merged
+            .iter()
+            .all(|paragraph| paragraph.text.chars().count() < 48));
+    }
+
+    #[test]
+    fn sections_with_citations_are_tagged_as_cards() {
+        let mut cite = paragraph(3, "Smith 24", None);
+        cite.is_f8_cite = true;
+        let paragraphs = vec![
+            paragraph(1, "Politics DA", Some(1)),
+            paragraph(2, "Midterms link", Some(4)),
+            cite,
+            paragraph(4, "Evidence body text.", None),
+            paragraph(5, "Analytic — they drop the link", Some(4)),
+            paragraph(6, "No warrant for their uniqueness claim.", None),
+            paragraph(7, "Turnout impact", Some(4)),
+            paragraph(
+                8,
+                "Jones, R., Policy Review, 2018, vol 4, retrieved from archive",
+                None,
+            ),
+        ];
+
+        let types = section_argument_types(&paragraphs);
+        assert_eq!(types.get(&1), None);
+        assert_eq!(types.get(&2).map(String::as_str), Some("card"));
+        assert_eq!(types.get(&5).map(String::as_str), Some("analytic"));
+        assert_eq!(types.get(&7).map(String::as_str), Some("card"));
+    }
+
+    #[test]
+    fn headings_with_only_nested_sections_are_untagged() {
+        let paragraphs = vec![
+            paragraph(1, "1NC", Some(1)),
+            paragraph(2, "", None),
+            paragraph(3, "Politics DA", Some(2)),
+            paragraph(4, "Uniqueness", Some(4)),
+            paragraph(5, "Dems win the midterms now.", None),
+        ];
+
+        let types = section_argument_types(&paragraphs);
+        assert_eq!(types.get(&1), None);
+        assert_eq!(types.get(&3), None);
+        assert_eq!(types.get(&4).map(String::as_str), Some("analytic"));
+    }
+}
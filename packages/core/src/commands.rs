@@ -3,29 +3,50 @@ use std::fs;
 use std::path::Path;
 use std::time::Instant;
 
+use base64::Engine;
 use rayon::prelude::*;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use crate::runtime::AppHandle;
 use walkdir::WalkDir;
 
-use crate::chunking::build_chunks;
-use crate::db::{add_or_get_root_id, load_existing_files, open_database, root_id};
+use crate::chunking::{
+    append_comment_units, append_paragraph_units, build_chunks, collapse_chunk_whitespace,
+    merge_short_adjacent_headings, section_argument_types, ARGUMENT_TYPE_ANALYTIC,
+    ARGUMENT_TYPE_CARD, SHORT_HEADING_MERGE_MAX_CHARS,
+};
+use crate::db::{
+    add_or_get_root_id, app_data_dir, begin_write_transaction, load_existing_files,
+    mark_capture_files, open_database, retry_busy, root_id,
+};
 use crate::docx_capture::{
-    append_capture_to_docx, ensure_valid_capture_docx, extract_styled_section,
-    paragraph_xml_heading, rewrite_docx_with_parts,
+    bookmarked_paragraph_index, ensure_valid_capture_docx, extract_styled_section,
+    insert_section_into_docx, next_bookmark_id, paragraph_xml_citation, paragraph_xml_heading,
+    paragraph_xml_with_bookmark, repair_capture_docx, rewrite_docx_with_parts, write_section_docx,
 };
-use crate::docx_parse::{build_heading_ranges, has_tag, parse_docx_paragraphs, read_docx_part};
-use crate::indexer::rebuild_lexical_index;
+use crate::docx_parse::{
+    build_heading_ranges, collect_docx_style_usage, collect_preview_debug_stats,
+    corrupt_docx_reason, extract_highlighted_blocks, has_tag, heading_neighbors, heading_outline,
+    parse_docx_comments, parse_docx_paragraphs, parse_docx_properties, read_docx_part,
+};
+use crate::indexer::{rebuild_lexical_index, refresh_lexical_file};
 use crate::lexical;
-use crate::preview::{extract_heading_preview_html, extract_preview_content};
+use crate::logging;
+use crate::preview::{
+    cached_preview_content, extract_heading_preview_html, extract_preview_content,
+    heading_anchor_id, preview_headings, render_document_html,
+};
 use crate::query_engine;
 use crate::search::normalize_for_search;
+use crate::text_parse::{is_indexable_path, is_plain_text_path, parse_source_paragraphs};
 use crate::types::*;
 use crate::util::*;
 use crate::CommandResult;
 use crate::DEFAULT_CAPTURE_TARGET;
 
-use crate::docx_capture::{fallback_body_insertion_index, insertion_index_after_paragraph_count};
+use crate::docx_capture::{
+    fallback_body_insertion_index, insert_fragment_into_document_xml,
+    insertion_index_after_paragraph_count,
+};
 
 use roxmltree::{Document, Node};
 
@@ -61,6 +82,96 @@ pub(crate) fn remove_root(app: AppHandle, path: String) -> CommandResult<()> {
     Ok(())
 }
 
+const CAPTURE_SETTINGS_FILE_NAME: &str = "capture-settings.json";
+
+fn load_capture_settings(app: &AppHandle) -> CaptureSettings {
+    let Ok(data_dir) = app_data_dir(app) else {
+        return CaptureSettings::default();
+    };
+    let settings_path = data_dir.join(CAPTURE_SETTINGS_FILE_NAME);
+    let Ok(raw) = fs::read_to_string(&settings_path) else {
+        return CaptureSettings::default();
+    };
+    serde_json::from_str::<CaptureSettings>(&raw).unwrap_or_else(|error| {
+        logging::warn(&format!(
+            "Ignoring invalid capture settings '{}': {error}",
+            path_display(&settings_path)
+        ));
+        CaptureSettings::default()
+    })
+}
+
+fn save_capture_settings(app: &AppHandle, settings: &CaptureSettings) -> CommandResult<()> {
+    let settings_path = app_data_dir(app)?.join(CAPTURE_SETTINGS_FILE_NAME);
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|error| format!("Could not serialize capture settings: {error}"))?;
+    fs::write(&settings_path, json).map_err(|error| {
+        format!(
+            "Could not write capture settings '{}': {error}",
+            path_display(&settings_path)
+        )
+    })
+}
+
+fn default_capture_target(app: &AppHandle) -> String {
+    load_capture_settings(app)
+        .default_target
+        .unwrap_or_else(|| DEFAULT_CAPTURE_TARGET.to_string())
+}
+
+pub(crate) fn set_default_capture_target(
+    app: AppHandle,
+    name: Option<String>,
+) -> CommandResult<String> {
+    let default_target = match name {
+        Some(value) => {
+            let trimmed = value.trim();
+            let is_docx = Path::new(trimmed)
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("docx"));
+            if !is_docx {
+                return Err(format!(
+                    "Default capture target '{trimmed}' must end in .docx."
+                ));
+            }
+            if Path::new(trimmed).is_absolute() {
+                return Err("Default capture target must be relative to the root.".to_string());
+            }
+            Some(normalize_capture_target_path(Some(trimmed))?)
+        }
+        None => None,
+    };
+
+    let mut settings = load_capture_settings(&app);
+    settings.default_target = default_target;
+    save_capture_settings(&app, &settings)?;
+    Ok(default_capture_target(&app))
+}
+
+pub(crate) fn set_capture_banner(
+    app: AppHandle,
+    text: Option<String>,
+) -> CommandResult<Option<String>> {
+    let banner_text = match text {
+        Some(value) => {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                return Err(
+                    "Capture banner text cannot be empty; pass null to disable it.".to_string(),
+                );
+            }
+            Some(trimmed.to_string())
+        }
+        None => None,
+    };
+
+    let mut settings = load_capture_settings(&app);
+    settings.banner_text = banner_text;
+    save_capture_settings(&app, &settings)?;
+    Ok(settings.banner_text)
+}
+
 pub(crate) fn insert_capture(
     app: AppHandle,
     root_path: String,
@@ -72,6 +183,8 @@ pub(crate) fn insert_capture(
     heading_level: Option<i64>,
     heading_order: Option<i64>,
     selected_target_heading_order: Option<i64>,
+    under_marker: Option<String>,
+    with_citation_header: bool,
 ) -> CommandResult<CaptureInsertResult> {
     let content_value = content;
     if content_value.trim().is_empty() {
@@ -79,16 +192,45 @@ pub(crate) fn insert_capture(
     }
 
     let canonical_root = canonicalize_folder(&root_path)?;
-    let target_relative_path = normalize_capture_target_path(target_path.as_deref())?;
-    let normalized_heading_level = heading_level.filter(|level| (1..=9).contains(level));
-    let normalized_target_heading_order = selected_target_heading_order.filter(|value| *value > 0);
+    let mut target_relative_path = normalize_capture_target_path_with_default(
+        target_path.as_deref(),
+        &default_capture_target(&app),
+    )?;
+    let mut normalized_heading_level = heading_level.filter(|level| (1..=9).contains(level));
+    let mut normalized_target_heading_order =
+        selected_target_heading_order.filter(|value| *value > 0);
     let root_path_string = path_display(&canonical_root);
     let connection = open_database(&app)?;
     let root_id = add_or_get_root_id(&connection, &root_path_string)?;
 
+    if let Some(marker) = under_marker
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        let parent = resolve_marker_heading(&connection, &canonical_root, root_id, marker)?;
+        let explicit_target = target_path
+            .as_deref()
+            .is_some_and(|value| !value.trim().is_empty());
+        if explicit_target && parent.target_relative_path != target_relative_path {
+            return Err(format!(
+                "Capture {marker} lives in '{}', not '{target_relative_path}'.",
+                parent.target_relative_path
+            ));
+        }
+        target_relative_path = parent.target_relative_path;
+        normalized_target_heading_order = Some(parent.heading_order);
+        if normalized_heading_level
+            .filter(|level| *level > parent.level)
+            .is_none()
+        {
+            normalized_heading_level = Some((parent.level + 1).min(9));
+        }
+    }
+
     let created_at_ms = now_ms();
-    connection
-        .execute(
+    retry_busy(|| {
+        connection.execute(
             "
             INSERT INTO captures(
               root_id,
@@ -111,12 +253,13 @@ pub(crate) fn insert_capture(
                 created_at_ms
             ],
         )
-        .map_err(|error| format!("Could not insert capture entry: {error}"))?;
+    })
+    .map_err(|error| format!("Could not insert capture entry: {error}"))?;
 
     let capture_id = connection.last_insert_rowid();
     let capture_path = capture_docx_path(&canonical_root, &target_relative_path);
     let source_file_path = Path::new(&source_path);
-    let styled_section = paragraph_xml
+    let mut styled_section = paragraph_xml
         .and_then(|entries| {
             let cleaned = entries
                 .into_iter()
@@ -135,12 +278,42 @@ pub(crate) fn insert_capture(
             }
         })
         .unwrap_or_else(|| extract_styled_section(source_file_path, heading_order, &content_value));
-    append_capture_to_docx(
+    // The marker rides on the block's heading so `under_marker` can find it after edits.
+    let target_document_xml = capture_path
+        .is_file()
+        .then(|| {
+            read_docx_part(&capture_path, "word/document.xml")
+                .ok()
+                .flatten()
+        })
+        .flatten()
+        .unwrap_or_default();
+    let bookmark_id = next_bookmark_id(
+        std::iter::once(target_document_xml.as_str())
+            .chain(styled_section.paragraph_xml.iter().map(String::as_str)),
+    );
+    if let Some(first) = styled_section.paragraph_xml.first_mut() {
+        *first = paragraph_xml_with_bookmark(first, bookmark_id, &capture_marker(capture_id));
+    }
+    if with_citation_header {
+        if let Some(citation) =
+            capture_citation_line(source_file_path, heading_order, &content_value)
+        {
+            // Under the heading, so the citation stays inside the block when it is moved or deleted.
+            let after_heading = styled_section.paragraph_xml.len().min(1);
+            styled_section
+                .paragraph_xml
+                .insert(after_heading, paragraph_xml_citation(&citation));
+        }
+    }
+    let banner_text = load_capture_settings(&app).banner_text;
+    insert_section_into_docx(
         &capture_path,
         source_file_path,
         normalized_heading_level,
         normalized_target_heading_order,
         &styled_section,
+        banner_text.as_deref(),
     )?;
 
     Ok(CaptureInsertResult {
@@ -150,17 +323,119 @@ pub(crate) fn insert_capture(
     })
 }
 
+// Prefers the author line inside the source heading's section, then one in the raw content.
+fn capture_citation_line(
+    source_path: &Path,
+    heading_order: Option<i64>,
+    content: &str,
+) -> Option<String> {
+    let section_author = heading_order.and_then(|order| {
+        let paragraphs = parse_source_paragraphs(source_path).ok()?;
+        let range = build_heading_ranges(&paragraphs)
+            .into_iter()
+            .find(|range| range.order == order)?;
+        paragraphs
+            .get(range.start_index + 1..range.end_index)?
+            .iter()
+            .map(|paragraph| paragraph.text.trim())
+            .find(|text| is_probable_author_line(text))
+            .map(str::to_string)
+    });
+    let author = section_author.or_else(|| {
+        content
+            .lines()
+            .map(str::trim)
+            .find(|line| is_probable_author_line(line))
+            .map(str::to_string)
+    })?;
+    Some(debate_citation_line(&author))
+}
+
+struct MarkerHeading {
+    target_relative_path: String,
+    heading_order: i64,
+    level: i64,
+}
+
+fn resolve_marker_heading(
+    connection: &Connection,
+    canonical_root: &Path,
+    root_id: i64,
+    marker: &str,
+) -> CommandResult<MarkerHeading> {
+    let capture_id = parse_capture_marker(marker)?;
+    let target_relative_path = connection
+        .query_row(
+            "SELECT target_relative_path FROM captures WHERE id = ?1 AND root_id = ?2",
+            params![capture_id, root_id],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|error| format!("Could not load capture {marker}: {error}"))?
+        .ok_or_else(|| format!("Capture {marker} was not found in this root."))?;
+
+    let capture_path = capture_docx_path(canonical_root, &target_relative_path);
+    let paragraphs = parse_docx_paragraphs(&capture_path)?;
+    let (heading_order, level) =
+        bookmarked_paragraph_index(&capture_path, &capture_marker(capture_id))?
+            .and_then(|index| paragraphs.get(index))
+            .and_then(|paragraph| Some((paragraph.order, paragraph.heading_level?)))
+            .ok_or_else(|| {
+                format!(
+                    "Could not find the heading for capture {marker} in '{target_relative_path}'."
+                )
+            })?;
+
+    Ok(MarkerHeading {
+        target_relative_path,
+        heading_order,
+        level,
+    })
+}
+
+pub(crate) fn preview_capture(
+    _app: AppHandle,
+    source_path: String,
+    heading_order: Option<i64>,
+    content: String,
+) -> CommandResult<CapturePreview> {
+    if content.trim().is_empty() {
+        return Err("Cannot preview empty capture content.".to_string());
+    }
+
+    let styled_section = extract_styled_section(Path::new(&source_path), heading_order, &content);
+    let mut style_ids = styled_section
+        .style_ids
+        .into_iter()
+        .collect::<Vec<String>>();
+    style_ids.sort();
+    let mut relationship_ids = styled_section
+        .relationship_ids
+        .into_iter()
+        .collect::<Vec<String>>();
+    relationship_ids.sort();
+
+    Ok(CapturePreview {
+        paragraph_xml: styled_section.paragraph_xml,
+        used_source_xml: styled_section.used_source_xml,
+        style_ids,
+        relationship_ids,
+    })
+}
+
 pub(crate) fn list_capture_targets(
     app: AppHandle,
     root_path: String,
+    sort: Option<CaptureTargetSort>,
 ) -> CommandResult<Vec<CaptureTarget>> {
     let canonical_root = canonicalize_folder(&root_path)?;
     let root_path_string = path_display(&canonical_root);
     let connection = open_database(&app)?;
     let root_id = add_or_get_root_id(&connection, &root_path_string)?;
 
+    let default_target = default_capture_target(&app);
     let mut by_target = HashMap::<String, i64>::new();
-    by_target.insert(DEFAULT_CAPTURE_TARGET.to_string(), 0);
+    by_target.insert(default_target.clone(), 0);
 
     let mut statement = connection
         .prepare(
@@ -190,24 +465,316 @@ pub(crate) fn list_capture_targets(
         .into_iter()
         .map(|(relative_path, entry_count)| {
             let absolute_path = capture_docx_path(&canonical_root, &relative_path);
+            let modified_ms = fs::metadata(&absolute_path)
+                .and_then(|metadata| metadata.modified())
+                .map(epoch_ms)
+                .unwrap_or(0);
             CaptureTarget {
                 relative_path,
                 absolute_path: path_display(&absolute_path),
                 exists: absolute_path.is_file(),
                 entry_count,
+                modified_ms,
             }
         })
         .collect::<Vec<CaptureTarget>>();
 
-    targets.sort_by(|left, right| {
-        (left.relative_path != DEFAULT_CAPTURE_TARGET)
-            .cmp(&(right.relative_path != DEFAULT_CAPTURE_TARGET))
-            .then(left.relative_path.cmp(&right.relative_path))
-    });
+    match sort.unwrap_or_default() {
+        CaptureTargetSort::Name => targets.sort_by(|left, right| {
+            (left.relative_path != default_target)
+                .cmp(&(right.relative_path != default_target))
+                .then(left.relative_path.cmp(&right.relative_path))
+        }),
+        CaptureTargetSort::Modified => targets.sort_by(|left, right| {
+            right
+                .modified_ms
+                .cmp(&left.modified_ms)
+                .then(left.relative_path.cmp(&right.relative_path))
+        }),
+    }
 
     Ok(targets)
 }
 
+pub(crate) fn resolve_capture_marker(
+    app: AppHandle,
+    marker: String,
+) -> CommandResult<ResolvedCaptureMarker> {
+    let capture_id = parse_capture_marker(&marker)?;
+    let marker = capture_marker(capture_id);
+    let connection = open_database(&app)?;
+    let (root_path, source_path, target_relative_path, section_title, created_at_ms) = connection
+        .query_row(
+            "
+            SELECT r.path, c.source_path, c.target_relative_path, c.section_title, c.created_at_ms
+            FROM captures c
+            JOIN roots r ON r.id = c.root_id
+            WHERE c.id = ?1
+            ",
+            params![capture_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|error| format!("Could not load capture {marker}: {error}"))?
+        .ok_or_else(|| format!("Capture {marker} was not found."))?;
+
+    let target_path = capture_docx_path(Path::new(&root_path), &target_relative_path);
+    Ok(ResolvedCaptureMarker {
+        marker,
+        root_path,
+        source_path,
+        target_relative_path,
+        target_path: path_display(&target_path),
+        target_exists: target_path.is_file(),
+        section_title,
+        created_at_ms,
+    })
+}
+
+const CAPTURE_EXPORT_CSV_HEADER: &str =
+    "id,marker,source_path,section_title,target_relative_path,heading_level,created_at_ms";
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn load_capture_records(
+    connection: &Connection,
+    root_id: i64,
+) -> CommandResult<Vec<CaptureRecord>> {
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT id, source_path, section_title, target_relative_path, heading_level, created_at_ms
+            FROM captures
+            WHERE root_id = ?1
+            ORDER BY id ASC
+            ",
+        )
+        .map_err(|error| format!("Could not prepare capture export query: {error}"))?;
+    let rows = statement
+        .query_map(params![root_id], |row| {
+            let id = row.get::<_, i64>(0)?;
+            Ok(CaptureRecord {
+                id,
+                marker: capture_marker(id),
+                source_path: row.get(1)?,
+                section_title: row.get(2)?,
+                target_relative_path: row.get(3)?,
+                heading_level: row.get(4)?,
+                created_at_ms: row.get(5)?,
+            })
+        })
+        .map_err(|error| format!("Could not iterate capture export query: {error}"))?;
+    rows.collect::<Result<Vec<CaptureRecord>, _>>()
+        .map_err(|error| format!("Could not parse capture export row: {error}"))
+}
+
+pub(crate) fn export_captures(
+    app: AppHandle,
+    root_path: String,
+    format: Option<String>,
+) -> CommandResult<String> {
+    let format = format
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| "json".to_string());
+    if format != "json" && format != "csv" {
+        return Err(format!(
+            "Unknown capture export format '{format}'; expected 'json' or 'csv'."
+        ));
+    }
+
+    let canonical_path = canonicalize_folder(&root_path)
+        .map(|canonical| path_display(&canonical))
+        .unwrap_or(root_path);
+    let connection = open_database(&app)?;
+    let records = match root_id(&connection, &canonical_path)? {
+        Some(root_id) => load_capture_records(&connection, root_id)?,
+        None => Vec::new(),
+    };
+
+    if format == "json" {
+        return serde_json::to_string_pretty(&records)
+            .map_err(|error| format!("Could not serialize capture export: {error}"));
+    }
+
+    let mut csv = String::from(CAPTURE_EXPORT_CSV_HEADER);
+    csv.push('\n');
+    for record in &records {
+        let heading_level = record
+            .heading_level
+            .map(|level| level.to_string())
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            record.id,
+            record.marker,
+            csv_field(&record.source_path),
+            csv_field(&record.section_title),
+            csv_field(&record.target_relative_path),
+            heading_level,
+            record.created_at_ms
+        ));
+    }
+    Ok(csv)
+}
+
+pub(crate) fn delete_capture_target(
+    app: AppHandle,
+    root_path: String,
+    target_path: String,
+    confirm: Option<bool>,
+) -> CommandResult<Vec<CaptureTarget>> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let default_target = default_capture_target(&app);
+    if normalized_target == default_target && !confirm.unwrap_or(false) {
+        return Err(format!(
+            "Refusing to delete the default capture target '{default_target}' without confirmation."
+        ));
+    }
+
+    let capture_path = capture_docx_path(&canonical_root, &normalized_target);
+    if !capture_path.starts_with(&canonical_root) {
+        return Err(format!(
+            "Capture target '{}' is outside the root folder.",
+            normalized_target
+        ));
+    }
+
+    let root_path_string = path_display(&canonical_root);
+    let mut connection = open_database(&app)?;
+    let root_id = add_or_get_root_id(&connection, &root_path_string)?;
+    let entry_count = connection
+        .query_row(
+            "SELECT COUNT(*) FROM captures WHERE root_id = ?1 AND target_relative_path = ?2",
+            params![root_id, normalized_target],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|error| format!("Could not count capture target entries: {error}"))?;
+    if !capture_path.is_file() && entry_count == 0 {
+        return Err(format!(
+            "Could not find capture target '{normalized_target}'."
+        ));
+    }
+
+    let transaction = begin_write_transaction(&mut connection)
+        .map_err(|error| format!("Could not start capture target delete: {error}"))?;
+    for table in ["captures", "capture_trash"] {
+        transaction
+            .execute(
+                &format!("DELETE FROM {table} WHERE root_id = ?1 AND target_relative_path = ?2"),
+                params![root_id, normalized_target],
+            )
+            .map_err(|error| format!("Could not delete {table} target entries: {error}"))?;
+    }
+    if capture_path.is_file() {
+        fs::remove_file(&capture_path).map_err(|error| {
+            format!(
+                "Could not delete capture target '{}': {error}",
+                path_display(&capture_path)
+            )
+        })?;
+    }
+    transaction
+        .commit()
+        .map_err(|error| format!("Could not commit capture target delete: {error}"))?;
+
+    list_capture_targets(app, root_path_string, None)
+}
+
+pub(crate) fn rename_capture_target(
+    app: AppHandle,
+    root_path: String,
+    old_target: String,
+    new_target: String,
+) -> CommandResult<Vec<CaptureTarget>> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let old_normalized = normalize_capture_target_path(Some(&old_target))?;
+    let new_normalized = normalize_capture_target_path(Some(&new_target))?;
+    if old_normalized == new_normalized {
+        return Err("Capture target already has that path.".to_string());
+    }
+
+    let old_path = capture_docx_path(&canonical_root, &old_normalized);
+    let new_path = capture_docx_path(&canonical_root, &new_normalized);
+    for (normalized, path) in [(&old_normalized, &old_path), (&new_normalized, &new_path)] {
+        if !path.starts_with(&canonical_root) {
+            return Err(format!(
+                "Capture target '{normalized}' is outside the root folder."
+            ));
+        }
+    }
+    if !old_path.is_file() {
+        return Err(format!(
+            "Capture target '{}' does not exist.",
+            path_display(&old_path)
+        ));
+    }
+    if new_path.exists() {
+        return Err(format!(
+            "Refusing to overwrite existing file '{}'.",
+            path_display(&new_path)
+        ));
+    }
+
+    let root_path_string = path_display(&canonical_root);
+    let mut connection = open_database(&app)?;
+    let root_id = add_or_get_root_id(&connection, &root_path_string)?;
+    let transaction = begin_write_transaction(&mut connection)
+        .map_err(|error| format!("Could not start capture target rename: {error}"))?;
+    for table in ["captures", "capture_trash"] {
+        transaction
+            .execute(
+                &format!(
+                    "UPDATE {table} SET target_relative_path = ?3 WHERE root_id = ?1 AND target_relative_path = ?2"
+                ),
+                params![root_id, old_normalized, new_normalized],
+            )
+            .map_err(|error| format!("Could not update {table} target paths: {error}"))?;
+    }
+
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent).map_err(|error| {
+            format!(
+                "Could not create folder '{}': {error}",
+                path_display(parent)
+            )
+        })?;
+    }
+    fs::rename(&old_path, &new_path).map_err(|error| {
+        format!(
+            "Could not move capture target '{}' to '{}': {error}",
+            path_display(&old_path),
+            path_display(&new_path)
+        )
+    })?;
+    if let Err(error) = transaction.commit() {
+        // Put the file back so it still matches the rows that were not updated.
+        if let Err(restore_error) = fs::rename(&new_path, &old_path) {
+            logging::error(&format!(
+                "Could not restore capture target '{}': {restore_error}",
+                path_display(&old_path)
+            ));
+        }
+        return Err(format!("Could not commit capture target rename: {error}"));
+    }
+
+    list_capture_targets(app, root_path_string, None)
+}
+
 fn capture_target_preview_for_path(
     canonical_root: &Path,
     normalized_target: &str,
@@ -224,7 +791,7 @@ fn capture_target_preview_for_path(
         };
     }
 
-    let (mut headings, _) = extract_preview_content(&absolute_path).unwrap_or_default();
+    let (mut headings, _, _) = extract_preview_content(&absolute_path).unwrap_or_default();
     headings.sort_by(|left, right| left.order.cmp(&right.order));
 
     CaptureTargetPreview {
@@ -240,21 +807,57 @@ pub(crate) fn get_capture_target_preview(
     _app: AppHandle,
     root_path: String,
     target_path: String,
+    with_anchors: Option<bool>,
 ) -> CommandResult<CaptureTargetPreview> {
     let canonical_root = canonicalize_folder(&root_path)?;
     let normalized_target = normalize_capture_target_path(Some(&target_path))?;
-    Ok(capture_target_preview_for_path(
-        &canonical_root,
-        &normalized_target,
-    ))
+    let mut preview = capture_target_preview_for_path(&canonical_root, &normalized_target);
+    if with_anchors.unwrap_or(false) {
+        for heading in &mut preview.headings {
+            heading.anchor_id = Some(heading_anchor_id(heading.order, &heading.text));
+        }
+    }
+    Ok(preview)
 }
 
-pub(crate) fn delete_capture_heading(
+pub(crate) fn capture_target_stats(
     _app: AppHandle,
     root_path: String,
     target_path: String,
-    heading_order: i64,
-) -> CommandResult<CaptureTargetPreview> {
+) -> CommandResult<CaptureTargetStats> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
+    let mut stats = CaptureTargetStats {
+        relative_path: normalized_target,
+        exists: absolute_path.is_file(),
+        heading_count: 0,
+        paragraph_count: 0,
+        word_count: 0,
+    };
+    if !stats.exists {
+        return Ok(stats);
+    }
+
+    for paragraph in parse_docx_paragraphs(&absolute_path)? {
+        let words = paragraph.text.split_whitespace().count();
+        if words == 0 {
+            continue;
+        }
+        if paragraph.heading_level.is_some() {
+            stats.heading_count += 1;
+        }
+        stats.paragraph_count += 1;
+        stats.word_count += i64::try_from(words).unwrap_or(0);
+    }
+    Ok(stats)
+}
+
+pub(crate) fn export_capture_html(
+    _app: AppHandle,
+    root_path: String,
+    target_path: String,
+) -> CommandResult<String> {
     let canonical_root = canonicalize_folder(&root_path)?;
     let normalized_target = normalize_capture_target_path(Some(&target_path))?;
     let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
@@ -266,25 +869,116 @@ pub(crate) fn delete_capture_heading(
         ));
     }
 
-    ensure_valid_capture_docx(&absolute_path)?;
-    let paragraphs = parse_docx_paragraphs(&absolute_path)?;
-    let heading_ranges = build_heading_ranges(&paragraphs);
-    let target_range = heading_ranges
-        .iter()
-        .find(|range| range.order == heading_order)
-        .cloned()
-        .ok_or_else(|| format!("Heading order {heading_order} not found in target document."))?;
+    render_document_html(&absolute_path, &normalized_target)
+}
 
-    let document_xml = read_docx_part(&absolute_path, "word/document.xml")?.ok_or_else(|| {
-        format!(
-            "Missing word/document.xml in '{}'",
-            path_display(&absolute_path)
-        )
-    })?;
-    let document = Document::parse(&document_xml).map_err(|error| {
-        format!(
-            "Could not parse destination document XML '{}': {error}",
+pub(crate) fn delete_capture_heading(
+    app: AppHandle,
+    root_path: String,
+    target_path: String,
+    heading_order: i64,
+) -> CommandResult<CaptureTargetPreview> {
+    delete_capture_headings(app, root_path, target_path, vec![heading_order])
+}
+
+pub(crate) fn delete_capture_headings(
+    app: AppHandle,
+    root_path: String,
+    target_path: String,
+    heading_orders: Vec<i64>,
+) -> CommandResult<CaptureTargetPreview> {
+    if heading_orders.is_empty() {
+        return Err("No headings selected for deletion.".to_string());
+    }
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
+
+    if !absolute_path.is_file() {
+        return Err(format!(
+            "Target capture file does not exist: {}",
             path_display(&absolute_path)
+        ));
+    }
+
+    ensure_valid_capture_docx(&absolute_path)?;
+    let removal = cut_capture_headings(&absolute_path, heading_orders)?;
+
+    let mut connection = open_database(&app)?;
+    let root_id = add_or_get_root_id(&connection, &path_display(&canonical_root))?;
+    let deleted_at_ms = now_ms();
+    let transaction = begin_write_transaction(&mut connection)
+        .map_err(|error| format!("Could not start trash transaction: {error}"))?;
+    for removed in &removal.removed {
+        transaction
+            .execute(
+                "INSERT INTO capture_trash(root_id, target_relative_path, heading_level, heading_text, fragment_xml, deleted_at_ms)
+                 VALUES(?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    root_id,
+                    normalized_target,
+                    removed.level,
+                    removed.text,
+                    removed.fragment_xml,
+                    deleted_at_ms
+                ],
+            )
+            .map_err(|error| format!("Could not move heading to trash: {error}"))?;
+    }
+    // Dropping the transaction on a failed rewrite rolls the trash rows back.
+    write_document_xml(&absolute_path, removal.document_xml)?;
+    transaction
+        .commit()
+        .map_err(|error| format!("Could not commit trash transaction: {error}"))?;
+
+    Ok(capture_target_preview_for_path(
+        &canonical_root,
+        &normalized_target,
+    ))
+}
+
+struct RemovedHeading {
+    level: i64,
+    text: String,
+    fragment_xml: String,
+}
+
+struct HeadingRemoval {
+    removed: Vec<RemovedHeading>,
+    document_xml: String,
+}
+
+// Cuts the sections of `heading_orders` out of the document XML without
+// writing it back, so callers decide what happens to the removed fragments.
+fn cut_capture_headings(
+    absolute_path: &Path,
+    heading_orders: Vec<i64>,
+) -> CommandResult<HeadingRemoval> {
+    let paragraphs = parse_docx_paragraphs(absolute_path)?;
+    let heading_ranges = build_heading_ranges(&paragraphs);
+    let mut target_ranges = Vec::with_capacity(heading_orders.len());
+    for heading_order in heading_orders {
+        let target_range = heading_ranges
+            .iter()
+            .find(|range| range.order == heading_order)
+            .cloned()
+            .ok_or_else(|| {
+                format!("Heading order {heading_order} not found in target document.")
+            })?;
+        target_ranges.push(target_range);
+    }
+    target_ranges.sort_by_key(|range| range.start_index);
+
+    let document_xml = read_docx_part(absolute_path, "word/document.xml")?.ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'",
+            path_display(absolute_path)
+        )
+    })?;
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse destination document XML '{}': {error}",
+            path_display(absolute_path)
         )
     })?;
     let paragraph_nodes = document
@@ -292,23 +986,143 @@ pub(crate) fn delete_capture_heading(
         .filter(|node| has_tag(*node, "p"))
         .collect::<Vec<Node<'_, '_>>>();
 
-    if target_range.start_index >= paragraph_nodes.len()
-        || target_range.end_index == 0
-        || target_range.end_index > paragraph_nodes.len()
-    {
-        return Err("Heading range is out of bounds in destination document.".to_string());
+    // All ranges come from the original document. A heading nested inside an
+    // earlier selected range is removed along with it.
+    let mut removals = Vec::<(HeadingRange, usize, usize)>::with_capacity(target_ranges.len());
+    for target_range in target_ranges {
+        if removals
+            .last()
+            .is_some_and(|(previous, _, _)| target_range.start_index < previous.end_index)
+        {
+            continue;
+        }
+        if target_range.start_index >= paragraph_nodes.len()
+            || target_range.end_index == 0
+            || target_range.end_index > paragraph_nodes.len()
+        {
+            return Err("Heading range is out of bounds in destination document.".to_string());
+        }
+
+        let start = paragraph_nodes[target_range.start_index].range().start;
+        let end = paragraph_nodes[target_range.end_index - 1].range().end;
+        if start >= end || end > document_xml.len() {
+            return Err("Could not resolve heading XML range in destination document.".to_string());
+        }
+        removals.push((target_range, start, end));
     }
 
-    let start = paragraph_nodes[target_range.start_index].range().start;
-    let end = paragraph_nodes[target_range.end_index - 1].range().end;
-    if start >= end || end > document_xml.len() {
-        return Err("Could not resolve heading XML range in destination document.".to_string());
+    let removed = removals
+        .iter()
+        .map(|(target_range, start, end)| RemovedHeading {
+            level: target_range.level,
+            text: paragraphs
+                .get(target_range.start_index)
+                .map(|paragraph| paragraph.text.clone())
+                .unwrap_or_default(),
+            fragment_xml: document_xml[*start..*end].to_string(),
+        })
+        .collect();
+
+    // Removing from the highest offset down keeps the earlier offsets valid.
+    let mut updated_document_xml = document_xml.clone();
+    for (_, start, end) in removals.iter().rev() {
+        updated_document_xml.replace_range(*start..*end, "");
     }
 
-    let mut updated_document_xml =
-        String::with_capacity(document_xml.len().saturating_sub(end.saturating_sub(start)));
-    updated_document_xml.push_str(&document_xml[..start]);
-    updated_document_xml.push_str(&document_xml[end..]);
+    Ok(HeadingRemoval {
+        removed,
+        document_xml: updated_document_xml,
+    })
+}
+
+fn write_document_xml(absolute_path: &Path, document_xml: String) -> CommandResult<()> {
+    let mut replacements = HashMap::new();
+    replacements.insert("word/document.xml".to_string(), document_xml.into_bytes());
+    rewrite_docx_with_parts(absolute_path, &replacements)
+}
+
+pub(crate) fn list_trash(
+    app: AppHandle,
+    root_path: String,
+    target_path: Option<String>,
+) -> CommandResult<Vec<TrashedHeading>> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = target_path
+        .as_deref()
+        .map(|target| normalize_capture_target_path(Some(target)))
+        .transpose()?;
+    let connection = open_database(&app)?;
+    let root_id = add_or_get_root_id(&connection, &path_display(&canonical_root))?;
+
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT id, target_relative_path, heading_level, heading_text, deleted_at_ms
+            FROM capture_trash
+            WHERE root_id = ?1 AND (?2 IS NULL OR target_relative_path = ?2)
+            ORDER BY deleted_at_ms DESC, id DESC
+            ",
+        )
+        .map_err(|error| format!("Could not prepare trash query: {error}"))?;
+
+    let rows = statement
+        .query_map(params![root_id, normalized_target], |row| {
+            Ok(TrashedHeading {
+                id: row.get(0)?,
+                target_relative_path: row.get(1)?,
+                heading_level: row.get(2)?,
+                heading_text: row.get(3)?,
+                deleted_at_ms: row.get(4)?,
+            })
+        })
+        .map_err(|error| format!("Could not iterate trash query: {error}"))?;
+
+    let mut trashed = Vec::new();
+    for row in rows {
+        trashed.push(row.map_err(|error| format!("Could not parse trash row: {error}"))?);
+    }
+    Ok(trashed)
+}
+
+pub(crate) fn restore_capture_heading(
+    app: AppHandle,
+    root_path: String,
+    target_path: String,
+    trash_id: i64,
+) -> CommandResult<CaptureTargetPreview> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
+
+    if !absolute_path.is_file() {
+        return Err(format!(
+            "Target capture file does not exist: {}",
+            path_display(&absolute_path)
+        ));
+    }
+
+    let connection = open_database(&app)?;
+    let root_id = add_or_get_root_id(&connection, &path_display(&canonical_root))?;
+    let fragment_xml = connection
+        .query_row(
+            "SELECT fragment_xml FROM capture_trash
+             WHERE id = ?1 AND root_id = ?2 AND target_relative_path = ?3",
+            params![trash_id, root_id, normalized_target],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|error| format!("Could not load trashed heading {trash_id}: {error}"))?
+        .ok_or_else(|| format!("Trashed heading {trash_id} not found for target document."))?;
+
+    ensure_valid_capture_docx(&absolute_path)?;
+    let document_xml = read_docx_part(&absolute_path, "word/document.xml")?.ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'",
+            path_display(&absolute_path)
+        )
+    })?;
+    let updated_document_xml =
+        insert_fragment_into_document_xml(&document_xml, &fragment_xml, None)?;
 
     let mut replacements = HashMap::new();
     replacements.insert(
@@ -317,12 +1131,39 @@ pub(crate) fn delete_capture_heading(
     );
     rewrite_docx_with_parts(&absolute_path, &replacements)?;
 
+    retry_busy(|| connection.execute("DELETE FROM capture_trash WHERE id = ?1", params![trash_id]))
+        .map_err(|error| format!("Could not remove restored heading from trash: {error}"))?;
+
     Ok(capture_target_preview_for_path(
         &canonical_root,
         &normalized_target,
     ))
 }
 
+pub(crate) fn repair_capture_target(
+    _app: AppHandle,
+    root_path: String,
+    target_path: String,
+) -> CommandResult<CaptureRepairReport> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
+
+    if !absolute_path.is_file() {
+        return Err(format!(
+            "Target capture file does not exist: {}",
+            path_display(&absolute_path)
+        ));
+    }
+
+    let repaired_parts = repair_capture_docx(&absolute_path)?;
+    Ok(CaptureRepairReport {
+        target_relative_path: normalized_target,
+        repaired: !repaired_parts.is_empty(),
+        repaired_parts,
+    })
+}
+
 pub(crate) fn move_capture_heading(
     _app: AppHandle,
     root_path: String,
@@ -348,8 +1189,65 @@ pub(crate) fn move_capture_heading(
         ));
     }
 
-    ensure_valid_capture_docx(&absolute_path)?;
-    let paragraphs = parse_docx_paragraphs(&absolute_path)?;
+    let (_, source_range, insert_before_index) =
+        plan_capture_heading_move(&absolute_path, source_heading_order, target_heading_order)?;
+    move_heading_block(&absolute_path, &source_range, insert_before_index)?;
+
+    Ok(capture_target_preview_for_path(
+        &canonical_root,
+        &normalized_target,
+    ))
+}
+
+// Dry run of move_capture_heading: applies the same block move to the parsed paragraphs and
+// returns the headings the target would have, leaving the file untouched.
+pub(crate) fn preview_move_capture_heading(
+    _app: AppHandle,
+    root_path: String,
+    target_path: String,
+    source_heading_order: i64,
+    target_heading_order: i64,
+) -> CommandResult<Vec<FileHeading>> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
+
+    if source_heading_order == target_heading_order {
+        return Ok(capture_target_preview_for_path(&canonical_root, &normalized_target).headings);
+    }
+
+    if !absolute_path.is_file() {
+        return Err(format!(
+            "Target capture file does not exist: {}",
+            path_display(&absolute_path)
+        ));
+    }
+
+    let (mut paragraphs, source_range, insert_before_index) =
+        plan_capture_heading_move(&absolute_path, source_heading_order, target_heading_order)?;
+    let moved = paragraphs
+        .drain(source_range.start_index..source_range.end_index)
+        .collect::<Vec<ParsedParagraph>>();
+    let insertion_index = if source_range.start_index < insert_before_index {
+        insert_before_index.saturating_sub(moved.len())
+    } else {
+        insert_before_index
+    };
+    paragraphs.splice(insertion_index..insertion_index, moved);
+    for (index, paragraph) in paragraphs.iter_mut().enumerate() {
+        paragraph.order = i64::try_from(index + 1).unwrap_or(i64::MAX);
+    }
+
+    Ok(preview_headings(&paragraphs))
+}
+
+fn plan_capture_heading_move(
+    absolute_path: &Path,
+    source_heading_order: i64,
+    target_heading_order: i64,
+) -> CommandResult<(Vec<ParsedParagraph>, HeadingRange, usize)> {
+    ensure_valid_capture_docx(absolute_path)?;
+    let paragraphs = parse_docx_paragraphs(absolute_path)?;
     let heading_ranges = build_heading_ranges(&paragraphs);
 
     let source_range = heading_ranges
@@ -373,16 +1271,24 @@ pub(crate) fn move_capture_heading(
         return Err("Cannot move a heading into its own subtree.".to_string());
     }
 
-    let document_xml = read_docx_part(&absolute_path, "word/document.xml")?.ok_or_else(|| {
+    Ok((paragraphs, source_range, target_range.end_index))
+}
+
+fn move_heading_block(
+    absolute_path: &Path,
+    source_range: &HeadingRange,
+    insert_before_index: usize,
+) -> CommandResult<()> {
+    let document_xml = read_docx_part(absolute_path, "word/document.xml")?.ok_or_else(|| {
         format!(
             "Missing word/document.xml in '{}'",
-            path_display(&absolute_path)
+            path_display(absolute_path)
         )
     })?;
     let document = Document::parse(&document_xml).map_err(|error| {
         format!(
             "Could not parse destination document XML '{}': {error}",
-            path_display(&absolute_path)
+            path_display(absolute_path)
         )
     })?;
     let paragraph_nodes = document
@@ -393,9 +1299,7 @@ pub(crate) fn move_capture_heading(
     if source_range.start_index >= paragraph_nodes.len()
         || source_range.end_index == 0
         || source_range.end_index > paragraph_nodes.len()
-        || target_range.start_index >= paragraph_nodes.len()
-        || target_range.end_index == 0
-        || target_range.end_index > paragraph_nodes.len()
+        || insert_before_index > paragraph_nodes.len()
     {
         return Err("Heading range is out of bounds in destination document.".to_string());
     }
@@ -415,8 +1319,8 @@ pub(crate) fn move_capture_heading(
     let source_len = source_range
         .end_index
         .saturating_sub(source_range.start_index);
-    let mut insertion_paragraph_count = target_range.end_index;
-    if source_range.start_index < target_range.end_index {
+    let mut insertion_paragraph_count = insert_before_index;
+    if source_range.start_index < insert_before_index {
         insertion_paragraph_count = insertion_paragraph_count.saturating_sub(source_len);
     }
 
@@ -435,7 +1339,71 @@ pub(crate) fn move_capture_heading(
         "word/document.xml".to_string(),
         updated_document_xml.into_bytes(),
     );
-    rewrite_docx_with_parts(&absolute_path, &replacements)?;
+    rewrite_docx_with_parts(absolute_path, &replacements)
+}
+
+fn heading_parent_index(ranges: &[HeadingRange], index: usize) -> Option<usize> {
+    let range = &ranges[index];
+    ranges
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| {
+            candidate.level < range.level
+                && candidate.start_index < range.start_index
+                && range.start_index < candidate.end_index
+        })
+        .map(|(candidate_index, _)| candidate_index)
+        .last()
+}
+
+pub(crate) fn reorder_capture_heading(
+    _app: AppHandle,
+    root_path: String,
+    target_path: String,
+    heading_order: i64,
+    new_sibling_index: usize,
+) -> CommandResult<CaptureTargetPreview> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_target = normalize_capture_target_path(Some(&target_path))?;
+    let absolute_path = capture_docx_path(&canonical_root, &normalized_target);
+
+    if !absolute_path.is_file() {
+        return Err(format!(
+            "Target capture file does not exist: {}",
+            path_display(&absolute_path)
+        ));
+    }
+
+    ensure_valid_capture_docx(&absolute_path)?;
+    let paragraphs = parse_docx_paragraphs(&absolute_path)?;
+    let heading_ranges = build_heading_ranges(&paragraphs);
+    let source_index = heading_ranges
+        .iter()
+        .position(|range| range.order == heading_order)
+        .ok_or_else(|| format!("Heading order {heading_order} not found in target document."))?;
+    let source_range = heading_ranges[source_index].clone();
+    let parent = heading_parent_index(&heading_ranges, source_index);
+
+    let siblings = (0..heading_ranges.len())
+        .filter(|index| *index != source_index)
+        .filter(|index| heading_ranges[*index].level == source_range.level)
+        .filter(|index| heading_parent_index(&heading_ranges, *index) == parent)
+        .map(|index| &heading_ranges[index])
+        .collect::<Vec<&HeadingRange>>();
+
+    let insert_before_index = match siblings.get(new_sibling_index) {
+        Some(sibling) => sibling.start_index,
+        None => match siblings.last() {
+            Some(last) => last.end_index,
+            None => source_range.start_index,
+        },
+    };
+
+    if insert_before_index < source_range.start_index
+        || insert_before_index > source_range.end_index
+    {
+        move_heading_block(&absolute_path, &source_range, insert_before_index)?;
+    }
 
     Ok(capture_target_preview_for_path(
         &canonical_root,
@@ -443,16 +1411,105 @@ pub(crate) fn move_capture_heading(
     ))
 }
 
+pub(crate) fn move_heading_across_targets(
+    app: AppHandle,
+    root_path: String,
+    source_target: String,
+    source_heading_order: i64,
+    dest_target: String,
+    dest_heading_order: Option<i64>,
+) -> CommandResult<CaptureHeadingTransfer> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let normalized_source = normalize_capture_target_path(Some(&source_target))?;
+    let normalized_dest = normalize_capture_target_path(Some(&dest_target))?;
+    if normalized_source == normalized_dest {
+        return Err(
+            "Source and destination targets are the same. Use move_capture_heading instead."
+                .to_string(),
+        );
+    }
+
+    let source_path = capture_docx_path(&canonical_root, &normalized_source);
+    let dest_path = capture_docx_path(&canonical_root, &normalized_dest);
+    if !source_path.is_file() {
+        return Err(format!(
+            "Source capture file does not exist: {}",
+            path_display(&source_path)
+        ));
+    }
+
+    // The source is only read here; an unreadable source fails the move instead of being
+    // reinitialized.
+    let heading_level = parse_docx_paragraphs(&source_path)?
+        .iter()
+        .find(|paragraph| paragraph.order == source_heading_order)
+        .and_then(|paragraph| paragraph.heading_level)
+        .ok_or_else(|| {
+            format!("Source heading order {source_heading_order} not found in source document.")
+        })?;
+
+    let styled_section = extract_styled_section(&source_path, Some(source_heading_order), "");
+    if !styled_section.used_source_xml {
+        return Err("Could not resolve source heading XML range.".to_string());
+    }
+    // A moved block lives on in the destination, so it skips the trash.
+    let removal = cut_capture_headings(&source_path, vec![source_heading_order])?;
+
+    let original_dest = if dest_path.is_file() {
+        Some(fs::read(&dest_path).map_err(|error| {
+            format!(
+                "Could not read destination capture file '{}': {error}",
+                path_display(&dest_path)
+            )
+        })?)
+    } else {
+        None
+    };
+    let banner_text = load_capture_settings(&app).banner_text;
+    insert_section_into_docx(
+        &dest_path,
+        &source_path,
+        Some(heading_level),
+        dest_heading_order.filter(|value| *value > 0),
+        &styled_section,
+        banner_text.as_deref(),
+    )?;
+
+    if let Err(error) = write_document_xml(&source_path, removal.document_xml) {
+        // Puts the destination back so the block never ends up in both files.
+        let restored = match &original_dest {
+            Some(bytes) => fs::write(&dest_path, bytes),
+            None => fs::remove_file(&dest_path),
+        };
+        if let Err(restore_error) = restored {
+            logging::error(&format!(
+                "Could not restore destination capture file '{}': {restore_error}",
+                path_display(&dest_path)
+            ));
+        }
+        return Err(error);
+    }
+
+    Ok(CaptureHeadingTransfer {
+        source: capture_target_preview_for_path(&canonical_root, &normalized_source),
+        destination: capture_target_preview_for_path(&canonical_root, &normalized_dest),
+    })
+}
+
+const MAX_CAPTURE_HEADING_LEVEL: i64 = 6;
+
 pub(crate) fn add_capture_heading(
-    _app: AppHandle,
+    app: AppHandle,
     root_path: String,
     target_path: String,
     heading_level: i64,
     heading_text: String,
     selected_target_heading_order: Option<i64>,
 ) -> CommandResult<CaptureTargetPreview> {
-    if !(1..=4).contains(&heading_level) {
-        return Err("Heading level must be H1, H2, H3, or H4.".to_string());
+    if !(1..=MAX_CAPTURE_HEADING_LEVEL).contains(&heading_level) {
+        return Err(format!(
+            "Heading level must be between H1 and H{MAX_CAPTURE_HEADING_LEVEL}."
+        ));
     }
 
     let trimmed_text = heading_text.trim();
@@ -471,12 +1528,14 @@ pub(crate) fn add_capture_heading(
         used_source_xml: false,
     };
 
-    append_capture_to_docx(
+    let banner_text = load_capture_settings(&app).banner_text;
+    insert_section_into_docx(
         &absolute_path,
         &absolute_path,
         Some(heading_level),
         selected_target_heading_order.filter(|value| *value > 0),
         &styled_section,
+        banner_text.as_deref(),
     )?;
 
     Ok(capture_target_preview_for_path(
@@ -494,6 +1553,7 @@ pub(crate) fn list_roots(app: AppHandle) -> CommandResult<Vec<RootSummary>> {
               r.path,
               r.added_at_ms,
               r.last_indexed_ms,
+              r.last_error,
               (SELECT COUNT(*) FROM files f WHERE f.root_id = r.id) AS file_count,
               (
                 SELECT COUNT(*)
@@ -513,8 +1573,9 @@ pub(crate) fn list_roots(app: AppHandle) -> CommandResult<Vec<RootSummary>> {
                 path: row.get(0)?,
                 added_at_ms: row.get(1)?,
                 last_indexed_ms: row.get(2)?,
-                file_count: row.get(3)?,
-                heading_count: row.get(4)?,
+                last_error: row.get(3)?,
+                file_count: row.get(4)?,
+                heading_count: row.get(5)?,
             })
         })
         .map_err(|error| format!("Could not iterate roots query: {error}"))?;
@@ -527,7 +1588,11 @@ pub(crate) fn list_roots(app: AppHandle) -> CommandResult<Vec<RootSummary>> {
     Ok(roots)
 }
 
-pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexStats> {
+pub(crate) fn index_root(
+    app: AppHandle,
+    path: String,
+    options: IndexOptions,
+) -> CommandResult<IndexStats> {
     let started_at = now_ms();
     let canonical_root = canonicalize_folder(&path)?;
     let root_path = path_display(&canonical_root);
@@ -535,14 +1600,28 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
     let mut connection = open_database(&app)?;
     let root_id = add_or_get_root_id(&connection, &root_path)?;
     let existing_files = load_existing_files(&connection, root_id)?;
-
-    let mut scanned = 0_usize;
+    let stored_chunk_fingerprint = connection
+        .query_row(
+            "SELECT chunk_options FROM roots WHERE id = ?1",
+            params![root_id],
+            |row| row.get::<_, String>(0),
+        )
+        .map_err(|error| format!("Could not read root chunk options: {error}"))?;
+    let options = options.with_stored_chunk_options(&stored_chunk_fingerprint);
+    let chunk_fingerprint = options.chunk_fingerprint();
+    // Roots indexed before the fingerprint existed have none, so they are reparsed once.
+    let chunk_options_changed = chunk_fingerprint != stored_chunk_fingerprint;
+
+    let mut scanned = 0_usize;
     let mut updated = 0_usize;
     let mut skipped = 0_usize;
     let mut removed = 0_usize;
     let mut headings_extracted = 0_usize;
     let mut seen_relative_paths = HashSet::new();
     let mut indexing_candidates = Vec::new();
+    let mut bucket_samples = Vec::new();
+    let mut failed = Vec::new();
+    let mut skipped_too_large = Vec::new();
 
     let mut progress = IndexProgress {
         root_path: root_path.clone(),
@@ -553,6 +1632,7 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
         updated: 0,
         skipped: 0,
         removed: 0,
+        percent: INDEX_PROGRESS_INDETERMINATE,
         elapsed_ms: 0,
         current_file: None,
     };
@@ -578,20 +1658,13 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
             continue;
         }
 
-        let is_docx = entry
-            .path()
-            .extension()
-            .and_then(|extension| extension.to_str())
-            .map(|extension| extension.eq_ignore_ascii_case("docx"))
-            .unwrap_or(false);
-        if !is_docx {
+        if !is_indexable_path(entry.path(), options.include_text_files) {
             continue;
         }
 
         scanned += 1;
         let absolute_path = entry.path().to_path_buf();
         let relative_path_value = relative_path(&canonical_root, &absolute_path)?;
-        seen_relative_paths.insert(relative_path_value.clone());
 
         let metadata = fs::metadata(&absolute_path).map_err(|error| {
             format!(
@@ -599,18 +1672,28 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                 path_display(&absolute_path)
             )
         })?;
+        // Oversized files are not reparsed, but rows from an earlier index of them are kept.
+        seen_relative_paths.insert(relative_path_value.clone());
+        if options
+            .max_indexable_bytes
+            .is_some_and(|max_bytes| metadata.len() > max_bytes)
+        {
+            skipped_too_large.push(relative_path_value);
+            continue;
+        }
         let modified_ms = metadata.modified().map(epoch_ms).unwrap_or(0);
         let size = i64::try_from(metadata.len()).unwrap_or(0);
 
         if let Some(existing) = existing_files.get(&relative_path_value) {
-            if existing.modified_ms == modified_ms
+            if !chunk_options_changed
+                && existing.modified_ms == modified_ms
                 && existing.size == size
                 && !existing.file_hash.is_empty()
             {
                 skipped += 1;
             } else {
                 let file_hash = fast_file_hash(&absolute_path)?;
-                if existing.file_hash == file_hash {
+                if !chunk_options_changed && existing.file_hash == file_hash {
                     skipped += 1;
                 } else {
                     indexing_candidates.push(IndexCandidate {
@@ -659,6 +1742,7 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
     progress.discovered = scanned;
     progress.changed = indexing_candidates.len();
     progress.skipped = skipped;
+    progress.percent = phase_percent(0, progress.changed);
     emit_index_progress(
         &app,
         started_at,
@@ -668,38 +1752,22 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
     );
 
     let parse_chunk_size = suggested_parse_chunk_size();
-    let transaction = connection
-        .transaction()
+    let transaction = begin_write_transaction(&mut connection)
         .map_err(|error| format!("Could not start index transaction: {error}"))?;
 
     for chunk in indexing_candidates.chunks(parse_chunk_size) {
-        let parsed_chunk = chunk
-            .par_iter()
-            .map(|candidate| {
-                let paragraphs =
-                    parse_docx_paragraphs(&candidate.absolute_path).unwrap_or_default();
-                let headings = paragraphs
-                    .iter()
-                    .filter_map(|paragraph| {
-                        paragraph.heading_level.map(|level| ParsedHeading {
-                            order: paragraph.order,
-                            level,
-                            text: paragraph.text.clone(),
-                        })
-                    })
-                    .collect::<Vec<ParsedHeading>>();
-                let authors = extract_author_candidates(&paragraphs);
-                let chunks = build_chunks(&paragraphs);
-                ParsedIndexCandidate {
-                    candidate: candidate.clone(),
-                    headings,
-                    authors,
-                    chunks,
-                }
-            })
-            .collect::<Vec<ParsedIndexCandidate>>();
+        let (parsed_chunk, chunk_failures) = parse_index_batch(chunk, &options);
+        for failure in chunk_failures {
+            logging::warn(&format!(
+                "Skipped '{}' while indexing: {}",
+                failure.relative_path, failure.error
+            ));
+            let _ = app.emit(INDEX_WARNING_EVENT, &failure);
+            failed.push(failure);
+        }
 
         for parsed in parsed_chunk {
+            let insert_started = Instant::now();
             let relative_path_value = parsed.candidate.relative_path;
             let absolute_path_string = path_display(&parsed.candidate.absolute_path);
             let modified_ms = parsed.candidate.modified_ms;
@@ -713,14 +1781,18 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                 transaction
                     .execute(
                         "UPDATE files
-                         SET absolute_path = ?1, modified_ms = ?2, size = ?3, file_hash = ?4, heading_count = ?5
-                         WHERE id = ?6",
+                         SET absolute_path = ?1, modified_ms = ?2, size = ?3, file_hash = ?4, heading_count = ?5,
+                             doc_title = ?6, doc_creator = ?7, doc_keywords = ?8
+                         WHERE id = ?9",
                         params![
                             absolute_path_string,
                             modified_ms,
                             size,
                             parsed.candidate.file_hash.as_str(),
                             heading_count,
+                            parsed.properties.title,
+                            parsed.properties.creator,
+                            parsed.properties.keywords,
                             existing.id
                         ],
                     )
@@ -734,8 +1806,8 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
             } else {
                 transaction
                     .execute(
-                        "INSERT INTO files(root_id, relative_path, absolute_path, modified_ms, size, file_hash, heading_count)
-                         VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        "INSERT INTO files(root_id, relative_path, absolute_path, modified_ms, size, file_hash, heading_count, doc_title, doc_creator, doc_keywords)
+                         VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
                         params![
                             root_id,
                             relative_path_value.as_str(),
@@ -743,7 +1815,10 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                             modified_ms,
                             size,
                             parsed.candidate.file_hash.as_str(),
-                            heading_count
+                            heading_count,
+                            parsed.properties.title,
+                            parsed.properties.creator,
+                            parsed.properties.keywords
                         ],
                     )
                     .map_err(|error| {
@@ -786,8 +1861,8 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                 let normalized = normalize_for_search(&heading.text);
                 transaction
                     .execute(
-                        "INSERT INTO headings(file_id, heading_order, level, text, normalized, file_name, relative_path)
-                         VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        "INSERT INTO headings(file_id, heading_order, level, text, normalized, file_name, relative_path, argument_type)
+                         VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                         params![
                             file_id,
                             heading.order,
@@ -795,7 +1870,8 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                             heading.text,
                             normalized,
                             file_name.as_str(),
-                            relative_path_value.as_str()
+                            relative_path_value.as_str(),
+                            parsed.argument_types.get(&heading.order)
                         ],
                     )
                     .map_err(|error| {
@@ -839,30 +1915,38 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                           root_id,
                           file_id,
                           chunk_order,
+                          kind,
                           heading_order,
                           heading_level,
                           heading_text,
                           author_text,
                           chunk_text,
+                          content_hash,
                           file_name,
                           relative_path,
-                          absolute_path
+                          absolute_path,
+                          argument_type
                         )
-                        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
                         ",
                         params![
                             chunk_id,
                             root_id,
                             file_id,
                             chunk.chunk_order,
+                            chunk.kind,
                             chunk.heading_order,
                             chunk.heading_level,
                             chunk.heading_text,
                             chunk.author_text,
                             chunk.chunk_text,
+                            chunk_content_hash(&chunk.chunk_text),
                             file_name.as_str(),
                             relative_path_value.as_str(),
-                            absolute_path_string.as_str()
+                            absolute_path_string.as_str(),
+                            chunk
+                                .heading_order
+                                .and_then(|order| parsed.argument_types.get(&order))
                         ],
                     )
                     .map_err(|error| {
@@ -873,9 +1957,14 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
                     })?;
             }
 
+            if options.benchmark {
+                bucket_samples.push((size, parsed.parse_ms, elapsed_ms(insert_started)));
+            }
+
             updated += 1;
             progress.processed = updated;
             progress.updated = updated;
+            progress.percent = phase_percent(updated, progress.changed);
             progress.current_file = Some(relative_path_value);
             emit_index_progress(
                 &app,
@@ -887,8 +1976,10 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
         }
     }
 
+    let stale_total = stale_entries.len();
     progress.phase = "cleaning".to_string();
     progress.current_file = None;
+    progress.percent = phase_percent(0, stale_total);
     emit_index_progress(
         &app,
         started_at,
@@ -909,6 +2000,7 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
         removed += 1;
 
         progress.removed = removed;
+        progress.percent = phase_percent(removed, stale_total);
         progress.current_file = Some(relative_path_value);
         emit_index_progress(
             &app,
@@ -919,12 +2011,28 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
         );
     }
 
+    mark_capture_files(&transaction, root_id, &default_capture_target(&app))?;
+
     let finished_at_ms = now_ms();
 
+    let last_error = failed.first().map(|first| {
+        format!(
+            "{} file(s) failed to parse; first: {}: {}",
+            failed.len(),
+            first.relative_path,
+            first.error
+        )
+    });
     transaction
         .execute(
-            "UPDATE roots SET last_indexed_ms = ?1 WHERE id = ?2",
-            params![finished_at_ms, root_id],
+            "UPDATE roots SET last_indexed_ms = ?1, last_error = ?2, failed_count = ?3, chunk_options = ?4 WHERE id = ?5",
+            params![
+                finished_at_ms,
+                last_error,
+                i64::try_from(failed.len()).unwrap_or(i64::MAX),
+                chunk_fingerprint,
+                root_id
+            ],
         )
         .map_err(|error| format!("Could not update root index timestamp: {error}"))?;
 
@@ -944,6 +2052,7 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
     progress.updated = updated;
     progress.skipped = skipped;
     progress.removed = removed;
+    progress.percent = 100.0;
     emit_index_progress(
         &app,
         started_at,
@@ -953,7 +2062,9 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
     );
 
     // Rebuild vector index asynchronously after lexical/index metadata updates complete.
-    crate::vector::trigger_rebuild(app.clone(), true);
+    if options.rebuild_semantic {
+        crate::vector::trigger_rebuild(app.clone(), true);
+    }
 
     Ok(IndexStats {
         scanned,
@@ -962,9 +2073,230 @@ pub(crate) fn index_root(app: AppHandle, path: String) -> CommandResult<IndexSta
         removed,
         headings_extracted,
         elapsed_ms: finished_at_ms - started_at,
+        size_buckets: if options.benchmark {
+            summarize_size_buckets(&bucket_samples)
+        } else {
+            Vec::new()
+        },
+        failed,
+        skipped_too_large,
+    })
+}
+
+const INDEX_FAILURE_CORRUPT: &str = "corrupt";
+const INDEX_FAILURE_PARSE: &str = "parse";
+
+fn parse_index_candidate(
+    candidate: &IndexCandidate,
+    options: &IndexOptions,
+) -> Result<ParsedIndexCandidate, IndexFailure> {
+    let parse_started = Instant::now();
+    if !is_plain_text_path(&candidate.absolute_path) {
+        if let Some(reason) = corrupt_docx_reason(&candidate.absolute_path) {
+            return Err(IndexFailure {
+                relative_path: candidate.relative_path.clone(),
+                kind: INDEX_FAILURE_CORRUPT.to_string(),
+                error: reason,
+            });
+        }
+    }
+    let mut paragraphs =
+        parse_source_paragraphs(&candidate.absolute_path).map_err(|error| IndexFailure {
+            relative_path: candidate.relative_path.clone(),
+            kind: INDEX_FAILURE_PARSE.to_string(),
+            error,
+        })?;
+    if options.merge_short_headings.unwrap_or(false) {
+        paragraphs = merge_short_adjacent_headings(paragraphs, SHORT_HEADING_MERGE_MAX_CHARS);
+    }
+    let headings = paragraphs
+        .iter()
+        .filter_map(|paragraph| {
+            paragraph.heading_level.map(|level| ParsedHeading {
+                order: paragraph.order,
+                level,
+                text: paragraph.text.clone(),
+            })
+        })
+        .collect::<Vec<ParsedHeading>>();
+    let authors = extract_author_candidates(&paragraphs);
+    let mut chunks = build_chunks(&paragraphs);
+    if options.index_paragraphs.unwrap_or(false) {
+        append_paragraph_units(&paragraphs, &mut chunks);
+    }
+    if options.index_comments.unwrap_or(false) && !is_plain_text_path(&candidate.absolute_path) {
+        let comments =
+            parse_docx_comments(&candidate.absolute_path).map_err(|error| IndexFailure {
+                relative_path: candidate.relative_path.clone(),
+                kind: INDEX_FAILURE_PARSE.to_string(),
+                error,
+            })?;
+        append_comment_units(&paragraphs, &comments, &mut chunks);
+    }
+    if options.collapse_whitespace.unwrap_or(false) {
+        collapse_chunk_whitespace(&mut chunks);
+    }
+    let argument_types = section_argument_types(&paragraphs);
+    let properties = if is_plain_text_path(&candidate.absolute_path) {
+        DocumentProperties::default()
+    } else {
+        parse_docx_properties(&candidate.absolute_path).unwrap_or_default()
+    };
+    Ok(ParsedIndexCandidate {
+        candidate: candidate.clone(),
+        headings,
+        authors,
+        chunks,
+        argument_types,
+        properties,
+        parse_ms: elapsed_ms(parse_started),
     })
 }
 
+fn parse_index_batch(
+    candidates: &[IndexCandidate],
+    options: &IndexOptions,
+) -> (Vec<ParsedIndexCandidate>, Vec<IndexFailure>) {
+    let results = with_index_pool(|| {
+        candidates
+            .par_iter()
+            .map(|candidate| parse_index_candidate(candidate, options))
+            .collect::<Vec<Result<ParsedIndexCandidate, IndexFailure>>>()
+    });
+
+    let mut parsed = Vec::with_capacity(results.len());
+    let mut failures = Vec::new();
+    for result in results {
+        match result {
+            Ok(candidate) => parsed.push(candidate),
+            Err(failure) => failures.push(failure),
+        }
+    }
+    (parsed, failures)
+}
+
+const INDEX_SIZE_BUCKETS: [(&str, i64); 3] = [
+    ("<100KB", 100 * 1024),
+    ("<1MB", 1024 * 1024),
+    (">1MB", i64::MAX),
+];
+
+fn summarize_size_buckets(samples: &[(i64, f64, f64)]) -> Vec<IndexSizeBucket> {
+    let mut buckets = INDEX_SIZE_BUCKETS
+        .iter()
+        .map(|(label, _)| IndexSizeBucket {
+            label: label.to_string(),
+            ..IndexSizeBucket::default()
+        })
+        .collect::<Vec<IndexSizeBucket>>();
+
+    for (size, parse_ms, insert_ms) in samples {
+        let index = INDEX_SIZE_BUCKETS
+            .iter()
+            .position(|(_, limit)| size < limit)
+            .unwrap_or(INDEX_SIZE_BUCKETS.len() - 1);
+        let bucket = &mut buckets[index];
+        bucket.file_count += 1;
+        bucket.total_bytes = bucket.total_bytes.saturating_add(*size);
+        bucket.mean_parse_ms += parse_ms;
+        bucket.mean_insert_ms += insert_ms;
+    }
+
+    for bucket in &mut buckets {
+        if bucket.file_count == 0 {
+            continue;
+        }
+        let count = bucket.file_count as f64;
+        bucket.mean_parse_ms /= count;
+        bucket.mean_insert_ms /= count;
+        bucket.mean_total_ms = bucket.mean_parse_ms + bucket.mean_insert_ms;
+    }
+
+    buckets
+}
+
+pub(crate) fn configure_indexer(writer_heap_bytes: usize) -> CommandResult<usize> {
+    Ok(lexical::configure_writer_heap_bytes(writer_heap_bytes))
+}
+
+pub(crate) fn configure_index_parallelism(threads: Option<usize>) -> CommandResult<usize> {
+    configure_index_thread_pool(threads)
+}
+
+pub(crate) fn set_semantic_enabled(enabled: bool) -> CommandResult<bool> {
+    Ok(crate::vector::set_enabled(enabled))
+}
+
+pub(crate) fn set_semantic_model(
+    app: AppHandle,
+    name: String,
+) -> CommandResult<SemanticModelSelection> {
+    crate::vector::set_model(&app, &name)
+}
+
+pub(crate) fn configure_semantic_limits(
+    max_tokens: Option<usize>,
+    max_chars: Option<usize>,
+) -> CommandResult<SemanticEmbeddingLimits> {
+    Ok(crate::vector::configure_limits(max_tokens, max_chars))
+}
+
+pub(crate) fn cancel_semantic_rebuild(app: AppHandle, clear: Option<bool>) -> CommandResult<bool> {
+    crate::vector::cancel_rebuild(&app, clear.unwrap_or(false))
+}
+
+pub(crate) fn text_similarity(app: AppHandle, a: String, b: String) -> CommandResult<f32> {
+    crate::vector::text_similarity(&app, &a, &b)
+}
+
+pub(crate) fn get_semantic_readiness(app: AppHandle) -> CommandResult<SemanticReadiness> {
+    Ok(crate::vector::readiness(&app))
+}
+
+pub(crate) fn configure_search_ranking(kind_boost: f64) -> CommandResult<f64> {
+    let applied = lexical::configure_kind_boost(kind_boost);
+    // Cached hits were ranked with the old boost.
+    query_engine::clear_query_cache();
+    Ok(applied)
+}
+
+pub(crate) fn configure_short_queries(enabled: bool) -> CommandResult<bool> {
+    Ok(query_engine::configure_short_queries(enabled))
+}
+
+pub(crate) fn flush_query_cache(app: AppHandle) -> CommandResult<usize> {
+    query_engine::flush_query_cache(&app)
+}
+
+pub(crate) fn configure_lexical_tokenizers(
+    app: AppHandle,
+    prefix_min_gram: Option<usize>,
+    prefix_max_gram: Option<usize>,
+    ngram_min_gram: Option<usize>,
+    ngram_max_gram: Option<usize>,
+) -> CommandResult<LexicalTokenizerConfig> {
+    let current = lexical::tokenizer_config(&app)?;
+    lexical::configure_tokenizers(
+        &app,
+        LexicalTokenizerConfig {
+            prefix_min_gram: prefix_min_gram.unwrap_or(current.prefix_min_gram),
+            prefix_max_gram: prefix_max_gram.unwrap_or(current.prefix_max_gram),
+            ngram_min_gram: ngram_min_gram.unwrap_or(current.ngram_min_gram),
+            ngram_max_gram: ngram_max_gram.unwrap_or(current.ngram_max_gram),
+        },
+    )
+}
+
+pub(crate) fn configure_preview_limits(
+    max_chars_per_heading: Option<usize>,
+    max_total_chars: Option<usize>,
+) -> CommandResult<PreviewLimits> {
+    Ok(crate::preview::configure_preview_limits(
+        max_chars_per_heading,
+        max_total_chars,
+    ))
+}
+
 fn ensure_folder_with_ancestors(folders: &mut HashMap<String, FolderEntry>, folder_path: &str) {
     let mut current = folder_path.to_string();
 
@@ -1010,7 +2342,185 @@ fn ensure_folder_with_ancestors(folders: &mut HashMap<String, FolderEntry>, fold
     }
 }
 
-pub(crate) fn get_index_snapshot(app: AppHandle, path: String) -> CommandResult<IndexSnapshot> {
+pub(crate) fn rebuild_lexical_index_now(app: AppHandle) -> CommandResult<LexicalRebuildStats> {
+    let started = Instant::now();
+    let document_count = rebuild_lexical_index(&app)?;
+    query_engine::clear_query_cache();
+    Ok(LexicalRebuildStats {
+        document_count,
+        elapsed_ms: i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX),
+    })
+}
+
+// Re-runs author and argument-type detection for one indexed file without rebuilding its
+// headings or chunks, so heuristic changes apply without a reindex.
+pub(crate) fn refresh_metadata(
+    app: AppHandle,
+    file_id: i64,
+) -> CommandResult<MetadataRefreshStats> {
+    let mut connection = open_database(&app)?;
+    let (relative_path, absolute_path) = load_file_paths(&connection, file_id)?;
+    let paragraphs = parse_source_paragraphs(Path::new(&absolute_path))?;
+
+    let indexed_orders = {
+        let mut statement = connection
+            .prepare(
+                "SELECT heading_order FROM headings WHERE file_id = ?1 ORDER BY heading_order ASC",
+            )
+            .map_err(|error| format!("Could not prepare heading order query: {error}"))?;
+        let rows = statement
+            .query_map(params![file_id], |row| row.get::<_, i64>(0))
+            .map_err(|error| format!("Could not query heading orders: {error}"))?;
+        rows.collect::<Result<Vec<i64>, _>>()
+            .map_err(|error| format!("Could not read heading order row: {error}"))?
+    };
+    let heading_orders = |paragraphs: &[ParsedParagraph]| {
+        paragraphs
+            .iter()
+            .filter(|paragraph| paragraph.heading_level.is_some())
+            .map(|paragraph| paragraph.order)
+            .collect::<Vec<i64>>()
+    };
+    // Files indexed with mergeShortHeadings only keep the first fragment's order.
+    let paragraphs = if heading_orders(&paragraphs) == indexed_orders {
+        paragraphs
+    } else {
+        let merged = merge_short_adjacent_headings(paragraphs, SHORT_HEADING_MERGE_MAX_CHARS);
+        if heading_orders(&merged) != indexed_orders {
+            return Err(format!(
+                "'{relative_path}' changed since it was indexed. Reindex its root instead."
+            ));
+        }
+        merged
+    };
+
+    let authors = extract_author_candidates(&paragraphs);
+    let argument_types = section_argument_types(&paragraphs);
+    // Headings missing from the map (containers) drop any tag an older heuristic gave them.
+    let heading_argument_types = build_heading_ranges(&paragraphs)
+        .into_iter()
+        .map(|range| (range.order, argument_types.get(&range.order)))
+        .collect::<Vec<(i64, Option<&String>)>>();
+    let chunk_authors = build_chunks(&paragraphs)
+        .into_iter()
+        .map(|chunk| (chunk.chunk_order, chunk.author_text))
+        .collect::<Vec<(i64, Option<String>)>>();
+    let file_name = file_name_from_relative(&relative_path);
+
+    let transaction = begin_write_transaction(&mut connection)
+        .map_err(|error| format!("Could not start metadata transaction: {error}"))?;
+    transaction
+        .execute("DELETE FROM authors WHERE file_id = ?1", params![file_id])
+        .map_err(|error| {
+            format!("Could not clear old author rows for '{relative_path}': {error}")
+        })?;
+    for (author_order, author_text) in &authors {
+        transaction
+            .execute(
+                "INSERT INTO authors(file_id, author_order, text, normalized, file_name, relative_path)
+                 VALUES(?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    file_id,
+                    author_order,
+                    author_text,
+                    normalize_for_search(author_text),
+                    file_name.as_str(),
+                    relative_path.as_str()
+                ],
+            )
+            .map_err(|error| {
+                format!("Could not insert author metadata for '{relative_path}': {error}")
+            })?;
+    }
+
+    let mut updated_headings = 0_usize;
+    let mut updated_chunks = 0_usize;
+    for (heading_order, argument_type) in &heading_argument_types {
+        updated_headings += transaction
+            .execute(
+                "UPDATE headings SET argument_type = ?1
+                 WHERE file_id = ?2 AND heading_order = ?3 AND argument_type IS NOT ?1",
+                params![argument_type, file_id, heading_order],
+            )
+            .map_err(|error| {
+                format!("Could not update heading argument type for '{relative_path}': {error}")
+            })?;
+        updated_chunks += transaction
+            .execute(
+                "UPDATE chunks SET argument_type = ?1
+                 WHERE file_id = ?2 AND heading_order = ?3 AND argument_type IS NOT ?1",
+                params![argument_type, file_id, heading_order],
+            )
+            .map_err(|error| {
+                format!("Could not update chunk argument type for '{relative_path}': {error}")
+            })?;
+    }
+    for (chunk_order, author_text) in &chunk_authors {
+        updated_chunks += transaction
+            .execute(
+                "UPDATE chunks SET author_text = ?1
+                 WHERE file_id = ?2 AND chunk_order = ?3 AND kind = 'chunk' AND author_text IS NOT ?1",
+                params![author_text, file_id, chunk_order],
+            )
+            .map_err(|error| {
+                format!("Could not update chunk author for '{relative_path}': {error}")
+            })?;
+    }
+    transaction
+        .commit()
+        .map_err(|error| format!("Could not commit metadata refresh: {error}"))?;
+
+    let lexical_document_count = refresh_lexical_file(&app, file_id)?;
+    query_engine::clear_query_cache();
+    Ok(MetadataRefreshStats {
+        file_id,
+        author_count: authors.len(),
+        updated_headings,
+        updated_chunks,
+        lexical_document_count,
+    })
+}
+
+pub(crate) fn get_global_stats(app: AppHandle) -> CommandResult<GlobalStats> {
+    let connection = open_database(&app)?;
+    let mut stats = connection
+        .query_row(
+            "
+            SELECT
+              (SELECT COUNT(*) FROM roots),
+              (SELECT COUNT(*) FROM files),
+              (SELECT COUNT(*) FROM headings),
+              (SELECT COUNT(*) FROM authors),
+              (SELECT COUNT(*) FROM chunks),
+              (SELECT COUNT(*) FROM (SELECT DISTINCT root_id, target_relative_path FROM captures)),
+              (SELECT COALESCE(SUM(size), 0) FROM files)
+            ",
+            [],
+            |row| {
+                Ok(GlobalStats {
+                    root_count: row.get(0)?,
+                    file_count: row.get(1)?,
+                    heading_count: row.get(2)?,
+                    author_count: row.get(3)?,
+                    chunk_count: row.get(4)?,
+                    capture_target_count: row.get(5)?,
+                    total_indexed_bytes: row.get(6)?,
+                    semantic_item_count: 0,
+                })
+            },
+        )
+        .map_err(|error| format!("Could not read global stats: {error}"))?;
+    stats.semantic_item_count = crate::vector::item_count(&app).unwrap_or(0);
+    Ok(stats)
+}
+
+pub(crate) fn get_index_snapshot(
+    app: AppHandle,
+    path: String,
+    modified_since_ms: Option<i64>,
+    skip_folders: Option<bool>,
+) -> CommandResult<IndexSnapshot> {
+    let skip_folders = skip_folders.unwrap_or(false);
     let canonical_path = canonicalize_folder(&path)
         .map(|canonical| path_display(&canonical))
         .unwrap_or(path);
@@ -1055,29 +2565,35 @@ pub(crate) fn get_index_snapshot(app: AppHandle, path: String) -> CommandResult<
 
     let mut files = Vec::new();
     let mut folders = HashMap::new();
-    ensure_folder_with_ancestors(&mut folders, "");
+    if !skip_folders {
+        ensure_folder_with_ancestors(&mut folders, "");
+    }
 
     for row in rows {
         let record = row.map_err(|error| format!("Could not parse indexed file row: {error}"))?;
         let folder_path = folder_from_relative(&record.relative_path);
-        ensure_folder_with_ancestors(&mut folders, &folder_path);
+        if !skip_folders {
+            ensure_folder_with_ancestors(&mut folders, &folder_path);
+            let mut current_folder = folder_path.clone();
+            loop {
+                if let Some(folder_entry) = folders.get_mut(&current_folder) {
+                    folder_entry.file_count += 1;
+                }
 
-        let mut current_folder = folder_path.clone();
-        loop {
-            if let Some(folder_entry) = folders.get_mut(&current_folder) {
-                folder_entry.file_count += 1;
-            }
+                if current_folder.is_empty() {
+                    break;
+                }
 
-            if current_folder.is_empty() {
-                break;
+                current_folder = current_folder
+                    .rsplit_once('/')
+                    .map(|(parent, _)| parent.to_string())
+                    .unwrap_or_default();
             }
-
-            current_folder = current_folder
-                .rsplit_once('/')
-                .map(|(parent, _)| parent.to_string())
-                .unwrap_or_default();
         }
 
+        if modified_since_ms.is_some_and(|since| record.modified_ms <= since) {
+            continue;
+        }
         files.push(IndexedFile {
             id: record.id,
             file_name: file_name_from_relative(&record.relative_path),
@@ -1103,680 +2619,4106 @@ pub(crate) fn get_index_snapshot(app: AppHandle, path: String) -> CommandResult<
     })
 }
 
-pub(crate) fn get_file_preview(app: AppHandle, file_id: i64) -> CommandResult<FilePreview> {
+pub(crate) fn files_by_author(
+    app: AppHandle,
+    root_path: String,
+    author_query: String,
+) -> CommandResult<Vec<IndexedFile>> {
+    let normalized_query = normalize_for_search(&author_query);
+    if normalized_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let canonical_path = canonicalize_folder(&root_path)
+        .map(|canonical| path_display(&canonical))
+        .unwrap_or(root_path);
     let connection = open_database(&app)?;
+    let Some(root_id) = root_id(&connection, &canonical_path)? else {
+        return Ok(Vec::new());
+    };
 
-    let (relative_path, absolute_path, heading_count) = connection
-        .query_row(
-            "SELECT relative_path, absolute_path, heading_count FROM files WHERE id = ?1",
-            params![file_id],
-            |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, i64>(2)?,
-                ))
-            },
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT DISTINCT f.id, f.relative_path, f.modified_ms, f.heading_count
+            FROM files f
+            JOIN authors a ON a.file_id = f.id
+            WHERE f.root_id = ?1 AND instr(a.normalized, ?2) > 0
+            ORDER BY f.relative_path
+            ",
         )
-        .map_err(|error| format!("Could not load file preview metadata: {error}"))?;
-    let (mut headings, mut f8_cites) =
-        extract_preview_content(Path::new(&absolute_path)).unwrap_or_default();
-
-    headings.sort_by(|left, right| left.order.cmp(&right.order));
-    f8_cites.sort_by(|left, right| left.order.cmp(&right.order));
-
-    Ok(FilePreview {
-        file_id,
-        file_name: file_name_from_relative(&relative_path),
-        relative_path,
-        absolute_path,
-        heading_count: i64::try_from(headings.len()).unwrap_or(heading_count),
-        headings,
-        f8_cites,
-    })
-}
+        .map_err(|error| format!("Could not prepare author file query: {error}"))?;
+    let rows = statement
+        .query_map(params![root_id, normalized_query], |row| {
+            Ok(FileRecord {
+                id: row.get(0)?,
+                relative_path: row.get(1)?,
+                modified_ms: row.get(2)?,
+                heading_count: row.get(3)?,
+            })
+        })
+        .map_err(|error| format!("Could not load files for author: {error}"))?;
 
-pub(crate) fn get_heading_preview_html(
-    app: AppHandle,
-    file_id: i64,
-    heading_order: i64,
-) -> CommandResult<String> {
-    if heading_order <= 0 {
-        return Ok(String::new());
+    let mut files = Vec::new();
+    for row in rows {
+        let record = row.map_err(|error| format!("Could not parse author file row: {error}"))?;
+        files.push(IndexedFile {
+            id: record.id,
+            file_name: file_name_from_relative(&record.relative_path),
+            folder_path: folder_from_relative(&record.relative_path),
+            relative_path: record.relative_path,
+            modified_ms: record.modified_ms,
+            heading_count: record.heading_count,
+        });
     }
+    Ok(files)
+}
 
+pub(crate) fn find_duplicate_files(app: AppHandle) -> CommandResult<Vec<DuplicateFileCluster>> {
     let connection = open_database(&app)?;
-    let absolute_path = connection
-        .query_row(
-            "SELECT absolute_path FROM files WHERE id = ?1",
-            params![file_id],
-            |row| row.get::<_, String>(0),
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT f.file_hash, f.size, f.id, r.path, f.relative_path, f.absolute_path
+            FROM files f
+            JOIN roots r ON r.id = f.root_id
+            WHERE f.file_hash IN (
+              SELECT file_hash
+              FROM files
+              WHERE file_hash != ''
+              GROUP BY file_hash
+              HAVING COUNT(*) > 1
+            )
+            ORDER BY f.size DESC, f.file_hash, r.path, f.relative_path
+            ",
         )
-        .map_err(|error| format!("Could not load heading preview source file: {error}"))?;
+        .map_err(|error| format!("Could not prepare duplicate file query: {error}"))?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                DuplicateFile {
+                    file_id: row.get(2)?,
+                    root_path: row.get(3)?,
+                    relative_path: row.get(4)?,
+                    absolute_path: row.get(5)?,
+                },
+            ))
+        })
+        .map_err(|error| format!("Could not load duplicate files: {error}"))?;
 
-    extract_heading_preview_html(Path::new(&absolute_path), heading_order)
+    let mut clusters: Vec<DuplicateFileCluster> = Vec::new();
+    for row in rows {
+        let (file_hash, size, file) =
+            row.map_err(|error| format!("Could not parse duplicate file row: {error}"))?;
+        match clusters.last_mut() {
+            Some(cluster) if cluster.file_hash == file_hash => cluster.files.push(file),
+            _ => clusters.push(DuplicateFileCluster {
+                file_hash,
+                size,
+                files: vec![file],
+            }),
+        }
+    }
+    Ok(clusters)
 }
 
-pub(crate) async fn search_index(
+pub(crate) fn export_index_snapshot(
     app: AppHandle,
-    query: String,
-    root_path: Option<String>,
-    limit: Option<usize>,
-) -> CommandResult<Vec<SearchHit>> {
-    crate::async_runtime::spawn_blocking(move || {
-        query_engine::search_lexical(&app, &query, root_path, limit)
-    })
-    .await
-    .map_err(|error| format!("Lexical search command failed: {error}"))?
+    root_path: String,
+    output_path: String,
+) -> CommandResult<String> {
+    let snapshot = get_index_snapshot(app, root_path, None, None)?;
+    let output = Path::new(&output_path);
+    write_index_snapshot(&snapshot, output)?;
+    Ok(path_display(output))
 }
 
-pub(crate) async fn search_index_semantic(
-    app: AppHandle,
-    query: String,
-    root_path: Option<String>,
-    limit: Option<usize>,
-) -> CommandResult<Vec<SearchHit>> {
-    query_engine::search_semantic(&app, &query, root_path, limit).await
+fn write_index_snapshot(snapshot: &IndexSnapshot, output_path: &Path) -> CommandResult<()> {
+    if output_path.is_dir() {
+        return Err(format!(
+            "Snapshot export path '{}' is a directory.",
+            path_display(output_path)
+        ));
+    }
+    if let Some(parent) = output_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        if !parent.is_dir() {
+            return Err(format!(
+                "Snapshot export folder '{}' does not exist.",
+                path_display(parent)
+            ));
+        }
+    }
+
+    let json = serde_json::to_string_pretty(snapshot)
+        .map_err(|error| format!("Could not serialize index snapshot: {error}"))?;
+    fs::write(output_path, json).map_err(|error| {
+        format!(
+            "Could not write index snapshot to '{}': {error}",
+            path_display(output_path)
+        )
+    })
 }
 
-pub(crate) async fn search_index_hybrid(
+pub(crate) fn verify_index(
     app: AppHandle,
-    query: String,
-    root_path: Option<String>,
-    limit: Option<usize>,
-    file_name_only: Option<bool>,
-    semantic_enabled: Option<bool>,
-) -> CommandResult<Vec<SearchHit>> {
-    query_engine::search_hybrid(
-        &app,
-        &query,
-        root_path,
-        limit,
-        file_name_only.unwrap_or(false),
-        semantic_enabled.unwrap_or(true),
-    )
-    .await
+    root_path: String,
+) -> CommandResult<Vec<IndexDriftEntry>> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let root_path_value = path_display(&canonical_root);
+
+    let connection = open_database(&app)?;
+    let root_id = root_id(&connection, &root_path_value)?.ok_or_else(|| {
+        format!(
+            "No index found for '{}'. Add the folder first.",
+            root_path_value
+        )
+    })?;
+    let existing_files = load_existing_files(&connection, root_id)?;
+    let include_text_files = existing_files
+        .keys()
+        .any(|relative_path| is_plain_text_path(Path::new(relative_path)));
+    let disk_files = scan_source_metadata(&canonical_root, include_text_files)?;
+
+    Ok(classify_index_drift(&existing_files, &disk_files))
 }
 
-fn elapsed_ms(started: Instant) -> f64 {
-    started.elapsed().as_secs_f64() * 1000.0
+pub(crate) fn root_health(app: AppHandle, root_path: String) -> CommandResult<RootHealth> {
+    let canonical_root = canonicalize_folder(&root_path)?;
+    let root_path_value = path_display(&canonical_root);
+
+    let connection = open_database(&app)?;
+    let root_id = root_id(&connection, &root_path_value)?.ok_or_else(|| {
+        format!(
+            "No index found for '{}'. Add the folder first.",
+            root_path_value
+        )
+    })?;
+    let (last_indexed_ms, failed_count, zero_heading_files) = connection
+        .query_row(
+            "
+            SELECT
+              r.last_indexed_ms,
+              r.failed_count,
+              (SELECT COUNT(*) FROM files f WHERE f.root_id = r.id AND f.heading_count = 0)
+            FROM roots r
+            WHERE r.id = ?1
+            ",
+            params![root_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            },
+        )
+        .map_err(|error| format!("Could not read root health: {error}"))?;
+
+    let existing_files = load_existing_files(&connection, root_id)?;
+    let include_text_files = existing_files
+        .keys()
+        .any(|relative_path| is_plain_text_path(Path::new(relative_path)));
+    let disk_files = scan_source_metadata(&canonical_root, include_text_files)?;
+    let drift = classify_index_drift(&existing_files, &disk_files);
+
+    let parse_error_files = usize::try_from(failed_count).unwrap_or(0);
+    let changed_files = drift
+        .iter()
+        .filter(|entry| entry.status == "modified" || entry.status == "missing")
+        .count();
+    // Files that failed to parse are never stored, so they also show up as new.
+    let new_files = drift.iter().filter(|entry| entry.status == "new").count();
+    let out_of_date_files = changed_files + new_files.saturating_sub(parse_error_files);
+    let zero_heading_files = usize::try_from(zero_heading_files).unwrap_or(0);
+    let semantic_coverage = if crate::vector::is_enabled() {
+        Some(crate::vector::root_coverage(
+            &app,
+            root_id,
+            last_indexed_ms,
+        )?)
+    } else {
+        None
+    };
+
+    let file_count = existing_files.len();
+    let tracked_files = (file_count + parse_error_files).max(1) as f64;
+    let penalty = 30.0 * parse_error_files as f64 / tracked_files
+        + 30.0 * (out_of_date_files as f64 / tracked_files).min(1.0)
+        + 20.0 * zero_heading_files as f64 / file_count.max(1) as f64
+        + 20.0 * (1.0 - semantic_coverage.unwrap_or(1.0));
+
+    Ok(RootHealth {
+        score: (100.0 - penalty).round().clamp(0.0, 100.0) as u8,
+        file_count,
+        parse_error_files,
+        out_of_date_files,
+        zero_heading_files,
+        semantic_coverage,
+    })
 }
 
-fn percentile(sorted_samples: &[f64], percentile: f64) -> f64 {
-    if sorted_samples.is_empty() {
-        return 0.0;
+fn scan_source_metadata(
+    root: &Path,
+    include_text_files: bool,
+) -> CommandResult<HashMap<String, (i64, i64)>> {
+    let mut files = HashMap::new();
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(is_visible_entry)
+    {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if !is_indexable_path(entry.path(), include_text_files) {
+            continue;
+        }
+
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified_ms = metadata.modified().map(epoch_ms).unwrap_or(0);
+        let size = i64::try_from(metadata.len()).unwrap_or(0);
+        files.insert(relative_path(root, entry.path())?, (modified_ms, size));
     }
-    let clamped = percentile.clamp(0.0, 1.0);
-    let last = sorted_samples.len().saturating_sub(1);
-    let index = ((last as f64) * clamped).round() as usize;
-    sorted_samples[index.min(last)]
+    Ok(files)
 }
 
-fn latency_stats(samples: &[f64]) -> BenchmarkLatencyStats {
-    if samples.is_empty() {
-        return BenchmarkLatencyStats::default();
-    }
+fn classify_index_drift(
+    existing_files: &HashMap<String, ExistingFileMeta>,
+    disk_files: &HashMap<String, (i64, i64)>,
+) -> Vec<IndexDriftEntry> {
+    let mut entries = existing_files
+        .iter()
+        .map(|(relative_path, existing)| {
+            let status = match disk_files.get(relative_path) {
+                None => "missing",
+                Some((modified_ms, size))
+                    if *modified_ms != existing.modified_ms || *size != existing.size =>
+                {
+                    "modified"
+                }
+                Some(_) => "ok",
+            };
+            IndexDriftEntry {
+                relative_path: relative_path.clone(),
+                status: status.to_string(),
+            }
+        })
+        .collect::<Vec<IndexDriftEntry>>();
+
+    entries.extend(
+        disk_files
+            .keys()
+            .filter(|relative_path| !existing_files.contains_key(*relative_path))
+            .map(|relative_path| IndexDriftEntry {
+                relative_path: relative_path.clone(),
+                status: "new".to_string(),
+            }),
+    );
+    entries.sort_by(|left, right| left.relative_path.cmp(&right.relative_path));
+    entries
+}
 
-    let mut sorted = samples.to_vec();
-    sorted.sort_by(|left, right| {
-        left.partial_cmp(right)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-    let sum = sorted.iter().copied().sum::<f64>();
-    BenchmarkLatencyStats {
-        runs: sorted.len(),
-        min_ms: *sorted.first().unwrap_or(&0.0),
-        p50_ms: percentile(&sorted, 0.50),
-        p95_ms: percentile(&sorted, 0.95),
-        max_ms: *sorted.last().unwrap_or(&0.0),
-        mean_ms: sum / (sorted.len() as f64),
+type FilePreviewRow = (String, String, i64, i64);
+
+fn load_file_preview_row(
+    connection: &Connection,
+    file_id: i64,
+) -> rusqlite::Result<FilePreviewRow> {
+    connection.query_row(
+        "SELECT relative_path, absolute_path, heading_count, modified_ms FROM files WHERE id = ?1",
+        params![file_id],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        },
+    )
+}
+
+pub(crate) fn to_file_uri(path: String) -> CommandResult<String> {
+    if path.trim().is_empty() {
+        return Err("Path is empty.".to_string());
     }
+    Ok(file_uri(&path))
 }
 
-fn build_task_result(
-    enabled: bool,
-    samples: &[f64],
-    total_hits: usize,
-    error: Option<String>,
-) -> BenchmarkTaskResult {
-    BenchmarkTaskResult {
-        enabled,
-        error,
-        total_hits,
-        latency: latency_stats(samples),
+pub(crate) fn get_file_preview(app: AppHandle, file_id: i64) -> CommandResult<FilePreview> {
+    let connection = open_database(&app)?;
+    let row = load_file_preview_row(&connection, file_id)
+        .map_err(|error| format!("Could not load file preview metadata: {error}"))?;
+    Ok(build_file_preview(file_id, row))
+}
+
+pub(crate) fn get_file_previews(
+    app: AppHandle,
+    file_ids: Vec<i64>,
+    max_headings_each: Option<usize>,
+) -> CommandResult<Vec<FilePreview>> {
+    let connection = open_database(&app)?;
+    let mut seen = HashSet::new();
+    let mut rows = Vec::new();
+    for file_id in file_ids {
+        if !seen.insert(file_id) {
+            continue;
+        }
+        // Ids that are no longer indexed are skipped rather than failing the batch.
+        if let Some(row) = load_file_preview_row(&connection, file_id)
+            .optional()
+            .map_err(|error| format!("Could not load file preview metadata: {error}"))?
+        {
+            rows.push((file_id, row));
+        }
     }
+
+    Ok(rows
+        .into_par_iter()
+        .map(|(file_id, row)| {
+            let mut preview = build_file_preview(file_id, row);
+            if let Some(max_headings) = max_headings_each {
+                preview.headings.truncate(max_headings);
+            }
+            preview
+        })
+        .collect())
 }
 
-fn query_candidates_from_text(text: &str) -> Vec<String> {
-    let normalized = normalize_for_search(text);
-    if normalized.is_empty() {
-        return Vec::new();
+fn build_file_preview(file_id: i64, row: FilePreviewRow) -> FilePreview {
+    let (relative_path, absolute_path, heading_count, indexed_modified_ms) = row;
+    let modified_ms = fs::metadata(&absolute_path)
+        .and_then(|metadata| metadata.modified())
+        .map(epoch_ms)
+        .unwrap_or(indexed_modified_ms);
+    let (mut headings, mut f8_cites, word_count) = cached_preview_content(
+        file_id,
+        modified_ms,
+        Path::new(&absolute_path),
+        extract_preview_content,
+    )
+    .unwrap_or_default();
+
+    headings.sort_by(|left, right| left.order.cmp(&right.order));
+    f8_cites.sort_by(|left, right| left.order.cmp(&right.order));
+
+    FilePreview {
+        file_id,
+        file_name: file_name_from_relative(&relative_path),
+        relative_path,
+        absolute_path,
+        heading_count: i64::try_from(headings.len()).unwrap_or(heading_count),
+        headings,
+        f8_cites,
+        word_count,
+        estimated_read_ms: word_count.saturating_mul(60_000) / READING_WORDS_PER_MINUTE,
     }
+}
 
-    let tokens = normalized
-        .split_whitespace()
-        .filter(|token| token.len() >= 3)
-        .map(|token| token.to_string())
+fn load_file_paths(connection: &Connection, file_id: i64) -> CommandResult<(String, String)> {
+    connection
+        .query_row(
+            "SELECT relative_path, absolute_path FROM files WHERE id = ?1",
+            params![file_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()
+        .map_err(|error| format!("Could not load file {file_id}: {error}"))?
+        .ok_or_else(|| format!("File {file_id} is not indexed."))
+}
+
+fn diff_heading_texts(paragraphs: &[ParsedParagraph]) -> Vec<(String, String)> {
+    let mut seen = HashSet::new();
+    paragraphs
+        .iter()
+        .filter(|paragraph| paragraph.heading_level.is_some())
+        .filter_map(|paragraph| {
+            let normalized = normalize_for_search(&paragraph.text);
+            (!normalized.is_empty() && seen.insert(normalized.clone()))
+                .then(|| (normalized, paragraph.text.clone()))
+        })
+        .collect()
+}
+
+pub(crate) fn diff_files(
+    app: AppHandle,
+    file_id_a: i64,
+    file_id_b: i64,
+) -> CommandResult<FileDiff> {
+    let connection = open_database(&app)?;
+    let (relative_path_a, absolute_path_a) = load_file_paths(&connection, file_id_a)?;
+    let (relative_path_b, absolute_path_b) = load_file_paths(&connection, file_id_b)?;
+    let paragraphs_a = parse_source_paragraphs(Path::new(&absolute_path_a))?;
+    let paragraphs_b = parse_source_paragraphs(Path::new(&absolute_path_b))?;
+
+    let headings_a = diff_heading_texts(&paragraphs_a);
+    let headings_b = diff_heading_texts(&paragraphs_b);
+    let keys_a = headings_a
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect::<HashSet<&str>>();
+    let keys_b = headings_b
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect::<HashSet<&str>>();
+    let (common_headings, removed_headings) = headings_a
+        .iter()
+        .partition::<Vec<&(String, String)>, _>(|(key, _)| keys_b.contains(key.as_str()));
+    let added_headings = headings_b
+        .iter()
+        .filter(|(key, _)| !keys_a.contains(key.as_str()))
+        .map(|(_, text)| text.clone())
         .collect::<Vec<String>>();
-    if tokens.is_empty() {
-        return Vec::new();
-    }
 
-    let mut candidates = Vec::new();
-    let head_three = tokens.iter().take(3).cloned().collect::<Vec<String>>();
-    if !head_three.is_empty() {
-        candidates.push(head_three.join(" "));
-    }
-    if tokens.len() >= 2 {
-        candidates.push(tokens.iter().take(2).cloned().collect::<Vec<String>>().join(" "));
-    }
-    candidates.push(tokens[0].clone());
-    if tokens.len() >= 4 {
-        let tail_two = tokens[tokens.len().saturating_sub(2)..]
+    let chunk_hashes = |paragraphs: &[ParsedParagraph]| {
+        build_chunks(paragraphs)
             .iter()
-            .cloned()
-            .collect::<Vec<String>>()
-            .join(" ");
-        candidates.push(tail_two);
-    }
+            .map(|chunk| chunk_content_hash(&chunk.chunk_text))
+            .collect::<HashSet<String>>()
+    };
+    let hashes_a = chunk_hashes(&paragraphs_a);
+    let hashes_b = chunk_hashes(&paragraphs_b);
+    let union = hashes_a.union(&hashes_b).count();
+    let similarity = if union == 0 {
+        1.0
+    } else {
+        hashes_a.intersection(&hashes_b).count() as f64 / union as f64
+    };
 
-    candidates
+    Ok(FileDiff {
+        file_id_a,
+        file_id_b,
+        relative_path_a,
+        relative_path_b,
+        common_headings: common_headings
+            .into_iter()
+            .map(|(_, text)| text.clone())
+            .collect(),
+        removed_headings: removed_headings
+            .into_iter()
+            .map(|(_, text)| text.clone())
+            .collect(),
+        added_headings,
+        similarity,
+    })
 }
 
-fn push_query_candidate(
-    target: &mut Vec<String>,
-    seen: &mut HashSet<String>,
-    candidate: String,
-    max_queries: usize,
-) {
-    let normalized = normalize_for_search(&candidate);
-    if normalized.chars().count() < 2 {
-        return;
-    }
-    if seen.insert(normalized.clone()) {
-        target.push(normalized);
-    }
-    if target.len() > max_queries {
-        target.truncate(max_queries);
+pub(crate) fn list_file_styles(app: AppHandle, file_id: i64) -> CommandResult<Vec<FileStyleUsage>> {
+    let connection = open_database(&app)?;
+    let (relative_path, absolute_path) = load_file_paths(&connection, file_id)?;
+    let path = Path::new(&absolute_path);
+    if is_plain_text_path(path) {
+        return Err(format!(
+            "Could not list styles for '{relative_path}': only .docx files have styles."
+        ));
     }
+    collect_docx_style_usage(path)
 }
 
-fn collect_benchmark_queries(
-    connection: &Connection,
-    root_id_value: i64,
-    provided_queries: &[String],
-    max_queries: usize,
-) -> CommandResult<Vec<String>> {
-    let mut queries = Vec::new();
-    let mut seen = HashSet::new();
-
-    for provided in provided_queries {
-        for candidate in query_candidates_from_text(provided) {
-            if queries.len() >= max_queries {
-                return Ok(queries);
-            }
-            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
-        }
+pub(crate) fn preview_debug_stats(
+    app: AppHandle,
+    file_id: i64,
+) -> CommandResult<PreviewDebugStats> {
+    let connection = open_database(&app)?;
+    let (relative_path, absolute_path) = load_file_paths(&connection, file_id)?;
+    let path = Path::new(&absolute_path);
+    if is_plain_text_path(path) {
+        return Err(format!(
+            "Could not collect preview stats for '{relative_path}': only .docx files have run formatting."
+        ));
     }
+    collect_preview_debug_stats(path)
+}
 
-    let mut heading_statement = connection
-        .prepare(
-            "
-            SELECT h.text
-            FROM headings h
-            JOIN files f ON f.id = h.file_id
-            WHERE f.root_id = ?1
-            ORDER BY length(h.text) DESC, h.id ASC
-            LIMIT 240
-            ",
-        )
-        .map_err(|error| format!("Could not prepare benchmark heading query source: {error}"))?;
-    let heading_rows = heading_statement
-        .query_map(params![root_id_value], |row| row.get::<_, String>(0))
-        .map_err(|error| format!("Could not load benchmark heading query source: {error}"))?;
-    for row in heading_rows {
-        if queries.len() >= max_queries {
-            break;
-        }
-        let text = row.map_err(|error| format!("Could not parse benchmark heading text: {error}"))?;
-        for candidate in query_candidates_from_text(&text) {
-            if queries.len() >= max_queries {
-                break;
-            }
-            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
-        }
+pub(crate) fn export_heading_docx(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+) -> CommandResult<String> {
+    let connection = open_database(&app)?;
+    let (relative_path, absolute_path) = load_file_paths(&connection, file_id)?;
+    let source_path = Path::new(&absolute_path);
+    if is_plain_text_path(source_path) {
+        return Err(format!(
+            "Could not export '{relative_path}' as docx: only .docx files can be exported."
+        ));
     }
 
-    let mut author_statement = connection
-        .prepare(
-            "
-            SELECT a.text
-            FROM authors a
-            JOIN files f ON f.id = a.file_id
-            WHERE f.root_id = ?1
-            ORDER BY a.id DESC
-            LIMIT 120
-            ",
-        )
-        .map_err(|error| format!("Could not prepare benchmark author query source: {error}"))?;
-    let author_rows = author_statement
-        .query_map(params![root_id_value], |row| row.get::<_, String>(0))
-        .map_err(|error| format!("Could not load benchmark author query source: {error}"))?;
-    for row in author_rows {
-        if queries.len() >= max_queries {
-            break;
-        }
-        let text = row.map_err(|error| format!("Could not parse benchmark author text: {error}"))?;
-        for candidate in query_candidates_from_text(&text) {
-            if queries.len() >= max_queries {
-                break;
-            }
-            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
-        }
+    let paragraphs = parse_docx_paragraphs(source_path)?;
+    let heading = paragraphs
+        .iter()
+        .find(|paragraph| paragraph.order == heading_order && paragraph.heading_level.is_some())
+        .ok_or_else(|| format!("Heading order {heading_order} not found in '{relative_path}'."))?;
+    let styled_section = extract_styled_section(source_path, Some(heading_order), &heading.text);
+
+    let export_path = std::env::temp_dir().join(format!(
+        "blockfile-export-{}-{file_id}-{heading_order}-{}.docx",
+        std::process::id(),
+        now_ms()
+    ));
+    let written = write_section_docx(&export_path, source_path, &styled_section).and_then(|_| {
+        fs::read(&export_path)
+            .map_err(|error| format!("Could not read exported heading docx: {error}"))
+    });
+    let _ = fs::remove_file(&export_path);
+    Ok(base64::engine::general_purpose::STANDARD.encode(written?))
+}
+
+pub(crate) fn get_file_outline(app: AppHandle, file_id: i64) -> CommandResult<Vec<OutlineHeading>> {
+    let connection = open_database(&app)?;
+    let (_, absolute_path) = load_file_paths(&connection, file_id)?;
+    let paragraphs = parse_source_paragraphs(Path::new(&absolute_path))?;
+    Ok(heading_outline(&paragraphs))
+}
+
+pub(crate) fn get_heading_neighbors(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+) -> CommandResult<HeadingNeighbors> {
+    let connection = open_database(&app)?;
+    let (_, absolute_path) = load_file_paths(&connection, file_id)?;
+    let path = Path::new(&absolute_path);
+    let paragraphs = parse_source_paragraphs(path)?;
+    let (headings, _, _) = extract_preview_content(path)?;
+    heading_neighbors(&paragraphs, &headings, heading_order)
+        .ok_or_else(|| format!("Heading {heading_order} was not found in file {file_id}."))
+}
+
+pub(crate) fn get_highlighted_text(
+    app: AppHandle,
+    file_id: i64,
+    colors: Option<Vec<String>>,
+) -> CommandResult<Vec<HighlightedBlock>> {
+    let connection = open_database(&app)?;
+    let (_, absolute_path) = load_file_paths(&connection, file_id)?;
+    let path = Path::new(&absolute_path);
+    if is_plain_text_path(path) {
+        return Ok(Vec::new());
     }
+    let colors = colors
+        .unwrap_or_default()
+        .iter()
+        .map(|color| color.trim().to_ascii_lowercase())
+        .filter(|color| !color.is_empty())
+        .collect::<Vec<String>>();
+    extract_highlighted_blocks(path, &colors)
+}
 
-    let mut file_statement = connection
-        .prepare(
-            "
-            SELECT relative_path
-            FROM files
-            WHERE root_id = ?1
-            ORDER BY heading_count DESC, modified_ms DESC
-            LIMIT 180
-            ",
+pub(crate) fn get_heading_preview_html(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+) -> CommandResult<String> {
+    if heading_order <= 0 {
+        return Ok(String::new());
+    }
+
+    let connection = open_database(&app)?;
+    let absolute_path = connection
+        .query_row(
+            "SELECT absolute_path FROM files WHERE id = ?1",
+            params![file_id],
+            |row| row.get::<_, String>(0),
         )
-        .map_err(|error| format!("Could not prepare benchmark file query source: {error}"))?;
-    let file_rows = file_statement
-        .query_map(params![root_id_value], |row| row.get::<_, String>(0))
-        .map_err(|error| format!("Could not load benchmark file query source: {error}"))?;
-    for row in file_rows {
-        if queries.len() >= max_queries {
-            break;
-        }
-        let relative_path_value =
-            row.map_err(|error| format!("Could not parse benchmark file relative path: {error}"))?;
-        let file_name = file_name_from_relative(&relative_path_value);
-        for candidate in query_candidates_from_text(&file_name) {
-            if queries.len() >= max_queries {
-                break;
-            }
-            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
-        }
+        .map_err(|error| format!("Could not load heading preview source file: {error}"))?;
+
+    extract_heading_preview_html(Path::new(&absolute_path), heading_order)
+}
+
+pub(crate) fn get_heading_xml(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+) -> CommandResult<HeadingXml> {
+    let connection = open_database(&app)?;
+    let (relative_path, absolute_path) = load_file_paths(&connection, file_id)?;
+    let path = Path::new(&absolute_path);
+    if is_plain_text_path(path) {
+        return Err(format!(
+            "Could not read heading XML for '{relative_path}': only .docx files have XML."
+        ));
     }
 
-    if queries.is_empty() {
-        for fallback in [
-            "introduction",
-            "method",
-            "results",
-            "discussion",
-            "conclusion",
-            "references",
-        ] {
-            push_query_candidate(
-                &mut queries,
-                &mut seen,
-                fallback.to_string(),
-                max_queries,
-            );
-        }
+    let paragraphs = parse_docx_paragraphs(path)?;
+    let heading_range = build_heading_ranges(&paragraphs)
+        .into_iter()
+        .find(|range| range.order == heading_order)
+        .ok_or_else(|| format!("Heading order {heading_order} not found in '{relative_path}'."))?;
+
+    let document_xml = read_docx_part(path, "word/document.xml")?
+        .ok_or_else(|| format!("Missing word/document.xml in '{relative_path}'"))?;
+    let document = Document::parse(&document_xml)
+        .map_err(|error| format!("Could not parse document XML '{relative_path}': {error}"))?;
+    let paragraph_nodes = document
+        .descendants()
+        .filter(|node| has_tag(*node, "p"))
+        .collect::<Vec<Node<'_, '_>>>();
+
+    if heading_range.start_index >= paragraph_nodes.len()
+        || heading_range.end_index == 0
+        || heading_range.end_index > paragraph_nodes.len()
+    {
+        return Err(format!(
+            "Heading range is out of bounds in '{relative_path}'."
+        ));
     }
 
-    Ok(queries)
+    let start = paragraph_nodes[heading_range.start_index].range().start;
+    let end = paragraph_nodes[heading_range.end_index - 1].range().end;
+    if start >= end || end > document_xml.len() {
+        return Err(format!(
+            "Could not resolve heading XML range in '{relative_path}'."
+        ));
+    }
+
+    let namespace_declarations = document
+        .root_element()
+        .namespaces()
+        .filter(|namespace| namespace.name() != Some("xml"))
+        .map(|namespace| match namespace.name() {
+            Some(prefix) => format!("xmlns:{prefix}=\"{}\"", namespace.uri()),
+            None => format!("xmlns=\"{}\"", namespace.uri()),
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    Ok(HeadingXml {
+        file_id,
+        heading_order,
+        heading_text: paragraphs[heading_range.start_index].text.clone(),
+        level: heading_range.level,
+        xml: document_xml[start..end].to_string(),
+        namespace_declarations,
+    })
 }
 
-fn sample_file_ids(
-    connection: &Connection,
-    root_id_value: i64,
-    limit: usize,
-) -> CommandResult<Vec<i64>> {
-    if limit == 0 {
-        return Ok(Vec::new());
-    }
+const MAX_CHUNK_CONTEXT: usize = 5;
+const READING_WORDS_PER_MINUTE: i64 = 200;
 
-    let limit_i64 = i64::try_from(limit).unwrap_or(i64::MAX);
+pub(crate) fn get_chunk_context(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+    before: Option<usize>,
+    after: Option<usize>,
+) -> CommandResult<Vec<ChunkContextEntry>> {
+    let connection = open_database(&app)?;
+    load_chunk_context(
+        &connection,
+        file_id,
+        heading_order,
+        before.unwrap_or(1).min(MAX_CHUNK_CONTEXT),
+        after.unwrap_or(1).min(MAX_CHUNK_CONTEXT),
+    )
+}
+
+fn load_chunk_context(
+    connection: &Connection,
+    file_id: i64,
+    heading_order: i64,
+    before: usize,
+    after: usize,
+) -> CommandResult<Vec<ChunkContextEntry>> {
     let mut statement = connection
         .prepare(
             "
-            SELECT id
-            FROM files
-            WHERE root_id = ?1
-            ORDER BY heading_count DESC, modified_ms DESC, id DESC
-            LIMIT ?2
+            SELECT chunk_order, heading_order, heading_level, heading_text, author_text, chunk_text
+            FROM chunks
+            WHERE file_id = ?1 AND kind = 'chunk'
+            ORDER BY chunk_order ASC
             ",
         )
-        .map_err(|error| format!("Could not prepare benchmark file preview sample query: {error}"))?;
+        .map_err(|error| format!("Could not prepare chunk context query: {error}"))?;
+
     let rows = statement
-        .query_map(params![root_id_value, limit_i64], |row| row.get::<_, i64>(0))
-        .map_err(|error| format!("Could not run benchmark file preview sample query: {error}"))?;
+        .query_map(params![file_id], |row| {
+            Ok(ChunkContextEntry {
+                chunk_order: row.get(0)?,
+                heading_order: row.get(1)?,
+                heading_level: row.get(2)?,
+                heading_text: row.get(3)?,
+                author_text: row.get(4)?,
+                chunk_text: row.get(5)?,
+                is_match: false,
+            })
+        })
+        .map_err(|error| format!("Could not read chunk context rows: {error}"))?;
 
-    let mut output = Vec::new();
+    let mut chunks = Vec::new();
     for row in rows {
-        output.push(row.map_err(|error| format!("Could not parse sampled file id: {error}"))?);
+        chunks.push(row.map_err(|error| format!("Could not parse chunk context row: {error}"))?);
     }
-    Ok(output)
-}
 
-fn sample_heading_refs(
-    connection: &Connection,
-    root_id_value: i64,
-    limit: usize,
-) -> CommandResult<Vec<(i64, i64)>> {
-    if limit == 0 {
+    let Some(anchor) = chunks
+        .iter()
+        .position(|chunk| chunk.heading_order == Some(heading_order))
+    else {
         return Ok(Vec::new());
-    }
+    };
 
-    let limit_i64 = i64::try_from(limit).unwrap_or(i64::MAX);
-    let mut statement = connection
-        .prepare(
-            "
-            SELECT h.file_id, h.heading_order
-            FROM headings h
-            JOIN files f ON f.id = h.file_id
-            WHERE f.root_id = ?1
-            ORDER BY f.heading_count DESC, h.heading_order ASC
-            LIMIT ?2
-            ",
+    let start = anchor.saturating_sub(before);
+    let end = (anchor + after + 1).min(chunks.len());
+    chunks[anchor].is_match = true;
+    Ok(chunks.drain(start..end).collect())
+}
+
+pub(crate) fn format_citation(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+    style: Option<CitationStyle>,
+) -> CommandResult<String> {
+    let connection = open_database(&app)?;
+    let heading_text = connection
+        .query_row(
+            "SELECT text FROM headings WHERE file_id = ?1 AND heading_order = ?2",
+            params![file_id, heading_order],
+            |row| row.get::<_, String>(0),
         )
-        .map_err(|error| {
-            format!("Could not prepare benchmark heading preview sample query: {error}")
-        })?;
+        .map_err(|error| format!("Could not load heading for citation: {error}"))?;
+    let next_heading_order = connection
+        .query_row(
+            "SELECT MIN(heading_order) FROM headings WHERE file_id = ?1 AND heading_order > ?2",
+            params![file_id, heading_order],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .map_err(|error| format!("Could not load next heading for citation: {error}"))?;
+
+    let mut statement = connection
+        .prepare("SELECT author_order, text FROM authors WHERE file_id = ?1 ORDER BY author_order")
+        .map_err(|error| format!("Could not prepare citation author query: {error}"))?;
     let rows = statement
-        .query_map(params![root_id_value, limit_i64], |row| {
-            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+        .query_map(params![file_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
         })
-        .map_err(|error| format!("Could not run benchmark heading preview sample query: {error}"))?;
-
-    let mut output = Vec::new();
+        .map_err(|error| format!("Could not read citation authors: {error}"))?;
+    let mut authors = Vec::new();
     for row in rows {
-        output.push(
-            row.map_err(|error| format!("Could not parse sampled heading reference: {error}"))?,
-        );
+        authors.push(row.map_err(|error| format!("Could not parse citation author: {error}"))?);
     }
-    Ok(output)
+
+    let author = citation_author(&authors, heading_order, next_heading_order);
+    Ok(format_citation_text(
+        &heading_text,
+        author,
+        style.unwrap_or_default(),
+    ))
 }
 
-pub(crate) async fn benchmark_root_performance(
-    app: AppHandle,
-    path: String,
-    queries: Option<Vec<String>>,
-    iterations: Option<usize>,
-    limit: Option<usize>,
-    include_semantic: Option<bool>,
-    preview_samples: Option<usize>,
+fn citation_author(
+    authors: &[(i64, String)],
+    heading_order: i64,
+    next_heading_order: Option<i64>,
+) -> Option<&str> {
+    let section_end = next_heading_order.unwrap_or(i64::MAX);
+    let within_section = authors
+        .iter()
+        .find(|(order, _)| *order > heading_order && *order < section_end);
+    within_section
+        .or_else(|| {
+            authors
+                .iter()
+                .rev()
+                .find(|(order, _)| *order < heading_order)
+        })
+        .map(|(_, text)| text.as_str())
+}
+
+fn citation_short_name(author_line: &str) -> String {
+    let surname = author_line
+        .split(|character: char| character == ',' || character.is_whitespace())
+        .find(|token| !token.is_empty())
+        .unwrap_or_default()
+        .trim_matches(|character: char| !character.is_alphanumeric());
+    match first_year_token(author_line) {
+        Some(year) => format!("{surname} {:02}", year % 100),
+        None => surname.to_string(),
+    }
+}
+
+fn debate_citation_line(author: &str) -> String {
+    format!("{} — {author}", citation_short_name(author))
+}
+
+fn format_citation_text(heading: &str, author: Option<&str>, style: CitationStyle) -> String {
+    let heading = heading.trim();
+    let Some(author) = author.map(str::trim).filter(|author| !author.is_empty()) else {
+        return heading.to_string();
+    };
+
+    match style {
+        CitationStyle::Debate => format!("{heading}\n{}", debate_citation_line(author)),
+        CitationStyle::Mla => format!(
+            "{}. \"{}.\"",
+            author.trim_end_matches('.'),
+            heading.trim_end_matches('.')
+        ),
+    }
+}
+
+pub(crate) async fn search_index(
+    app: AppHandle,
+    query: String,
+    root_path: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    crate::async_runtime::spawn_blocking(move || {
+        query_engine::search_lexical(&app, &query, root_path, limit)
+    })
+    .await
+    .map_err(|error| format!("Lexical search command failed: {error}"))?
+}
+
+pub(crate) async fn search_index_semantic(
+    app: AppHandle,
+    query: String,
+    root_path: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    query_engine::search_semantic(&app, &query, root_path, limit).await
+}
+
+pub(crate) async fn count_matches(
+    app: AppHandle,
+    query: String,
+    root_path: Option<String>,
+) -> CommandResult<usize> {
+    crate::async_runtime::spawn_blocking(move || {
+        query_engine::count_matches(&app, &query, root_path)
+    })
+    .await
+    .map_err(|error| format!("Count matches command failed: {error}"))?
+}
+
+pub(crate) async fn find_similar(
+    app: AppHandle,
+    file_id: i64,
+    heading_order: i64,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    query_engine::find_similar(&app, file_id, heading_order, limit).await
+}
+
+pub(crate) async fn search_index_hybrid(
+    app: AppHandle,
+    query: String,
+    mut options: HybridSearchOptions,
+) -> CommandResult<SearchResponse> {
+    options.argument_type = options
+        .argument_type
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty());
+    if let Some(value) = &options.argument_type {
+        if value != ARGUMENT_TYPE_CARD && value != ARGUMENT_TYPE_ANALYTIC {
+            return Err(format!(
+                "Unknown argument type filter '{value}'; expected '{ARGUMENT_TYPE_CARD}' or '{ARGUMENT_TYPE_ANALYTIC}'."
+            ));
+        }
+    }
+
+    options.request_id = options
+        .request_id
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if options.stream.unwrap_or(false) && options.request_id.is_none() {
+        return Err("Streaming search requires a request_id.".to_string());
+    }
+
+    let response = query_engine::search_hybrid(&app, &query, &options).await?;
+
+    if options.stream_request_id().is_some() {
+        return Ok(SearchResponse {
+            hits: Vec::new(),
+            ..response
+        });
+    }
+    Ok(response)
+}
+
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+fn percentile(sorted_samples: &[f64], percentile: f64) -> f64 {
+    if sorted_samples.is_empty() {
+        return 0.0;
+    }
+    let clamped = percentile.clamp(0.0, 1.0);
+    let last = sorted_samples.len().saturating_sub(1);
+    let index = ((last as f64) * clamped).round() as usize;
+    sorted_samples[index.min(last)]
+}
+
+fn latency_stats(samples: &[f64]) -> BenchmarkLatencyStats {
+    if samples.is_empty() {
+        return BenchmarkLatencyStats::default();
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|left, right| {
+        left.partial_cmp(right)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let sum = sorted.iter().copied().sum::<f64>();
+    BenchmarkLatencyStats {
+        runs: sorted.len(),
+        min_ms: *sorted.first().unwrap_or(&0.0),
+        p50_ms: percentile(&sorted, 0.50),
+        p95_ms: percentile(&sorted, 0.95),
+        max_ms: *sorted.last().unwrap_or(&0.0),
+        mean_ms: sum / (sorted.len() as f64),
+    }
+}
+
+fn build_task_result(
+    enabled: bool,
+    samples: &[f64],
+    total_hits: usize,
+    error: Option<String>,
+) -> BenchmarkTaskResult {
+    BenchmarkTaskResult {
+        enabled,
+        error,
+        total_hits,
+        latency: latency_stats(samples),
+    }
+}
+
+fn query_candidates_from_text(text: &str) -> Vec<String> {
+    let normalized = normalize_for_search(text);
+    if normalized.is_empty() {
+        return Vec::new();
+    }
+
+    let tokens = normalized
+        .split_whitespace()
+        .filter(|token| token.len() >= 3)
+        .map(|token| token.to_string())
+        .collect::<Vec<String>>();
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+    let head_three = tokens.iter().take(3).cloned().collect::<Vec<String>>();
+    if !head_three.is_empty() {
+        candidates.push(head_three.join(" "));
+    }
+    if tokens.len() >= 2 {
+        candidates.push(tokens.iter().take(2).cloned().collect::<Vec<String>>().join(" "));
+    }
+    candidates.push(tokens[0].clone());
+    if tokens.len() >= 4 {
+        let tail_two = tokens[tokens.len().saturating_sub(2)..]
+            .iter()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(" ");
+        candidates.push(tail_two);
+    }
+
+    candidates
+}
+
+fn push_query_candidate(
+    target: &mut Vec<String>,
+    seen: &mut HashSet<String>,
+    candidate: String,
+    max_queries: usize,
+) {
+    let normalized = normalize_for_search(&candidate);
+    if normalized.chars().count() < 2 {
+        return;
+    }
+    if seen.insert(normalized.clone()) {
+        target.push(normalized);
+    }
+    if target.len() > max_queries {
+        target.truncate(max_queries);
+    }
+}
+
+fn collect_benchmark_queries(
+    connection: &Connection,
+    root_id_value: i64,
+    provided_queries: &[String],
+    max_queries: usize,
+) -> CommandResult<Vec<String>> {
+    let mut queries = Vec::new();
+    let mut seen = HashSet::new();
+
+    for provided in provided_queries {
+        for candidate in query_candidates_from_text(provided) {
+            if queries.len() >= max_queries {
+                return Ok(queries);
+            }
+            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
+        }
+    }
+
+    let mut heading_statement = connection
+        .prepare(
+            "
+            SELECT h.text
+            FROM headings h
+            JOIN files f ON f.id = h.file_id
+            WHERE f.root_id = ?1
+            ORDER BY length(h.text) DESC, h.id ASC
+            LIMIT 240
+            ",
+        )
+        .map_err(|error| format!("Could not prepare benchmark heading query source: {error}"))?;
+    let heading_rows = heading_statement
+        .query_map(params![root_id_value], |row| row.get::<_, String>(0))
+        .map_err(|error| format!("Could not load benchmark heading query source: {error}"))?;
+    for row in heading_rows {
+        if queries.len() >= max_queries {
+            break;
+        }
+        let text = row.map_err(|error| format!("Could not parse benchmark heading text: {error}"))?;
+        for candidate in query_candidates_from_text(&text) {
+            if queries.len() >= max_queries {
+                break;
+            }
+            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
+        }
+    }
+
+    let mut author_statement = connection
+        .prepare(
+            "
+            SELECT a.text
+            FROM authors a
+            JOIN files f ON f.id = a.file_id
+            WHERE f.root_id = ?1
+            ORDER BY a.id DESC
+            LIMIT 120
+            ",
+        )
+        .map_err(|error| format!("Could not prepare benchmark author query source: {error}"))?;
+    let author_rows = author_statement
+        .query_map(params![root_id_value], |row| row.get::<_, String>(0))
+        .map_err(|error| format!("Could not load benchmark author query source: {error}"))?;
+    for row in author_rows {
+        if queries.len() >= max_queries {
+            break;
+        }
+        let text = row.map_err(|error| format!("Could not parse benchmark author text: {error}"))?;
+        for candidate in query_candidates_from_text(&text) {
+            if queries.len() >= max_queries {
+                break;
+            }
+            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
+        }
+    }
+
+    let mut file_statement = connection
+        .prepare(
+            "
+            SELECT relative_path
+            FROM files
+            WHERE root_id = ?1
+            ORDER BY heading_count DESC, modified_ms DESC
+            LIMIT 180
+            ",
+        )
+        .map_err(|error| format!("Could not prepare benchmark file query source: {error}"))?;
+    let file_rows = file_statement
+        .query_map(params![root_id_value], |row| row.get::<_, String>(0))
+        .map_err(|error| format!("Could not load benchmark file query source: {error}"))?;
+    for row in file_rows {
+        if queries.len() >= max_queries {
+            break;
+        }
+        let relative_path_value =
+            row.map_err(|error| format!("Could not parse benchmark file relative path: {error}"))?;
+        let file_name = file_name_from_relative(&relative_path_value);
+        for candidate in query_candidates_from_text(&file_name) {
+            if queries.len() >= max_queries {
+                break;
+            }
+            push_query_candidate(&mut queries, &mut seen, candidate, max_queries);
+        }
+    }
+
+    if queries.is_empty() {
+        for fallback in [
+            "introduction",
+            "method",
+            "results",
+            "discussion",
+            "conclusion",
+            "references",
+        ] {
+            push_query_candidate(
+                &mut queries,
+                &mut seen,
+                fallback.to_string(),
+                max_queries,
+            );
+        }
+    }
+
+    Ok(queries)
+}
+
+fn sample_file_ids(
+    connection: &Connection,
+    root_id_value: i64,
+    limit: usize,
+) -> CommandResult<Vec<i64>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let limit_i64 = i64::try_from(limit).unwrap_or(i64::MAX);
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT id
+            FROM files
+            WHERE root_id = ?1
+            ORDER BY heading_count DESC, modified_ms DESC, id DESC
+            LIMIT ?2
+            ",
+        )
+        .map_err(|error| format!("Could not prepare benchmark file preview sample query: {error}"))?;
+    let rows = statement
+        .query_map(params![root_id_value, limit_i64], |row| row.get::<_, i64>(0))
+        .map_err(|error| format!("Could not run benchmark file preview sample query: {error}"))?;
+
+    let mut output = Vec::new();
+    for row in rows {
+        output.push(row.map_err(|error| format!("Could not parse sampled file id: {error}"))?);
+    }
+    Ok(output)
+}
+
+fn sample_heading_refs(
+    connection: &Connection,
+    root_id_value: i64,
+    limit: usize,
+) -> CommandResult<Vec<(i64, i64)>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+
+    let limit_i64 = i64::try_from(limit).unwrap_or(i64::MAX);
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT h.file_id, h.heading_order
+            FROM headings h
+            JOIN files f ON f.id = h.file_id
+            WHERE f.root_id = ?1
+            ORDER BY f.heading_count DESC, h.heading_order ASC
+            LIMIT ?2
+            ",
+        )
+        .map_err(|error| {
+            format!("Could not prepare benchmark heading preview sample query: {error}")
+        })?;
+    let rows = statement
+        .query_map(params![root_id_value, limit_i64], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|error| format!("Could not run benchmark heading preview sample query: {error}"))?;
+
+    let mut output = Vec::new();
+    for row in rows {
+        output.push(
+            row.map_err(|error| format!("Could not parse sampled heading reference: {error}"))?,
+        );
+    }
+    Ok(output)
+}
+
+pub(crate) async fn benchmark_root_performance(
+    app: AppHandle,
+    path: String,
+    queries: Option<Vec<String>>,
+    iterations: Option<usize>,
+    limit: Option<usize>,
+    include_semantic: Option<bool>,
+    preview_samples: Option<usize>,
 ) -> CommandResult<BenchmarkReport> {
     let benchmark_started = Instant::now();
     let canonical_root = canonicalize_folder(&path)?;
     let root_path = path_display(&canonical_root);
 
-    add_root(app.clone(), root_path.clone())?;
-    let index_full = index_root(app.clone(), root_path.clone())?;
-    let index_incremental = index_root(app.clone(), root_path.clone())?;
+    add_root(app.clone(), root_path.clone())?;
+    let benchmark_options = IndexOptions {
+        benchmark: true,
+        ..IndexOptions::default()
+    };
+    let index_full = index_root(app.clone(), root_path.clone(), benchmark_options.clone())?;
+    let index_incremental = index_root(app.clone(), root_path.clone(), benchmark_options)?;
+
+    let connection = open_database(&app)?;
+    let root_id_value = root_id(&connection, &root_path)?.ok_or_else(|| {
+        format!(
+            "Benchmark root id missing for '{}'. Try indexing again.",
+            root_path
+        )
+    })?;
+
+    let benchmark_iterations = iterations.unwrap_or(3).clamp(1, 12);
+    let benchmark_limit = limit.unwrap_or(80).clamp(10, 400);
+    let benchmark_include_semantic = include_semantic.unwrap_or(true);
+    let benchmark_preview_samples = preview_samples.unwrap_or(16).clamp(0, 240);
+    let provided_queries = queries.unwrap_or_default();
+    let benchmark_queries =
+        collect_benchmark_queries(&connection, root_id_value, &provided_queries, 32)?;
+
+    let mut search = BenchmarkSearchSummary {
+        query_count: benchmark_queries.len(),
+        iterations: benchmark_iterations,
+        limit: benchmark_limit,
+        ..BenchmarkSearchSummary::default()
+    };
+
+    let mut lexical_raw_samples = Vec::new();
+    let mut lexical_raw_hits = 0_usize;
+    let mut lexical_raw_error: Option<String> = None;
+    let lexical_options = lexical::LexicalSearchOptions::new(&[root_id_value], benchmark_limit);
+    'lexical_raw: for _ in 0..benchmark_iterations {
+        for query in &benchmark_queries {
+            let started = Instant::now();
+            match lexical::search(&app, query, &lexical_options) {
+                Ok(hits) => {
+                    lexical_raw_samples.push(elapsed_ms(started));
+                    lexical_raw_hits = lexical_raw_hits.saturating_add(hits.len());
+                }
+                Err(error) => {
+                    lexical_raw_error = Some(error);
+                    break 'lexical_raw;
+                }
+            }
+        }
+    }
+    search.lexical_raw = build_task_result(
+        true,
+        &lexical_raw_samples,
+        lexical_raw_hits,
+        lexical_raw_error,
+    );
+
+    query_engine::clear_query_cache();
+    for query in &benchmark_queries {
+        let _ = query_engine::search_lexical(
+            &app,
+            query,
+            Some(root_path.clone()),
+            Some(benchmark_limit),
+        );
+    }
+    let mut lexical_cached_samples = Vec::new();
+    let mut lexical_cached_hits = 0_usize;
+    let mut lexical_cached_error: Option<String> = None;
+    'lexical_cached: for _ in 0..benchmark_iterations {
+        for query in &benchmark_queries {
+            let started = Instant::now();
+            match query_engine::search_lexical(
+                &app,
+                query,
+                Some(root_path.clone()),
+                Some(benchmark_limit),
+            ) {
+                Ok(hits) => {
+                    lexical_cached_samples.push(elapsed_ms(started));
+                    lexical_cached_hits = lexical_cached_hits.saturating_add(hits.len());
+                }
+                Err(error) => {
+                    lexical_cached_error = Some(error);
+                    break 'lexical_cached;
+                }
+            }
+        }
+    }
+    search.lexical_cached = build_task_result(
+        true,
+        &lexical_cached_samples,
+        lexical_cached_hits,
+        lexical_cached_error,
+    );
+
+    if benchmark_include_semantic {
+        let hybrid_options = HybridSearchOptions {
+            root_path: Some(root_path.clone()),
+            limit: Some(benchmark_limit),
+            ..HybridSearchOptions::default()
+        };
+        query_engine::clear_query_cache();
+        for query in &benchmark_queries {
+            let _ = query_engine::search_hybrid(&app, query, &hybrid_options).await;
+        }
+
+        let mut hybrid_samples = Vec::new();
+        let mut hybrid_hits = 0_usize;
+        let mut hybrid_error: Option<String> = None;
+        'hybrid: for _ in 0..benchmark_iterations {
+            for query in &benchmark_queries {
+                let started = Instant::now();
+                match query_engine::search_hybrid(&app, query, &hybrid_options).await {
+                    Ok(response) => {
+                        hybrid_samples.push(elapsed_ms(started));
+                        hybrid_hits = hybrid_hits.saturating_add(response.hits.len());
+                    }
+                    Err(error) => {
+                        hybrid_error = Some(error);
+                        break 'hybrid;
+                    }
+                }
+            }
+        }
+        search.hybrid = build_task_result(true, &hybrid_samples, hybrid_hits, hybrid_error);
+
+        let mut semantic_samples = Vec::new();
+        let mut semantic_hits = 0_usize;
+        let mut semantic_error: Option<String> = None;
+        if let Some(warm_query) = benchmark_queries.first() {
+            let _ = query_engine::search_semantic(
+                &app,
+                warm_query,
+                Some(root_path.clone()),
+                Some(benchmark_limit),
+            )
+            .await;
+        }
+        'semantic: for _ in 0..benchmark_iterations {
+            for query in &benchmark_queries {
+                let started = Instant::now();
+                match query_engine::search_semantic(
+                    &app,
+                    query,
+                    Some(root_path.clone()),
+                    Some(benchmark_limit),
+                )
+                .await
+                {
+                    Ok(hits) => {
+                        semantic_samples.push(elapsed_ms(started));
+                        semantic_hits = semantic_hits.saturating_add(hits.len());
+                    }
+                    Err(error) => {
+                        semantic_error = Some(error);
+                        break 'semantic;
+                    }
+                }
+            }
+        }
+        search.semantic = build_task_result(true, &semantic_samples, semantic_hits, semantic_error);
+    } else {
+        search.hybrid = build_task_result(false, &[], 0, None);
+        search.semantic = build_task_result(false, &[], 0, None);
+    }
+
+    let snapshot_started = Instant::now();
+    let _ = get_index_snapshot(app.clone(), root_path.clone(), None, None)?;
+    let mut preview = BenchmarkPreviewSummary {
+        snapshot_ms: elapsed_ms(snapshot_started),
+        ..BenchmarkPreviewSummary::default()
+    };
+
+    let sampled_file_ids = sample_file_ids(&connection, root_id_value, benchmark_preview_samples)?;
+    let mut file_preview_samples = Vec::new();
+    let mut file_preview_hits = 0_usize;
+    let mut file_preview_error: Option<String> = None;
+    for file_id in sampled_file_ids {
+        let started = Instant::now();
+        match get_file_preview(app.clone(), file_id) {
+            Ok(file_preview) => {
+                file_preview_samples.push(elapsed_ms(started));
+                file_preview_hits =
+                    file_preview_hits.saturating_add(usize::try_from(file_preview.heading_count).unwrap_or(0));
+            }
+            Err(error) => {
+                file_preview_error = Some(error);
+                break;
+            }
+        }
+    }
+    preview.file_preview = build_task_result(
+        benchmark_preview_samples > 0,
+        &file_preview_samples,
+        file_preview_hits,
+        file_preview_error,
+    );
+
+    let sampled_heading_refs =
+        sample_heading_refs(&connection, root_id_value, benchmark_preview_samples)?;
+    let mut heading_preview_samples = Vec::new();
+    let mut heading_preview_hits = 0_usize;
+    let mut heading_preview_error: Option<String> = None;
+    for (file_id, heading_order) in sampled_heading_refs {
+        let started = Instant::now();
+        match get_heading_preview_html(app.clone(), file_id, heading_order) {
+            Ok(html) => {
+                heading_preview_samples.push(elapsed_ms(started));
+                if !html.trim().is_empty() {
+                    heading_preview_hits = heading_preview_hits.saturating_add(1);
+                }
+            }
+            Err(error) => {
+                heading_preview_error = Some(error);
+                break;
+            }
+        }
+    }
+    preview.heading_preview_html = build_task_result(
+        benchmark_preview_samples > 0,
+        &heading_preview_samples,
+        heading_preview_hits,
+        heading_preview_error,
+    );
+
+    Ok(BenchmarkReport {
+        root_path,
+        index_full,
+        index_incremental,
+        queries: benchmark_queries,
+        search,
+        preview,
+        generated_at_ms: now_ms(),
+        elapsed_ms: elapsed_ms(benchmark_started).round() as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use base64::Engine;
+
+    use rusqlite::params;
+
+    use super::{
+        add_capture_heading, add_root, capture_target_stats, citation_author, classify_index_drift,
+        configure_index_parallelism, delete_capture_heading, delete_capture_headings,
+        delete_capture_target, diff_files, export_capture_html, export_captures,
+        export_heading_docx, files_by_author, find_duplicate_files, format_citation_text,
+        get_capture_target_preview, get_file_outline, get_file_previews, get_global_stats,
+        get_heading_neighbors, get_heading_preview_html, get_heading_xml, get_index_snapshot,
+        index_root, insert_capture, latency_stats, list_capture_targets, list_file_styles,
+        list_trash, load_chunk_context, move_capture_heading, move_heading_across_targets,
+        parse_index_batch, preview_capture, preview_debug_stats, preview_move_capture_heading,
+        query_candidates_from_text, rebuild_lexical_index_now, refresh_metadata,
+        rename_capture_target, reorder_capture_heading, resolve_capture_marker,
+        restore_capture_heading, root_health, scan_source_metadata, search_index,
+        search_index_hybrid, set_capture_banner, set_default_capture_target,
+        summarize_size_buckets, write_index_snapshot,
+    };
+    use crate::db::open_database;
+    use crate::docx_parse::{parse_docx_paragraphs, read_docx_part};
+    use crate::preview::extract_preview_content;
+    use crate::search::normalize_for_search;
+    use crate::test_support::{
+        document_xml, heading_xml, insert_file_row, paragraph_xml, temp_dir, test_app, write_docx,
+        write_docx_parts,
+    };
+    use crate::types::{
+        CaptureTargetSort, CitationStyle, ExistingFileMeta, FileHeading, FolderEntry, GlobalStats,
+        HybridSearchOptions, IndexCandidate, IndexOptions, IndexSnapshot, IndexedFile, SearchScope,
+    };
+    use crate::util::{capture_marker, now_ms};
+
+    #[test]
+    fn query_candidates_produces_multiple_usable_forms() {
+        let candidates = query_candidates_from_text("The quick brown fox jumps over fences");
+
+        assert!(candidates.contains(&"the quick brown".to_string()));
+        assert!(candidates.contains(&"the quick".to_string()));
+        assert!(candidates.contains(&"the".to_string()));
+        assert!(candidates.contains(&"over fences".to_string()));
+    }
+
+    #[test]
+    fn latency_stats_computes_expected_percentiles() {
+        let stats = latency_stats(&[10.0, 20.0, 30.0, 40.0]);
+
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min_ms, 10.0);
+        assert_eq!(stats.p50_ms, 30.0);
+        assert_eq!(stats.p95_ms, 40.0);
+        assert_eq!(stats.max_ms, 40.0);
+        assert_eq!(stats.mean_ms, 25.0);
+    }
+
+    #[test]
+    fn summarize_size_buckets_groups_files_by_size() {
+        let buckets = summarize_size_buckets(&[
+            (12 * 1024, 4.0, 2.0),
+            (40 * 1024, 6.0, 4.0),
+            (512 * 1024, 20.0, 10.0),
+            (3 * 1024 * 1024, 90.0, 30.0),
+        ]);
+
+        let counts = buckets
+            .iter()
+            .map(|bucket| (bucket.label.as_str(), bucket.file_count))
+            .collect::<Vec<(&str, usize)>>();
+        assert_eq!(counts, vec![("<100KB", 2), ("<1MB", 1), (">1MB", 1)]);
+        assert_eq!(buckets[0].mean_parse_ms, 5.0);
+        assert_eq!(buckets[0].mean_insert_ms, 3.0);
+        assert_eq!(buckets[0].mean_total_ms, 8.0);
+        assert_eq!(buckets[2].total_bytes, 3 * 1024 * 1024);
+    }
+
+    #[test]
+    fn exported_index_snapshot_round_trips() {
+        let snapshot = IndexSnapshot {
+            root_path: "/debate/files".to_string(),
+            indexed_at_ms: 1_700_000_000_000,
+            folders: vec![FolderEntry {
+                path: "Aff".to_string(),
+                name: "Aff".to_string(),
+                parent_path: Some(String::new()),
+                depth: 1,
+                file_count: 1,
+            }],
+            files: vec![IndexedFile {
+                id: 7,
+                file_name: "Plan.docx".to_string(),
+                relative_path: "Aff/Plan.docx".to_string(),
+                folder_path: "Aff".to_string(),
+                modified_ms: 1_700_000_000_000,
+                heading_count: 12,
+            }],
+        };
+
+        let output =
+            std::env::temp_dir().join(format!("blockfile-snapshot-{}.json", std::process::id()));
+        write_index_snapshot(&snapshot, &output).unwrap();
+        let written = std::fs::read_to_string(&output).unwrap();
+        let _ = std::fs::remove_file(&output);
+
+        let restored = serde_json::from_str::<IndexSnapshot>(&written).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn verify_index_reports_files_modified_on_disk() {
+        let root = std::env::temp_dir().join(format!("blockfile-verify-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("Kept.docx"), b"kept").unwrap();
+        std::fs::write(root.join("Edited.docx"), b"original").unwrap();
+        std::fs::write(root.join("Deleted.docx"), b"deleted").unwrap();
+
+        let existing_files = scan_source_metadata(&root, false)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (relative_path, (modified_ms, size)))| {
+                let meta = ExistingFileMeta {
+                    id: index as i64 + 1,
+                    modified_ms,
+                    size,
+                    file_hash: "hash".to_string(),
+                };
+                (relative_path, meta)
+            })
+            .collect::<HashMap<String, ExistingFileMeta>>();
+
+        std::fs::write(root.join("Edited.docx"), b"original plus new cards").unwrap();
+        std::fs::remove_file(root.join("Deleted.docx")).unwrap();
+        std::fs::write(root.join("Added.docx"), b"added").unwrap();
+
+        let disk_files = scan_source_metadata(&root, false).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let statuses = classify_index_drift(&existing_files, &disk_files)
+            .into_iter()
+            .map(|entry| (entry.relative_path, entry.status))
+            .collect::<Vec<(String, String)>>();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Added.docx".to_string(), "new".to_string()),
+                ("Deleted.docx".to_string(), "missing".to_string()),
+                ("Edited.docx".to_string(), "modified".to_string()),
+                ("Kept.docx".to_string(), "ok".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn corrupt_docx_is_reported_without_aborting_the_batch() {
+        let root = temp_dir("index-failures");
+        let good_path = root.join("Good.docx");
+        let corrupt_path = root.join("Corrupt.docx");
+        write_docx(
+            &good_path,
+            &format!(
+                "{}{}",
+                heading_xml(1, "Econ Advantage"),
+                paragraph_xml("Growth is resilient.")
+            ),
+        );
+        std::fs::write(&corrupt_path, b"this is not a zip archive").unwrap();
+
+        let candidates = [&corrupt_path, &good_path]
+            .into_iter()
+            .map(|path| IndexCandidate {
+                relative_path: path.file_name().unwrap().to_string_lossy().into_owned(),
+                absolute_path: path.to_path_buf(),
+                modified_ms: 0,
+                size: 0,
+                file_hash: String::new(),
+            })
+            .collect::<Vec<IndexCandidate>>();
+
+        let (parsed, failed) = parse_index_batch(&candidates, &IndexOptions::default());
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].relative_path, "Corrupt.docx");
+        assert!(!failed[0].error.is_empty());
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].candidate.relative_path, "Good.docx");
+        assert_eq!(parsed[0].headings[0].text, "Econ Advantage");
+    }
+
+    #[test]
+    fn single_thread_index_pool_still_indexes_every_file() {
+        let app = test_app("serial-index");
+        let root = temp_dir("serial-index-root");
+        let root_path = root.to_string_lossy().into_owned();
+        for name in ["Aff.docx", "Neg.docx", "Counterplan.docx"] {
+            write_docx(
+                &root.join(name),
+                &format!(
+                    "{}{}",
+                    heading_xml(1, &format!("{name} tag")),
+                    paragraph_xml("Card text")
+                ),
+            );
+        }
+
+        assert_eq!(configure_index_parallelism(Some(0)).unwrap(), 1);
+        add_root(app.clone(), root_path.clone()).unwrap();
+        let stats = index_root(
+            app,
+            root_path,
+            IndexOptions {
+                rebuild_semantic: false,
+                ..IndexOptions::default()
+            },
+        );
+        configure_index_parallelism(None).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let stats = stats.unwrap();
+        assert_eq!(stats.updated, 3);
+        assert_eq!(stats.headings_extracted, 3);
+        assert!(stats.failed.is_empty());
+    }
+
+    #[test]
+    fn empty_and_truncated_docx_files_are_skipped_as_corrupt() {
+        let app = test_app("corrupt-docx");
+        let root = temp_dir("corrupt-docx-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(&root.join("Good.docx"), &heading_xml(1, "Econ DA"));
+        std::fs::write(root.join("Empty.docx"), b"").unwrap();
+        let good_bytes = std::fs::read(root.join("Good.docx")).unwrap();
+        std::fs::write(
+            root.join("Truncated.docx"),
+            &good_bytes[..good_bytes.len() / 2],
+        )
+        .unwrap();
+
+        add_root(app.clone(), root_path.clone()).unwrap();
+        let stats = index_root(
+            app,
+            root_path,
+            IndexOptions {
+                rebuild_semantic: false,
+                ..IndexOptions::default()
+            },
+        );
+        let _ = std::fs::remove_dir_all(&root);
+
+        let stats = stats.unwrap();
+        assert_eq!(stats.updated, 1);
+        let mut failed = stats
+            .failed
+            .iter()
+            .map(|failure| (failure.relative_path.as_str(), failure.kind.as_str()))
+            .collect::<Vec<(&str, &str)>>();
+        failed.sort_unstable();
+        assert_eq!(
+            failed,
+            vec![("Empty.docx", "corrupt"), ("Truncated.docx", "corrupt")]
+        );
+    }
+
+    #[test]
+    fn identical_files_in_different_roots_form_one_duplicate_cluster() {
+        let app = test_app("duplicate-files");
+        let aff_root = temp_dir("duplicate-files-aff");
+        let archive_root = temp_dir("duplicate-files-archive");
+        write_docx(
+            &aff_root.join("Econ DA.docx"),
+            &format!(
+                "{}{}",
+                heading_xml(1, "Econ DA"),
+                paragraph_xml("Growth is steady.")
+            ),
+        );
+        std::fs::copy(
+            aff_root.join("Econ DA.docx"),
+            archive_root.join("Old Econ.docx"),
+        )
+        .unwrap();
+        write_docx(
+            &archive_root.join("Politics.docx"),
+            &heading_xml(1, "Politics DA"),
+        );
+        for root in [&aff_root, &archive_root] {
+            let root_path = root.to_string_lossy().into_owned();
+            add_root(app.clone(), root_path.clone()).unwrap();
+            index_root(
+                app.clone(),
+                root_path,
+                IndexOptions {
+                    rebuild_semantic: false,
+                    ..IndexOptions::default()
+                },
+            )
+            .unwrap();
+        }
+
+        let clusters = find_duplicate_files(app).unwrap();
+        let _ = std::fs::remove_dir_all(&aff_root);
+        let _ = std::fs::remove_dir_all(&archive_root);
+
+        assert_eq!(clusters.len(), 1);
+        let mut paths = clusters[0]
+            .files
+            .iter()
+            .map(|file| file.relative_path.as_str())
+            .collect::<Vec<&str>>();
+        paths.sort_unstable();
+        assert_eq!(paths, vec!["Econ DA.docx", "Old Econ.docx"]);
+        assert!(clusters[0].size > 0);
+    }
+
+    #[test]
+    fn heading_xml_covers_heading_range_and_parses() {
+        let app = test_app("heading-xml");
+        let root = temp_dir("heading-xml-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Neg.docx"),
+            &format!(
+                "{}{}{}{}{}",
+                heading_xml(1, "Politics DA"),
+                heading_xml(2, "Uniqueness"),
+                paragraph_xml("Capital is finite."),
+                heading_xml(2, "Link"),
+                paragraph_xml("The plan is unpopular.")
+            ),
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, &root_path, "Neg.docx");
+
+        let heading = get_heading_xml(app.clone(), file_id, 2).unwrap();
+        let missing = get_heading_xml(app, file_id, 40);
+        let _ = std::fs::remove_dir_all(&root);
+
+        let wrapped = format!(
+            "<w:body {}>{}</w:body>",
+            heading.namespace_declarations, heading.xml
+        );
+        let document = roxmltree::Document::parse(&wrapped).unwrap();
+        let texts = document
+            .descendants()
+            .filter(|node| node.tag_name().name() == "t")
+            .filter_map(|node| node.text())
+            .collect::<Vec<&str>>();
+        assert_eq!(heading.heading_text, "Uniqueness");
+        assert_eq!(heading.level, 2);
+        assert_eq!(texts, vec!["Uniqueness", "Capital is finite."]);
+        assert!(missing.unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn exported_heading_docx_unzips_to_section_document() {
+        let app = test_app("export-heading-docx");
+        let root = temp_dir("export-heading-docx-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Neg.docx"),
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "Politics DA"),
+                paragraph_xml("The plan is unpopular."),
+                heading_xml(1, "Econ DA"),
+                paragraph_xml("Growth is steady.")
+            ),
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, &root_path, "Neg.docx");
+
+        let encoded = export_heading_docx(app.clone(), file_id, 1).unwrap();
+        let missing = export_heading_docx(app, file_id, 2);
+        let exported = root.join("Exported.docx");
+        std::fs::write(
+            &exported,
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .unwrap(),
+        )
+        .unwrap();
+        let paragraphs = parse_docx_paragraphs(&exported).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let texts = paragraphs
+            .iter()
+            .map(|paragraph| paragraph.text.as_str())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<&str>>();
+        assert_eq!(texts, vec!["Politics DA", "The plan is unpopular."]);
+        assert_eq!(paragraphs[0].heading_level, Some(1));
+        assert!(missing.unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn file_outline_nests_subheadings_under_parents() {
+        let app = test_app("file-outline");
+        let root = temp_dir("file-outline-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Neg.docx"),
+            &format!(
+                "{}{}{}{}{}{}",
+                heading_xml(1, "Politics DA"),
+                heading_xml(2, "Uniqueness"),
+                paragraph_xml("Capital is finite."),
+                heading_xml(2, "Link"),
+                heading_xml(1, "Econ DA"),
+                heading_xml(2, "Impact")
+            ),
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, &root_path, "Neg.docx");
+
+        let outline = get_file_outline(app, file_id).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let summary = outline
+            .iter()
+            .map(|heading| {
+                (
+                    heading.text.as_str(),
+                    heading
+                        .children
+                        .iter()
+                        .map(|child| (child.text.as_str(), child.level))
+                        .collect::<Vec<(&str, i64)>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("Politics DA", vec![("Uniqueness", 2), ("Link", 2)]),
+                ("Econ DA", vec![("Impact", 2)]),
+            ]
+        );
+        assert_eq!(outline[0].level, 1);
+        assert!(outline[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn heading_neighbors_resolve_parent_siblings_and_child() {
+        let app = test_app("heading-neighbors");
+        let root = temp_dir("heading-neighbors-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Neg.docx"),
+            &[
+                heading_xml(1, "Econ DA"),
+                heading_xml(2, "Uniqueness"),
+                paragraph_xml("Growth is steady."),
+                heading_xml(2, "Link"),
+                heading_xml(3, "Spending link"),
+                heading_xml(2, "Impact"),
+                heading_xml(1, "Politics DA"),
+            ]
+            .concat(),
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, &root_path, "Neg.docx");
+
+        let link = get_heading_neighbors(app.clone(), file_id, 4).unwrap();
+        let econ = get_heading_neighbors(app.clone(), file_id, 1).unwrap();
+        let missing = get_heading_neighbors(app, file_id, 3);
+        let _ = std::fs::remove_dir_all(&root);
+
+        let text =
+            |heading: &Option<FileHeading>| heading.as_ref().map(|heading| heading.text.clone());
+        assert_eq!(text(&link.parent).as_deref(), Some("Econ DA"));
+        assert_eq!(text(&link.previous_sibling).as_deref(), Some("Uniqueness"));
+        assert_eq!(text(&link.next_sibling).as_deref(), Some("Impact"));
+        assert_eq!(text(&link.first_child).as_deref(), Some("Spending link"));
+        assert!(econ.parent.is_none() && econ.previous_sibling.is_none());
+        assert_eq!(text(&econ.next_sibling).as_deref(), Some("Politics DA"));
+        assert_eq!(text(&econ.first_child).as_deref(), Some("Uniqueness"));
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn diff_files_reports_shared_headings_and_partial_similarity() {
+        let app = test_app("diff-files");
+        let root = temp_dir("diff-files-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Old.docx"),
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "Econ DA"),
+                paragraph_xml("Growth is steady."),
+                heading_xml(1, "Politics DA"),
+                paragraph_xml("Capital is finite.")
+            ),
+        );
+        write_docx(
+            &root.join("New.docx"),
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "Econ DA"),
+                paragraph_xml("Growth is steady."),
+                heading_xml(1, "Warming Advantage"),
+                paragraph_xml("Emissions are rising.")
+            ),
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, old_id) = insert_file_row(&connection, &root_path, "Old.docx");
+        let (_, new_id) = insert_file_row(&connection, &root_path, "New.docx");
+
+        let diff = diff_files(app, old_id, new_id).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(diff.common_headings, vec!["Econ DA"]);
+        assert_eq!(diff.removed_headings, vec!["Politics DA"]);
+        assert_eq!(diff.added_headings, vec!["Warming Advantage"]);
+        assert!(diff.similarity > 0.0 && diff.similarity < 1.0);
+    }
+
+    #[test]
+    fn index_snapshot_filters_files_modified_since() {
+        let app = test_app("snapshot-since");
+        let root = temp_dir("snapshot-since-root");
+        let root_path = add_root(app.clone(), root.to_string_lossy().into_owned()).unwrap();
+        let connection = open_database(&app).unwrap();
+        let (_, old_id) = insert_file_row(&connection, &root_path, "Aff/Old.docx");
+        let (_, new_id) = insert_file_row(&connection, &root_path, "Neg/New.docx");
+        for (file_id, modified_ms) in [(old_id, 1_000), (new_id, 5_000)] {
+            connection
+                .execute(
+                    "UPDATE files SET modified_ms = ?2 WHERE id = ?1",
+                    params![file_id, modified_ms],
+                )
+                .unwrap();
+        }
+
+        let full = get_index_snapshot(app.clone(), root_path.clone(), None, None).unwrap();
+        let recent = get_index_snapshot(app.clone(), root_path.clone(), Some(1_000), None).unwrap();
+        let bare = get_index_snapshot(app, root_path, Some(1_000), Some(true)).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(full.files.len(), 2);
+        assert_eq!(
+            recent
+                .files
+                .iter()
+                .map(|file| file.id)
+                .collect::<Vec<i64>>(),
+            vec![new_id]
+        );
+        assert_eq!(recent.folders.len(), full.folders.len());
+        assert_eq!(bare.files.len(), 1);
+        assert!(bare.folders.is_empty());
+    }
+
+    #[test]
+    fn list_file_styles_counts_paragraph_style_usage() {
+        let app = test_app("list-file-styles");
+        let root = temp_dir("list-file-styles-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let styles_xml = "<w:styles xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:style w:type=\"paragraph\" w:styleId=\"Pocket\"><w:name w:val=\"heading 1\"/></w:style><w:style w:type=\"paragraph\" w:styleId=\"Cite\"><w:name w:val=\"F8 Cite\"/></w:style></w:styles>";
+        let cite_xml =
+            "<w:p><w:pPr><w:pStyle w:val=\"Cite\"/></w:pPr><w:r><w:t>Smith 21</w:t></w:r></w:p>";
+        let body = format!(
+            "{}{}{}{}{}{}",
+            heading_xml(1, "Econ DA").replace("Heading1", "Pocket"),
+            heading_xml(4, "Growth tag"),
+            cite_xml,
+            paragraph_xml("Growth is steady."),
+            heading_xml(4, "Capital tag"),
+            cite_xml,
+        );
+        write_docx_parts(
+            &root.join("Styles.docx"),
+            &[
+                ("word/document.xml", document_xml(&body)),
+                ("word/styles.xml", styles_xml.to_string()),
+            ],
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, &root_path, "Styles.docx");
+
+        let styles = list_file_styles(app, file_id).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let summary = styles
+            .iter()
+            .map(|style| {
+                (
+                    style.style_id.as_str(),
+                    style.name.as_str(),
+                    style.usage_count,
+                    style.heading_level,
+                    style.is_f8_cite,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("Cite", "F8 Cite", 2, None, true),
+                ("Heading4", "Heading4", 2, Some(4), false),
+                ("Pocket", "heading 1", 1, Some(1), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn chunk_context_returns_neighbors_in_order() {
+        let app = test_app("chunk-context");
+        let connection = open_database(&app).unwrap();
+        let (root_id, file_id) = insert_file_row(&connection, "/debate", "Aff.docx");
+        for (chunk_order, heading_order, kind) in [
+            (1, 2, "chunk"),
+            (2, 5, "chunk"),
+            (3, 9, "chunk"),
+            (4, 14, "chunk"),
+            (5, 5, "paragraph"),
+        ] {
+            connection
+                .execute(
+                    "INSERT INTO chunks(chunk_id, root_id, file_id, chunk_order, kind, heading_order, chunk_text, file_name, relative_path, absolute_path)
+                     VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, 'Aff.docx', 'Aff.docx', '/debate/Aff.docx')",
+                    params![
+                        format!("{root_id}:{file_id}:{chunk_order}"),
+                        root_id,
+                        file_id,
+                        chunk_order,
+                        kind,
+                        heading_order,
+                        format!("chunk {chunk_order}")
+                    ],
+                )
+                .unwrap();
+        }
+
+        let context = load_chunk_context(&connection, file_id, 9, 1, 1).unwrap();
+        let summary = context
+            .iter()
+            .map(|entry| (entry.chunk_order, entry.is_match))
+            .collect::<Vec<(i64, bool)>>();
+        assert_eq!(summary, vec![(2, false), (3, true), (4, false)]);
+
+        let edge = load_chunk_context(&connection, file_id, 2, 3, 0).unwrap();
+        assert_eq!(edge.len(), 1);
+        assert!(load_chunk_context(&connection, file_id, 99, 1, 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn citation_uses_author_line_under_heading() {
+        let authors = vec![
+            (2, "Jones, R., Policy Review, 2018, vol 4".to_string()),
+            (6, "Smith, J. (2021). Journal of Testing".to_string()),
+            (11, "Lee, K., Foreign Affairs, 2019, archive".to_string()),
+        ];
+        let author = citation_author(&authors, 5, Some(9));
+        assert_eq!(author, Some("Smith, J. (2021). Journal of Testing"));
+        assert_eq!(
+            citation_author(&authors, 12, None),
+            Some("Lee, K., Foreign Affairs, 2019, archive")
+        );
+
+        assert_eq!(
+            format_citation_text("Testing solves extinction", author, CitationStyle::Debate),
+            "Testing solves extinction\nSmith 21 — Smith, J. (2021). Journal of Testing"
+        );
+        assert_eq!(
+            format_citation_text("Testing solves extinction", author, CitationStyle::Mla),
+            "Smith, J. (2021). Journal of Testing. \"Testing solves extinction.\""
+        );
+        assert_eq!(
+            format_citation_text("Untagged", None, CitationStyle::Debate),
+            "Untagged"
+        );
+    }
+
+    #[test]
+    fn citation_header_capture_puts_citation_under_heading() {
+        let app = test_app("citation-header");
+        let root = temp_dir("citation-header-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Source.docx");
+        write_docx(
+            &source,
+            &format!(
+                "{}{}{}",
+                heading_xml(4, "Testing solves extinction"),
+                paragraph_xml("Smith, J. (2021). Journal of Testing, vol 4"),
+                paragraph_xml("Card body text.")
+            ),
+        );
+        set_capture_banner(app.clone(), None).unwrap();
+
+        insert_capture(
+            app.clone(),
+            root_path.clone(),
+            source.to_string_lossy().into_owned(),
+            "Testing solves extinction".to_string(),
+            "Card body text.".to_string(),
+            None,
+            Some("Cited.docx".to_string()),
+            None,
+            Some(1),
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let target = root.join("Cited.docx");
+        let texts = || {
+            parse_docx_paragraphs(&target)
+                .unwrap()
+                .into_iter()
+                .map(|paragraph| paragraph.text)
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<String>>()
+        };
+        let inserted = texts();
+        let document = crate::docx_parse::read_docx_part(&target, "word/document.xml")
+            .unwrap()
+            .unwrap();
+        let heading_order = parse_docx_paragraphs(&target)
+            .unwrap()
+            .iter()
+            .find(|paragraph| paragraph.text == "Testing solves extinction")
+            .unwrap()
+            .order;
+        delete_capture_heading(app, root_path, "Cited.docx".to_string(), heading_order).unwrap();
+        let after_delete = texts();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(
+            inserted.first().map(String::as_str),
+            Some("Testing solves extinction")
+        );
+        assert_eq!(
+            inserted.get(1).map(String::as_str),
+            Some("Smith 21 — Smith, J. (2021). Journal of Testing, vol 4")
+        );
+        assert!(!document.contains("__BF_CITATION_STYLE__"));
+        assert!(after_delete.is_empty());
+    }
+
+    #[test]
+    fn delete_capture_target_removes_file_and_entries() {
+        let app = test_app("delete-target");
+        let root = temp_dir("delete-target-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Source.docx");
+        write_docx(&source, &paragraph_xml("Source card text"));
+
+        insert_capture(
+            app.clone(),
+            root_path.clone(),
+            source.to_string_lossy().into_owned(),
+            "Politics".to_string(),
+            "# Politics\nMidterms link card".to_string(),
+            None,
+            Some("Politics.docx".to_string()),
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        assert!(root.join("Politics.docx").is_file());
+        delete_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            "Politics.docx".to_string(),
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            list_trash(app.clone(), root_path.clone(), None)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(delete_capture_target(
+            app.clone(),
+            root_path.clone(),
+            crate::DEFAULT_CAPTURE_TARGET.to_string(),
+            None
+        )
+        .is_err());
+
+        assert!(delete_capture_target(
+            app.clone(),
+            root_path.clone(),
+            "Missing.docx".to_string(),
+            None
+        )
+        .unwrap_err()
+        .contains("Could not find capture target"));
+
+        let targets = delete_capture_target(
+            app.clone(),
+            root_path.clone(),
+            "Politics.docx".to_string(),
+            None,
+        )
+        .unwrap();
+        let deleted_again = delete_capture_target(
+            app.clone(),
+            root_path.clone(),
+            "Politics.docx".to_string(),
+            None,
+        );
+        let trashed = list_trash(app, root_path, None).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(deleted_again.is_err());
+        assert!(trashed.is_empty());
+        assert!(!root.join("Politics.docx").exists());
+        assert!(targets
+            .iter()
+            .all(|target| target.relative_path != "Politics.docx"));
+    }
+
+    #[test]
+    fn rename_capture_target_moves_file_and_capture_rows() {
+        let app = test_app("rename-target");
+        let root = temp_dir("rename-target-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Source.docx");
+        write_docx(&source, &paragraph_xml("Source card text"));
+
+        insert_capture(
+            app.clone(),
+            root_path.clone(),
+            source.to_string_lossy().into_owned(),
+            "Politics".to_string(),
+            "# Politics\nMidterms link card".to_string(),
+            None,
+            Some("Politics.docx".to_string()),
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        insert_capture(
+            app.clone(),
+            root_path.clone(),
+            source.to_string_lossy().into_owned(),
+            "Econ".to_string(),
+            "# Econ\nRecession card".to_string(),
+            None,
+            Some("Politics.docx".to_string()),
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        delete_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            "Politics.docx".to_string(),
+            2,
+        )
+        .unwrap();
+        write_docx(&root.join("Taken.docx"), &paragraph_xml("Existing"));
+        assert!(rename_capture_target(
+            app.clone(),
+            root_path.clone(),
+            "Politics.docx".to_string(),
+            "Taken.docx".to_string(),
+        )
+        .is_err());
+
+        let targets = rename_capture_target(
+            app.clone(),
+            root_path.clone(),
+            "Politics.docx".to_string(),
+            "Neg/Politics DA".to_string(),
+        )
+        .unwrap();
+
+        let connection = open_database(&app).unwrap();
+        let moved_rows: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM captures WHERE target_relative_path = 'Neg/Politics DA.docx'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let stale_rows: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM captures WHERE target_relative_path = 'Politics.docx'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let trashed = list_trash(app.clone(), root_path.clone(), None).unwrap();
+        let old_exists = root.join("Politics.docx").exists();
+        let new_exists = root.join("Neg").join("Politics DA.docx").is_file();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(!old_exists);
+        assert!(new_exists);
+        assert_eq!((moved_rows, stale_rows), (2, 0));
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].target_relative_path, "Neg/Politics DA.docx");
+        assert!(targets
+            .iter()
+            .any(|target| target.relative_path == "Neg/Politics DA.docx"));
+    }
 
-    let connection = open_database(&app)?;
-    let root_id_value = root_id(&connection, &root_path)?.ok_or_else(|| {
-        format!(
-            "Benchmark root id missing for '{}'. Try indexing again.",
-            root_path
+    #[test]
+    fn move_heading_across_targets_transfers_block() {
+        let app = test_app("move-across");
+        let root = temp_dir("move-across-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("A.docx"),
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "Alpha"),
+                paragraph_xml("Alpha card"),
+                heading_xml(1, "Beta"),
+                paragraph_xml("Beta card")
+            ),
+        );
+        write_docx(&root.join("B.docx"), &heading_xml(1, "Gamma"));
+
+        let transfer = move_heading_across_targets(
+            app.clone(),
+            root_path.clone(),
+            "A.docx".to_string(),
+            3,
+            "B.docx".to_string(),
+            Some(1),
         )
-    })?;
+        .unwrap();
+        let trashed = list_trash(app, root_path, None).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let heading_texts = |headings: &[crate::types::FileHeading]| {
+            headings
+                .iter()
+                .map(|heading| heading.text.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(heading_texts(&transfer.source.headings), vec!["Alpha"]);
+        assert_eq!(
+            heading_texts(&transfer.destination.headings),
+            vec!["Gamma", "Beta"]
+        );
+        assert!(transfer.destination.headings[1]
+            .copy_text
+            .contains("Beta card"));
+        assert!(trashed.is_empty());
+    }
 
-    let benchmark_iterations = iterations.unwrap_or(3).clamp(1, 12);
-    let benchmark_limit = limit.unwrap_or(80).clamp(10, 400);
-    let benchmark_include_semantic = include_semantic.unwrap_or(true);
-    let benchmark_preview_samples = preview_samples.unwrap_or(16).clamp(0, 240);
-    let provided_queries = queries.unwrap_or_default();
-    let benchmark_queries =
-        collect_benchmark_queries(&connection, root_id_value, &provided_queries, 32)?;
+    #[test]
+    fn move_across_targets_leaves_an_unreadable_source_alone() {
+        let app = test_app("move-across-corrupt");
+        let root = temp_dir("move-across-corrupt-root");
+        let root_path = root.to_string_lossy().into_owned();
+        std::fs::write(root.join("A.docx"), b"not a zip").unwrap();
+
+        let moved = move_heading_across_targets(
+            app,
+            root_path,
+            "A.docx".to_string(),
+            1,
+            "B.docx".to_string(),
+            None,
+        );
+        let source = std::fs::read(root.join("A.docx")).unwrap();
+        let dest_exists = root.join("B.docx").exists();
+        let _ = std::fs::remove_dir_all(&root);
 
-    let mut search = BenchmarkSearchSummary {
-        query_count: benchmark_queries.len(),
-        iterations: benchmark_iterations,
-        limit: benchmark_limit,
-        ..BenchmarkSearchSummary::default()
-    };
+        assert!(moved.is_err());
+        assert_eq!(source, b"not a zip");
+        assert!(!dest_exists);
+    }
 
-    let mut lexical_raw_samples = Vec::new();
-    let mut lexical_raw_hits = 0_usize;
-    let mut lexical_raw_error: Option<String> = None;
-    'lexical_raw: for _ in 0..benchmark_iterations {
-        for query in &benchmark_queries {
-            let started = Instant::now();
-            match lexical::search(&app, query, Some(root_id_value), benchmark_limit, false) {
-                Ok(hits) => {
-                    lexical_raw_samples.push(elapsed_ms(started));
-                    lexical_raw_hits = lexical_raw_hits.saturating_add(hits.len());
-                }
-                Err(error) => {
-                    lexical_raw_error = Some(error);
-                    break 'lexical_raw;
-                }
+    #[test]
+    fn export_capture_html_includes_target_headings() {
+        let app = test_app("export-html");
+        let root = temp_dir("export-html-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Politics.docx"),
+            &format!(
+                "{}{}{}",
+                heading_xml(1, "Midterms DA"),
+                heading_xml(4, "Link &amp; impact"),
+                paragraph_xml("Card text")
+            ),
+        );
+
+        let html =
+            export_capture_html(app.clone(), root_path.clone(), "Politics".to_string()).unwrap();
+        let missing = export_capture_html(app, root_path, "Missing.docx".to_string());
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(html.contains("Midterms DA"));
+        assert!(html.contains("Link &amp; impact"));
+        assert!(html.contains("class=\"bf-preview-h4\""));
+        assert!(missing.unwrap_err().contains("does not exist"));
+    }
+
+    #[test]
+    fn export_captures_includes_inserted_capture_marker() {
+        let app = test_app("export-captures");
+        let connection = open_database(&app).unwrap();
+        let (root_id, _) = insert_file_row(&connection, "/debate", "Aff.docx");
+        connection
+            .execute(
+                "INSERT INTO captures(root_id, source_path, section_title, target_relative_path, heading_level, content, created_at_ms)
+                 VALUES(?1, '/debate/Aff.docx', 'Econ DA, \"turns\" case', 'Captures.docx', 3, 'x', 42)",
+                params![root_id],
+            )
+            .unwrap();
+        let marker = capture_marker(connection.last_insert_rowid());
+
+        let csv =
+            export_captures(app.clone(), "/debate".to_string(), Some("CSV".to_string())).unwrap();
+        let json = export_captures(app.clone(), "/debate".to_string(), None).unwrap();
+        let missing_root =
+            export_captures(app.clone(), "/nowhere".to_string(), Some("csv".to_string())).unwrap();
+        let bad_format = export_captures(app, "/debate".to_string(), Some("xml".to_string()));
+
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            format!(
+                "1,{marker},/debate/Aff.docx,\"Econ DA, \"\"turns\"\" case\",Captures.docx,3,42"
+            )
+        );
+        let records: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(records[0]["marker"], marker.as_str());
+        assert_eq!(records[0]["headingLevel"], 3);
+        assert_eq!(missing_root.lines().count(), 1);
+        assert!(bad_format.is_err());
+    }
+
+    #[test]
+    fn files_by_author_matches_normalized_substring() {
+        let app = test_app("files-by-author");
+        let connection = open_database(&app).unwrap();
+        let (_, aff_id) = insert_file_row(&connection, "/debate", "Aff/Plan.docx");
+        let (_, neg_id) = insert_file_row(&connection, "/debate", "Neg/Politics.docx");
+        let (_, other_id) = insert_file_row(&connection, "/debate", "Neg/Case.docx");
+        let (_, elsewhere_id) = insert_file_row(&connection, "/archive", "Old.docx");
+        for (file_id, order, text) in [
+            (aff_id, 1, "Mearsheimer, John. 2019. University of Chicago"),
+            (aff_id, 2, "Mearsheimer, John. 2021. Foreign Affairs"),
+            (neg_id, 1, "MEARSHEIMER and Walt, 2016, Harvard Press"),
+            (other_id, 1, "Kagan, Robert. 2018. Brookings"),
+            (elsewhere_id, 1, "Mearsheimer, John. 2001. Norton"),
+        ] {
+            connection
+                .execute(
+                    "INSERT INTO authors(file_id, author_order, text, normalized, file_name, relative_path)
+                     VALUES(?1, ?2, ?3, ?4, 'x.docx', 'x.docx')",
+                    params![file_id, order, text, normalize_for_search(text)],
+                )
+                .unwrap();
+        }
+
+        let files = files_by_author(
+            app.clone(),
+            "/debate".to_string(),
+            " mearsheimer ".to_string(),
+        )
+        .unwrap();
+        let blank = files_by_author(app, "/debate".to_string(), "  ".to_string()).unwrap();
+
+        let paths = files
+            .iter()
+            .map(|file| (file.relative_path.as_str(), file.folder_path.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![("Aff/Plan.docx", "Aff"), ("Neg/Politics.docx", "Neg")]
+        );
+        assert_eq!(files[0].file_name, "Plan.docx");
+        assert!(blank.is_empty());
+    }
+
+    #[test]
+    fn global_stats_aggregate_known_corpus() {
+        let app = test_app("global-stats");
+        let connection = open_database(&app).unwrap();
+        let (root_id, aff_id) = insert_file_row(&connection, "/debate", "Aff.docx");
+        let (other_root_id, neg_id) = insert_file_row(&connection, "/debate-neg", "Neg.docx");
+        connection
+            .execute_batch(&format!(
+                "
+                UPDATE files SET size = 1200 WHERE id = {aff_id};
+                UPDATE files SET size = 800 WHERE id = {neg_id};
+                INSERT INTO headings(file_id, heading_order, level, text, normalized, file_name, relative_path)
+                VALUES({aff_id}, 1, 1, 'Plan', 'plan', 'Aff.docx', 'Aff.docx'),
+                      ({aff_id}, 2, 2, 'Solvency', 'solvency', 'Aff.docx', 'Aff.docx'),
+                      ({neg_id}, 1, 1, 'Politics DA', 'politics da', 'Neg.docx', 'Neg.docx');
+                INSERT INTO authors(file_id, author_order, text, normalized, file_name, relative_path)
+                VALUES({neg_id}, 1, 'Smith 2024', 'smith 2024', 'Neg.docx', 'Neg.docx');
+                INSERT INTO chunks(chunk_id, root_id, file_id, chunk_order, chunk_text, file_name, relative_path, absolute_path)
+                VALUES('a:1', {root_id}, {aff_id}, 1, 'text', 'Aff.docx', 'Aff.docx', '/debate/Aff.docx'),
+                      ('a:2', {root_id}, {aff_id}, 2, 'more', 'Aff.docx', 'Aff.docx', '/debate/Aff.docx');
+                INSERT INTO captures(root_id, source_path, section_title, target_relative_path, content, created_at_ms)
+                VALUES({root_id}, 'Aff.docx', 'Plan', 'Captures.docx', 'x', 0),
+                      ({root_id}, 'Aff.docx', 'Solvency', 'Captures.docx', 'y', 0),
+                      ({other_root_id}, 'Neg.docx', 'Politics DA', 'Captures.docx', 'z', 0);
+                "
+            ))
+            .unwrap();
+
+        let stats = get_global_stats(app).unwrap();
+        assert_eq!(
+            stats,
+            GlobalStats {
+                root_count: 2,
+                file_count: 2,
+                heading_count: 3,
+                author_count: 1,
+                chunk_count: 2,
+                capture_target_count: 2,
+                total_indexed_bytes: 2000,
+                semantic_item_count: 0,
             }
+        );
+    }
+
+    #[test]
+    fn max_indexable_bytes_skips_and_reports_oversized_files() {
+        let app = test_app("max-indexable-bytes");
+        let root = temp_dir("max-indexable-bytes-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Small.docx"),
+            &format!("{}{}", heading_xml(1, "Small"), paragraph_xml("Card text")),
+        );
+        let huge_body = (0..2000)
+            .map(|index| paragraph_xml(&format!("Card {index} text")))
+            .collect::<String>();
+        write_docx(
+            &root.join("Huge.docx"),
+            &format!("{}{huge_body}", heading_xml(1, "Huge")),
+        );
+        let max_bytes = std::fs::metadata(root.join("Small.docx")).unwrap().len();
+
+        let stats = index_root(
+            app.clone(),
+            root_path,
+            IndexOptions {
+                rebuild_semantic: false,
+                max_indexable_bytes: Some(max_bytes),
+                ..IndexOptions::default()
+            },
+        )
+        .unwrap();
+        let connection = open_database(&app).unwrap();
+        let indexed = connection
+            .query_row("SELECT relative_path FROM files", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(stats.updated, 1);
+        assert_eq!(stats.skipped_too_large, vec!["Huge.docx".to_string()]);
+        assert_eq!(indexed, "Small.docx");
+    }
+
+    #[test]
+    fn max_indexable_bytes_keeps_rows_from_an_earlier_index() {
+        let app = test_app("max-indexable-bytes-kept");
+        let root = temp_dir("max-indexable-bytes-kept-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Small.docx"),
+            &format!("{}{}", heading_xml(1, "Small"), paragraph_xml("Card text")),
+        );
+        let huge_body = (0..2000)
+            .map(|index| paragraph_xml(&format!("Card {index} text")))
+            .collect::<String>();
+        write_docx(
+            &root.join("Huge.docx"),
+            &format!("{}{huge_body}", heading_xml(1, "Huge")),
+        );
+        let max_bytes = std::fs::metadata(root.join("Small.docx")).unwrap().len();
+        let no_semantic = IndexOptions {
+            rebuild_semantic: false,
+            ..IndexOptions::default()
+        };
+
+        index_root(app.clone(), root_path.clone(), no_semantic.clone()).unwrap();
+        let stats = index_root(
+            app.clone(),
+            root_path,
+            IndexOptions {
+                max_indexable_bytes: Some(max_bytes),
+                ..no_semantic
+            },
+        )
+        .unwrap();
+        let connection = open_database(&app).unwrap();
+        let huge_headings = connection
+            .query_row(
+                "SELECT COUNT(*) FROM headings h JOIN files f ON f.id = h.file_id WHERE f.relative_path = 'Huge.docx'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(stats.skipped_too_large, vec!["Huge.docx".to_string()]);
+        assert_eq!(stats.removed, 0);
+        assert_eq!(huge_headings, 1);
+    }
+
+    #[test]
+    fn default_capture_target_routes_unspecified_captures() {
+        let app = test_app("default-capture-target");
+        let root = temp_dir("default-capture-target-root");
+        let root_path = root.to_string_lossy().into_owned();
+
+        assert!(set_default_capture_target(app.clone(), Some("Team.txt".to_string())).is_err());
+        let active =
+            set_default_capture_target(app.clone(), Some(" Team-Captures.docx ".to_string()))
+                .unwrap();
+        let inserted = insert_capture(
+            app.clone(),
+            root_path.clone(),
+            root.join("Source.docx").to_string_lossy().into_owned(),
+            "Econ DA".to_string(),
+            "Growth is steady.".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        let targets = list_capture_targets(app.clone(), root_path, None).unwrap();
+        let reset = set_default_capture_target(app, None).unwrap();
+        let exists = root.join("Team-Captures.docx").is_file();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(active, "Team-Captures.docx");
+        assert_eq!(inserted.target_relative_path, "Team-Captures.docx");
+        assert!(exists);
+        assert_eq!(targets[0].relative_path, "Team-Captures.docx");
+        assert_eq!(reset, crate::DEFAULT_CAPTURE_TARGET);
+    }
+
+    #[test]
+    fn capture_targets_report_modified_time_and_sort_by_it() {
+        let app = test_app("capture-target-modified");
+        let root = temp_dir("capture-target-modified-root");
+        let root_path = root.to_string_lossy().into_owned();
+        for target in ["Old.docx", "New.docx"] {
+            insert_capture(
+                app.clone(),
+                root_path.clone(),
+                root.join("Source.docx").to_string_lossy().into_owned(),
+                "Econ DA".to_string(),
+                "Growth is steady.".to_string(),
+                None,
+                Some(target.to_string()),
+                None,
+                None,
+                None,
+                None,
+                false,
+            )
+            .unwrap();
         }
+        std::fs::File::options()
+            .write(true)
+            .open(root.join("Old.docx"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(3600))
+            .unwrap();
+
+        let targets =
+            list_capture_targets(app, root_path, Some(CaptureTargetSort::Modified)).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let order = targets
+            .iter()
+            .map(|target| target.relative_path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            order,
+            vec!["New.docx", "Old.docx", crate::DEFAULT_CAPTURE_TARGET]
+        );
+        assert!((now_ms() - targets[0].modified_ms).abs() < 60_000);
+        assert!(targets[1].modified_ms < targets[0].modified_ms - 3_000_000);
+        assert_eq!(targets[2].modified_ms, 0);
+    }
+
+    #[test]
+    fn preview_debug_stats_counts_run_formatting_and_blocks() {
+        let app = test_app("preview-debug-stats");
+        let dir = temp_dir("preview-debug-stats-root");
+        let dir_path = dir.to_string_lossy().into_owned();
+        let cite = |text: &str| {
+            format!("<w:p><w:pPr><w:pStyle w:val=\"F8Cite\"/></w:pPr><w:r><w:t>{text}</w:t></w:r></w:p>")
+        };
+        let formatted = concat!(
+            "<w:p>",
+            "<w:r><w:rPr><w:b/></w:rPr><w:t>Bold</w:t></w:r>",
+            "<w:r><w:rPr><w:i/><w:u w:val=\"single\"/></w:rPr><w:t>Both</w:t></w:r>",
+            "<w:r><w:rPr><w:highlight w:val=\"yellow\"/></w:rPr><w:t>Lit</w:t></w:r>",
+            "<w:r><w:rPr><w:u w:val=\"none\"/></w:rPr><w:t>Plain</w:t></w:r>",
+            "<w:r><w:rPr><w:b/></w:rPr></w:r>",
+            "</w:p>"
+        );
+        write_docx(
+            &dir.join("Styles.docx"),
+            &format!(
+                "{}{}{formatted}{}{}{}{}",
+                heading_xml(1, "Aff"),
+                heading_xml(4, "Growth"),
+                cite("Smith 21"),
+                cite("Journal of Testing"),
+                paragraph_xml("Card text"),
+                cite("Doe 22")
+            ),
+        );
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, &dir_path, "Styles.docx");
+
+        let stats = preview_debug_stats(app, file_id).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(stats.paragraph_count, 7);
+        assert_eq!(stats.run_count, 10);
+        assert_eq!(
+            (
+                stats.bold_runs,
+                stats.italic_runs,
+                stats.underline_runs,
+                stats.highlight_runs
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(
+            stats
+                .heading_levels
+                .into_iter()
+                .collect::<Vec<(i64, usize)>>(),
+            vec![(1, 1), (4, 1)]
+        );
+        assert_eq!(stats.f8_cite_blocks, 2);
+    }
+
+    #[test]
+    fn roots_indexed_by_an_older_parser_are_reparsed() {
+        let app = test_app("parser-version");
+        let root = temp_dir("parser-version-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Aff.docx"),
+            &format!(
+                "{}{}",
+                heading_xml(4, "Econ"),
+                paragraph_xml("Growth is steady.")
+            ),
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        let index = || {
+            index_root(
+                app.clone(),
+                root_path.clone(),
+                IndexOptions {
+                    rebuild_semantic: false,
+                    ..IndexOptions::default()
+                },
+            )
+            .unwrap()
+        };
+        index();
+        // Simulates rows written before argument types and the fingerprint existed.
+        let connection = open_database(&app).unwrap();
+        connection
+            .execute_batch(
+                "UPDATE headings SET argument_type = NULL;
+                 UPDATE roots SET chunk_options = '';",
+            )
+            .unwrap();
+
+        let stats = index();
+        let untagged = connection
+            .query_row(
+                "SELECT COUNT(*) FROM headings WHERE argument_type IS NULL",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(stats.updated, 1);
+        assert_eq!(untagged, 0);
+    }
+
+    #[test]
+    fn merge_short_headings_option_controls_tag_merging() {
+        let dir = temp_dir("merge-short-headings");
+        let path = dir.join("Neg.docx");
+        write_docx(
+            &path,
+            &format!(
+                "{}{}{}",
+                heading_xml(4, "Midterms shift —"),
+                heading_xml(4, "plan costs capital"),
+                paragraph_xml("Card body text.")
+            ),
+        );
+        let candidates = vec![IndexCandidate {
+            relative_path: "Neg.docx".to_string(),
+            absolute_path: path,
+            modified_ms: 0,
+            size: 0,
+            file_hash: String::new(),
+        }];
+        let heading_texts = |options: &IndexOptions| {
+            let (parsed, _) = parse_index_batch(&candidates, options);
+            parsed[0]
+                .headings
+                .iter()
+                .map(|heading| (heading.order, heading.text.clone()))
+                .collect::<Vec<(i64, String)>>()
+        };
+
+        let separate = heading_texts(&IndexOptions::default());
+        let merged = heading_texts(&IndexOptions {
+            merge_short_headings: Some(true),
+            ..IndexOptions::default()
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(separate.len(), 2);
+        assert_eq!(
+            merged,
+            vec![(1, "Midterms shift — plan costs capital".to_string())]
+        );
+    }
+
+    #[test]
+    fn collapse_whitespace_option_flattens_chunk_text_only() {
+        let dir = temp_dir("collapse-whitespace");
+        let path = dir.join("Aff.docx");
+        write_docx(
+            &path,
+            &format!(
+                "{}<w:p><w:r><w:t>Growth</w:t><w:tab/><w:t>is</w:t><w:br/><w:t>steady</w:t></w:r></w:p>",
+                heading_xml(4, "Econ")
+            ),
+        );
+        let candidates = vec![IndexCandidate {
+            relative_path: "Aff.docx".to_string(),
+            absolute_path: path.clone(),
+            modified_ms: 0,
+            size: 0,
+            file_hash: String::new(),
+        }];
+        let (parsed, _) = parse_index_batch(
+            &candidates,
+            &IndexOptions {
+                collapse_whitespace: Some(true),
+                ..IndexOptions::default()
+            },
+        );
+        let (headings, _, _) = extract_preview_content(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(parsed[0]
+            .chunks
+            .iter()
+            .any(|chunk| chunk.chunk_text == "Growth is steady"));
+        assert!(parsed[0]
+            .chunks
+            .iter()
+            .all(|chunk| !chunk.chunk_text.contains(['\t', '\n'])));
+        assert!(headings[0].copy_text.contains("Growth\tis\nsteady"));
+    }
+
+    #[test]
+    fn preview_move_capture_heading_matches_actual_move() {
+        let app = test_app("preview-move-heading");
+        let root = temp_dir("preview-move-heading-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Move.docx"),
+            &format!(
+                "{}{}{}{}{}{}",
+                heading_xml(1, "Alpha"),
+                paragraph_xml("Alpha card"),
+                heading_xml(1, "Beta"),
+                paragraph_xml("Beta card"),
+                heading_xml(1, "Gamma"),
+                paragraph_xml("Gamma card")
+            ),
+        );
+        let outline = |headings: &[FileHeading]| {
+            headings
+                .iter()
+                .map(|heading| {
+                    (
+                        heading.order,
+                        heading.text.clone(),
+                        heading.copy_text.clone(),
+                    )
+                })
+                .collect::<Vec<(i64, String, String)>>()
+        };
+
+        let projected = preview_move_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            "Move.docx".to_string(),
+            1,
+            5,
+        )
+        .unwrap();
+        let untouched = get_capture_target_preview(
+            app.clone(),
+            root_path.clone(),
+            "Move.docx".to_string(),
+            None,
+        )
+        .unwrap();
+        let moved = move_capture_heading(app, root_path, "Move.docx".to_string(), 1, 5).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(untouched.headings[0].text, "Alpha");
+        assert_eq!(outline(&projected), outline(&moved.headings));
+        assert_eq!(
+            projected
+                .iter()
+                .map(|heading| heading.text.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["Beta", "Gamma", "Alpha"]
+        );
+    }
+
+    #[test]
+    fn reorder_capture_heading_moves_heading_to_front_of_siblings() {
+        let app = test_app("reorder-heading");
+        let root = temp_dir("reorder-heading-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Reorder.docx"),
+            &format!(
+                "{}{}{}{}{}{}",
+                heading_xml(1, "Alpha"),
+                paragraph_xml("Alpha card"),
+                heading_xml(1, "Beta"),
+                paragraph_xml("Beta card"),
+                heading_xml(1, "Gamma"),
+                paragraph_xml("Gamma card")
+            ),
+        );
+
+        let preview =
+            reorder_capture_heading(app, root_path, "Reorder.docx".to_string(), 5, 0).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let heading_texts = preview
+            .headings
+            .iter()
+            .map(|heading| heading.text.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(heading_texts, vec!["Gamma", "Alpha", "Beta"]);
+        assert!(preview.headings[0].copy_text.contains("Gamma card"));
+    }
+
+    #[test]
+    fn capture_preview_anchors_are_unique_and_stable() {
+        let app = test_app("preview-anchors");
+        let root = temp_dir("preview-anchors-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Anchors.docx"),
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "Econ DA: Links!"),
+                paragraph_xml("Link card"),
+                heading_xml(1, "Econ DA: Links!"),
+                paragraph_xml("Second link card")
+            ),
+        );
+
+        let anchors = || {
+            get_capture_target_preview(
+                app.clone(),
+                root_path.clone(),
+                "Anchors.docx".to_string(),
+                Some(true),
+            )
+            .unwrap()
+            .headings
+            .into_iter()
+            .map(|heading| heading.anchor_id.unwrap())
+            .collect::<Vec<String>>()
+        };
+        let first = anchors();
+        let second = anchors();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(first, vec!["h1-econ-da-links", "h3-econ-da-links"]);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn deleted_capture_heading_can_be_restored_from_trash() {
+        let app = test_app("trash-restore");
+        let root = temp_dir("trash-restore-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Trash.docx"),
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "Alpha"),
+                paragraph_xml("Alpha card"),
+                heading_xml(1, "Beta"),
+                paragraph_xml("Beta card")
+            ),
+        );
+
+        let deleted =
+            delete_capture_heading(app.clone(), root_path.clone(), "Trash.docx".to_string(), 1)
+                .unwrap();
+        assert_eq!(deleted.heading_count, 1);
+
+        let trashed = list_trash(app.clone(), root_path.clone(), None).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].heading_text, "Alpha");
+        assert_eq!(trashed[0].target_relative_path, "Trash.docx");
+
+        let restored = restore_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            "Trash.docx".to_string(),
+            trashed[0].id,
+        )
+        .unwrap();
+        let remaining = list_trash(app, root_path, Some("Trash.docx".to_string())).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let heading_texts = restored
+            .headings
+            .iter()
+            .map(|heading| heading.text.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(heading_texts, vec!["Beta", "Alpha"]);
+        assert!(restored.headings[1].copy_text.contains("Alpha card"));
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn delete_capture_headings_removes_several_headings_in_one_rewrite() {
+        let app = test_app("bulk-delete");
+        let root = temp_dir("bulk-delete-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Blocks.docx"),
+            &format!(
+                "{}{}{}{}{}{}{}{}{}{}",
+                heading_xml(1, "Alpha"),
+                paragraph_xml("Alpha card"),
+                heading_xml(1, "Beta"),
+                paragraph_xml("Beta card"),
+                heading_xml(1, "Gamma"),
+                paragraph_xml("Gamma card"),
+                heading_xml(2, "Gamma link"),
+                paragraph_xml("Gamma link card"),
+                heading_xml(1, "Delta"),
+                paragraph_xml("Delta card")
+            ),
+        );
+
+        let missing = delete_capture_headings(
+            app.clone(),
+            root_path.clone(),
+            "Blocks.docx".to_string(),
+            vec![1, 42],
+        );
+        let deleted = delete_capture_headings(
+            app.clone(),
+            root_path.clone(),
+            "Blocks.docx".to_string(),
+            vec![9, 1, 5, 7],
+        )
+        .unwrap();
+        let trashed = list_trash(app, root_path, None).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(missing.is_err());
+        let heading_texts = deleted
+            .headings
+            .iter()
+            .map(|heading| heading.text.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(heading_texts, vec!["Beta"]);
+        assert!(deleted.headings[0].copy_text.contains("Beta card"));
+        let mut trashed_texts = trashed
+            .iter()
+            .map(|entry| entry.heading_text.clone())
+            .collect::<Vec<String>>();
+        trashed_texts.sort_unstable();
+        assert_eq!(trashed_texts, vec!["Alpha", "Delta", "Gamma"]);
+    }
+
+    #[test]
+    fn rebuild_lexical_index_now_populates_from_database_rows() {
+        let app = test_app("rebuild-lexical");
+        let connection = open_database(&app).unwrap();
+        let (root_id, file_id) = insert_file_row(&connection, "/rebuild", "Aff.docx");
+        connection
+            .execute_batch(&format!(
+                "
+                INSERT INTO headings(file_id, heading_order, level, text, normalized, file_name, relative_path)
+                VALUES({file_id}, 1, 1, 'Quokka advantage', 'quokka advantage', 'Aff.docx', 'Aff.docx');
+                "
+            ))
+            .unwrap();
+
+        let stats = rebuild_lexical_index_now(app.clone()).unwrap();
+        let hits = crate::lexical::search(
+            &app,
+            "quokka",
+            &crate::lexical::LexicalSearchOptions::new(&[root_id], 20),
+        )
+        .unwrap();
+
+        assert_eq!(stats.document_count, 2);
+        assert!(hits
+            .iter()
+            .any(|hit| hit.kind == "heading"
+                && hit.heading_text.as_deref() == Some("Quokka advantage")));
+    }
+
+    #[test]
+    fn preview_capture_reports_source_xml_usage() {
+        let app = test_app("preview-capture");
+        let root = temp_dir("preview-capture-root");
+        let source = root.join("Source.docx");
+        write_docx(
+            &source,
+            &format!(
+                "{}{}",
+                heading_xml(2, "Turnout link"),
+                paragraph_xml("Turnout card")
+            ),
+        );
+        let source_path = source.to_string_lossy().into_owned();
+
+        let styled = preview_capture(
+            app.clone(),
+            source_path.clone(),
+            Some(1),
+            "Turnout link\nTurnout card".to_string(),
+        )
+        .unwrap();
+        let plain = preview_capture(app, source_path, None, "Plain note".to_string()).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(styled.used_source_xml);
+        assert_eq!(styled.paragraph_xml.len(), 2);
+        assert!(styled.style_ids.contains(&"Heading2".to_string()));
+        assert!(!plain.used_source_xml);
+        assert_eq!(plain.paragraph_xml.len(), 1);
+    }
+
+    #[test]
+    fn capture_under_marker_lands_after_parent_section() {
+        let app = test_app("under-marker");
+        let root = temp_dir("under-marker-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Missing.docx").to_string_lossy().into_owned();
+        let capture = |title: &str, content: &str, level: Option<i64>, marker: Option<String>| {
+            insert_capture(
+                app.clone(),
+                root_path.clone(),
+                source.clone(),
+                title.to_string(),
+                content.to_string(),
+                None,
+                None,
+                level,
+                None,
+                None,
+                marker,
+                false,
+            )
+            .unwrap()
+        };
+
+        let first = capture("Politics DA", "# Politics DA\nFirst card", Some(1), None);
+        capture("Econ DA", "# Econ DA\nSibling card", Some(1), None);
+        let nested = capture("Link", "## Link\nSecond card", None, Some(first.marker));
+
+        let paragraphs = parse_docx_paragraphs(Path::new(&nested.capture_path)).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        let position = |text: &str| {
+            paragraphs
+                .iter()
+                .position(|paragraph| paragraph.text == text)
+                .unwrap()
+        };
+
+        assert!(position("First card") < position("Link"));
+        assert!(position("Second card") < position("Econ DA"));
+        assert_eq!(paragraphs[position("Link")].heading_level, Some(2));
+    }
+
+    #[test]
+    fn capture_bookmark_id_skips_ids_from_source_paragraphs() {
+        let app = test_app("bookmark-ids");
+        let root = temp_dir("bookmark-ids-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Missing.docx").to_string_lossy().into_owned();
+        let capture = |paragraph_xml: Option<Vec<String>>| {
+            insert_capture(
+                app.clone(),
+                root_path.clone(),
+                source.clone(),
+                "Politics DA".to_string(),
+                "# Politics DA\nCard".to_string(),
+                paragraph_xml,
+                None,
+                Some(1),
+                None,
+                None,
+                None,
+                false,
+            )
+            .unwrap()
+        };
+
+        capture(None);
+        let inserted = capture(Some(vec![concat!(
+            "<w:p><w:bookmarkStart w:id=\"2\" w:name=\"_Ref2\"/><w:bookmarkEnd w:id=\"2\"/>",
+            "<w:r><w:t>Source card</w:t></w:r></w:p>"
+        )
+        .to_string()]));
+        let capture_path = Path::new(&inserted.capture_path);
+        let document_xml = read_docx_part(capture_path, "word/document.xml")
+            .unwrap()
+            .unwrap();
+        let marker_index = bookmarked_paragraph_index(capture_path, &inserted.marker).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let mut ids = document_xml
+            .split("<w:bookmarkStart")
+            .skip(1)
+            .filter_map(|tag| tag.split("w:id=\"").nth(1))
+            .filter_map(|value| value.split('"').next())
+            .collect::<Vec<&str>>();
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["0", "2", "3"]);
+        assert!(marker_index.is_some());
+    }
+
+    #[test]
+    fn capture_under_marker_follows_a_reordered_same_titled_block() {
+        let app = test_app("under-marker-reordered");
+        let root = temp_dir("under-marker-reordered-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Missing.docx").to_string_lossy().into_owned();
+        let capture = |content: &str, marker: Option<String>| {
+            insert_capture(
+                app.clone(),
+                root_path.clone(),
+                source.clone(),
+                "Politics DA".to_string(),
+                content.to_string(),
+                None,
+                None,
+                Some(1),
+                None,
+                None,
+                marker,
+                false,
+            )
+            .unwrap()
+        };
+
+        capture("# Politics DA\nOlder card", None);
+        let newer = capture("# Politics DA\nNewer card", None);
+        let newer_order = parse_docx_paragraphs(Path::new(&newer.capture_path))
+            .unwrap()
+            .iter()
+            .rfind(|paragraph| paragraph.text == "Politics DA")
+            .unwrap()
+            .order;
+        reorder_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            newer.target_relative_path.clone(),
+            newer_order,
+            0,
+        )
+        .unwrap();
+        let nested = capture("## Link\nNested card", Some(newer.marker));
+
+        let paragraphs = parse_docx_paragraphs(Path::new(&nested.capture_path)).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        let position = |text: &str| {
+            paragraphs
+                .iter()
+                .position(|paragraph| paragraph.text == text)
+                .unwrap()
+        };
+
+        assert!(position("Newer card") < position("Link"));
+        assert!(position("Nested card") < position("Older card"));
+    }
+
+    #[test]
+    fn captures_scope_returns_only_capture_target_hits() {
+        let app = test_app("search-scope");
+        let root = temp_dir("search-scope-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Source.docx"),
+            &format!(
+                "{}{}",
+                heading_xml(1, "Midterms link"),
+                paragraph_xml("Source evidence on midterms.")
+            ),
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        insert_capture(
+            app.clone(),
+            root_path.clone(),
+            root.join("Missing.docx").to_string_lossy().into_owned(),
+            "Midterms block".to_string(),
+            "# Midterms block\nCaptured midterms card".to_string(),
+            None,
+            None,
+            Some(1),
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        index_root(
+            app.clone(),
+            root_path.clone(),
+            IndexOptions {
+                rebuild_semantic: false,
+                ..IndexOptions::default()
+            },
+        )
+        .unwrap();
+
+        let search = |scope: SearchScope| {
+            crate::async_runtime::block_on(search_index_hybrid(
+                app.clone(),
+                "midterms".to_string(),
+                HybridSearchOptions {
+                    root_path: Some(root_path.clone()),
+                    limit: Some(20),
+                    semantic_enabled: Some(false),
+                    scope: Some(scope),
+                    ..HybridSearchOptions::default()
+                },
+            ))
+            .unwrap()
+            .hits
+        };
+        let captures = search(SearchScope::Captures);
+        let sources = search(SearchScope::Sources);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(!captures.is_empty());
+        assert!(captures
+            .iter()
+            .all(|hit| hit.relative_path == crate::DEFAULT_CAPTURE_TARGET));
+        assert!(!sources.is_empty());
+        assert!(sources.iter().all(|hit| hit.relative_path == "Source.docx"));
+    }
+
+    #[test]
+    fn captured_card_is_found_through_captures_corpus_only() {
+        let app = test_app("search-corpus");
+        let root = temp_dir("search-corpus-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Source.docx"),
+            &format!(
+                "{}{}",
+                heading_xml(1, "Econ DA"),
+                paragraph_xml("Growth is steady across sectors.")
+            ),
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        insert_capture(
+            app.clone(),
+            root_path.clone(),
+            root.join("Source.docx").to_string_lossy().into_owned(),
+            "Deforestation impact".to_string(),
+            "# Deforestation impact\nNepstad 2024 — Amazon dieback triggers a carbon feedback loop"
+                .to_string(),
+            None,
+            None,
+            Some(1),
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        index_root(
+            app.clone(),
+            root_path.clone(),
+            IndexOptions {
+                rebuild_semantic: false,
+                ..IndexOptions::default()
+            },
+        )
+        .unwrap();
+
+        let search = |corpus: &str| {
+            let options: HybridSearchOptions = serde_json::from_value(serde_json::json!({
+                "rootPath": root_path,
+                "limit": 20,
+                "semanticEnabled": false,
+                "corpus": corpus,
+            }))
+            .unwrap();
+            crate::async_runtime::block_on(search_index_hybrid(
+                app.clone(),
+                "dieback".to_string(),
+                options,
+            ))
+            .unwrap()
+            .hits
+        };
+        let captures = search("captures");
+        let sources = search("sources");
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(captures.iter().any(|hit| {
+            hit.relative_path == crate::DEFAULT_CAPTURE_TARGET && hit.heading_order.is_some()
+        }));
+        assert!(sources.is_empty());
+    }
+
+    #[test]
+    fn indexed_comments_are_searchable_and_rendered() {
+        let app = test_app("comments");
+        let root = temp_dir("comments-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let body = format!(
+            "{}{}",
+            heading_xml(1, "Growth DA"),
+            concat!(
+                "<w:p><w:commentRangeStart w:id=\"0\"/>",
+                "<w:r><w:t>Growth is steady across sectors.</w:t></w:r>",
+                "<w:commentRangeEnd w:id=\"0\"/>",
+                "<w:r><w:commentReference w:id=\"0\"/></w:r></w:p>"
+            )
+        );
+        let comments = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+            "<w:comments xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">",
+            "<w:comment w:id=\"0\" w:author=\"Coach\"><w:p><w:r>",
+            "<w:t>Check this card against Brookings</w:t></w:r></w:p></w:comment>",
+            "</w:comments>"
+        );
+        write_docx_parts(
+            &root.join("Growth.docx"),
+            &[
+                ("word/document.xml", document_xml(&body)),
+                ("word/comments.xml", comments.to_string()),
+            ],
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        index_root(
+            app.clone(),
+            root_path.clone(),
+            IndexOptions {
+                rebuild_semantic: false,
+                index_comments: Some(true),
+                ..IndexOptions::default()
+            },
+        )
+        .unwrap();
+
+        let hits = crate::async_runtime::block_on(search_index_hybrid(
+            app.clone(),
+            "brookings".to_string(),
+            HybridSearchOptions {
+                root_path: Some(root_path.clone()),
+                limit: Some(20),
+                semantic_enabled: Some(false),
+                ..HybridSearchOptions::default()
+            },
+        ))
+        .unwrap()
+        .hits;
+        let comment_hit = hits
+            .iter()
+            .find(|hit| hit.kind == "comment")
+            .expect("comment hit");
+        assert_eq!(comment_hit.heading_order, Some(1));
+        assert_eq!(comment_hit.heading_text.as_deref(), Some("Growth DA"));
+
+        let html = get_heading_preview_html(app.clone(), comment_hit.file_id, 1).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        assert!(html.contains(
+            "<span class=\"bf-comment\" title=\"Coach\">Check this card against Brookings</span>"
+        ));
     }
-    search.lexical_raw = build_task_result(
-        true,
-        &lexical_raw_samples,
-        lexical_raw_hits,
-        lexical_raw_error,
-    );
 
-    query_engine::clear_query_cache();
-    for query in &benchmark_queries {
-        let _ = query_engine::search_lexical(
-            &app,
-            query,
-            Some(root_path.clone()),
-            Some(benchmark_limit),
+    #[test]
+    fn disabled_capture_banner_is_not_written() {
+        let app = test_app("capture-banner-setting");
+        let root = temp_dir("capture-banner-setting-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let banner_paragraphs = |target: &str| {
+            parse_docx_paragraphs(&root.join(target))
+                .unwrap()
+                .into_iter()
+                .filter(|paragraph| paragraph.heading_level.is_none())
+                .map(|paragraph| paragraph.text)
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<String>>()
+        };
+
+        assert!(set_capture_banner(app.clone(), Some("  ".to_string())).is_err());
+        assert_eq!(
+            set_capture_banner(app.clone(), Some(" Team Blocks ".to_string())).unwrap(),
+            Some("Team Blocks".to_string())
         );
+        add_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            "Custom.docx".to_string(),
+            1,
+            "Politics DA".to_string(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(set_capture_banner(app.clone(), None).unwrap(), None);
+        add_capture_heading(
+            app.clone(),
+            root_path.clone(),
+            "Plain.docx".to_string(),
+            1,
+            "Politics DA".to_string(),
+            None,
+        )
+        .unwrap();
+
+        let custom = banner_paragraphs("Custom.docx");
+        let plain = banner_paragraphs("Plain.docx");
+        let _ = std::fs::remove_dir_all(&root);
+        assert_eq!(custom, vec!["Team Blocks".to_string()]);
+        assert!(plain.is_empty());
     }
-    let mut lexical_cached_samples = Vec::new();
-    let mut lexical_cached_hits = 0_usize;
-    let mut lexical_cached_error: Option<String> = None;
-    'lexical_cached: for _ in 0..benchmark_iterations {
-        for query in &benchmark_queries {
-            let started = Instant::now();
-            match query_engine::search_lexical(
-                &app,
-                query,
-                Some(root_path.clone()),
-                Some(benchmark_limit),
-            ) {
-                Ok(hits) => {
-                    lexical_cached_samples.push(elapsed_ms(started));
-                    lexical_cached_hits = lexical_cached_hits.saturating_add(hits.len());
-                }
-                Err(error) => {
-                    lexical_cached_error = Some(error);
-                    break 'lexical_cached;
-                }
-            }
-        }
-    }
-    search.lexical_cached = build_task_result(
-        true,
-        &lexical_cached_samples,
-        lexical_cached_hits,
-        lexical_cached_error,
-    );
 
-    if benchmark_include_semantic {
-        query_engine::clear_query_cache();
-        for query in &benchmark_queries {
-            let _ = query_engine::search_hybrid(
-                &app,
-                query,
-                Some(root_path.clone()),
-                Some(benchmark_limit),
-                false,
-                true,
+    #[test]
+    fn add_capture_heading_supports_h5_and_h6_styles() {
+        let app = test_app("capture-heading-deep");
+        let root = temp_dir("capture-heading-deep-root");
+        let root_path = root.to_string_lossy().into_owned();
+        for (level, text) in [(1, "Politics DA"), (5, "Link"), (6, "Internal link")] {
+            add_capture_heading(
+                app.clone(),
+                root_path.clone(),
+                "Deep.docx".to_string(),
+                level,
+                text.to_string(),
+                None,
             )
-            .await;
+            .unwrap();
         }
+        let too_deep = add_capture_heading(
+            app,
+            root_path,
+            "Deep.docx".to_string(),
+            7,
+            "Too deep".to_string(),
+            None,
+        );
 
-        let mut hybrid_samples = Vec::new();
-        let mut hybrid_hits = 0_usize;
-        let mut hybrid_error: Option<String> = None;
-        'hybrid: for _ in 0..benchmark_iterations {
-            for query in &benchmark_queries {
-                let started = Instant::now();
-                match query_engine::search_hybrid(
-                    &app,
-                    query,
-                    Some(root_path.clone()),
-                    Some(benchmark_limit),
-                    false,
-                    true,
-                )
-                .await
-                {
-                    Ok(hits) => {
-                        hybrid_samples.push(elapsed_ms(started));
-                        hybrid_hits = hybrid_hits.saturating_add(hits.len());
-                    }
-                    Err(error) => {
-                        hybrid_error = Some(error);
-                        break 'hybrid;
-                    }
-                }
-            }
-        }
-        search.hybrid = build_task_result(true, &hybrid_samples, hybrid_hits, hybrid_error);
+        let target = root.join("Deep.docx");
+        let document = read_docx_part(&target, "word/document.xml")
+            .unwrap()
+            .unwrap();
+        let styles = read_docx_part(&target, "word/styles.xml").unwrap().unwrap();
+        let headings = parse_docx_paragraphs(&target)
+            .unwrap()
+            .into_iter()
+            .filter_map(|paragraph| Some((paragraph.heading_level?, paragraph.text)))
+            .collect::<Vec<(i64, String)>>();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(too_deep.is_err());
+        assert!(document.contains("<w:pStyle w:val=\"Heading5\"/>"));
+        assert!(document.contains("<w:pStyle w:val=\"Heading6\"/>"));
+        assert_eq!(styles.matches("w:styleId=\"Heading5\"").count(), 1);
+        assert_eq!(styles.matches("w:styleId=\"Heading6\"").count(), 1);
+        assert!(headings.contains(&(5, "Link".to_string())));
+        assert!(headings.contains(&(6, "Internal link".to_string())));
+    }
 
-        let mut semantic_samples = Vec::new();
-        let mut semantic_hits = 0_usize;
-        let mut semantic_error: Option<String> = None;
-        if let Some(warm_query) = benchmark_queries.first() {
-            let _ = query_engine::search_semantic(
-                &app,
-                warm_query,
-                Some(root_path.clone()),
-                Some(benchmark_limit),
-            )
-            .await;
+    #[test]
+    fn file_previews_skip_unknown_ids() {
+        let app = test_app("file-previews");
+        let root = temp_dir("file-previews-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let connection = open_database(&app).unwrap();
+        let mut file_ids = Vec::new();
+        for name in ["Aff.docx", "Neg.docx"] {
+            write_docx(
+                &root.join(name),
+                &format!(
+                    "{}{}{}",
+                    heading_xml(1, &format!("{name} one")),
+                    heading_xml(1, &format!("{name} two")),
+                    paragraph_xml("Card text.")
+                ),
+            );
+            file_ids.push(insert_file_row(&connection, &root_path, name).1);
         }
-        'semantic: for _ in 0..benchmark_iterations {
-            for query in &benchmark_queries {
-                let started = Instant::now();
-                match query_engine::search_semantic(
-                    &app,
-                    query,
-                    Some(root_path.clone()),
-                    Some(benchmark_limit),
+
+        let previews = get_file_previews(
+            app,
+            vec![file_ids[1], 9_999, file_ids[0], file_ids[1]],
+            Some(1),
+        )
+        .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let summary = previews
+            .iter()
+            .map(|preview| {
+                (
+                    preview.relative_path.as_str(),
+                    preview.heading_count,
+                    preview.headings.len(),
                 )
-                .await
-                {
-                    Ok(hits) => {
-                        semantic_samples.push(elapsed_ms(started));
-                        semantic_hits = semantic_hits.saturating_add(hits.len());
-                    }
-                    Err(error) => {
-                        semantic_error = Some(error);
-                        break 'semantic;
-                    }
-                }
-            }
-        }
-        search.semantic = build_task_result(true, &semantic_samples, semantic_hits, semantic_error);
-    } else {
-        search.hybrid = build_task_result(false, &[], 0, None);
-        search.semantic = build_task_result(false, &[], 0, None);
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![("Neg.docx", 2, 1), ("Aff.docx", 2, 1)]);
     }
 
-    let snapshot_started = Instant::now();
-    let _ = get_index_snapshot(app.clone(), root_path.clone())?;
-    let mut preview = BenchmarkPreviewSummary {
-        snapshot_ms: elapsed_ms(snapshot_started),
-        ..BenchmarkPreviewSummary::default()
-    };
+    #[test]
+    fn capture_target_stats_count_headings_paragraphs_and_words() {
+        let app = test_app("capture-stats");
+        let root = temp_dir("capture-stats-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Politics.docx"),
+            &format!(
+                "{}{}{}<w:p/>{}{}",
+                heading_xml(1, "Politics DA"),
+                heading_xml(4, "Midterms link"),
+                paragraph_xml("The plan costs political capital."),
+                heading_xml(4, "Turnout impact"),
+                paragraph_xml("Low turnout flips the house.")
+            ),
+        );
 
-    let sampled_file_ids = sample_file_ids(&connection, root_id_value, benchmark_preview_samples)?;
-    let mut file_preview_samples = Vec::new();
-    let mut file_preview_hits = 0_usize;
-    let mut file_preview_error: Option<String> = None;
-    for file_id in sampled_file_ids {
-        let started = Instant::now();
-        match get_file_preview(app.clone(), file_id) {
-            Ok(file_preview) => {
-                file_preview_samples.push(elapsed_ms(started));
-                file_preview_hits =
-                    file_preview_hits.saturating_add(usize::try_from(file_preview.heading_count).unwrap_or(0));
-            }
-            Err(error) => {
-                file_preview_error = Some(error);
-                break;
-            }
-        }
+        let stats =
+            capture_target_stats(app.clone(), root_path.clone(), "Politics.docx".to_string())
+                .unwrap();
+        let missing = capture_target_stats(app, root_path, "Missing.docx".to_string()).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(stats.exists);
+        assert_eq!(
+            (stats.heading_count, stats.paragraph_count, stats.word_count),
+            (3, 5, 16)
+        );
+        assert!(!missing.exists);
+        assert_eq!(missing.word_count, 0);
     }
-    preview.file_preview = build_task_result(
-        benchmark_preview_samples > 0,
-        &file_preview_samples,
-        file_preview_hits,
-        file_preview_error,
-    );
 
-    let sampled_heading_refs =
-        sample_heading_refs(&connection, root_id_value, benchmark_preview_samples)?;
-    let mut heading_preview_samples = Vec::new();
-    let mut heading_preview_hits = 0_usize;
-    let mut heading_preview_error: Option<String> = None;
-    for (file_id, heading_order) in sampled_heading_refs {
-        let started = Instant::now();
-        match get_heading_preview_html(app.clone(), file_id, heading_order) {
-            Ok(html) => {
-                heading_preview_samples.push(elapsed_ms(started));
-                if !html.trim().is_empty() {
-                    heading_preview_hits = heading_preview_hits.saturating_add(1);
-                }
-            }
-            Err(error) => {
-                heading_preview_error = Some(error);
-                break;
-            }
+    #[test]
+    fn root_health_score_drops_with_known_issues() {
+        let app = test_app("root-health");
+        let healthy_root = temp_dir("root-health-healthy");
+        let broken_root = temp_dir("root-health-broken");
+        for root in [&healthy_root, &broken_root] {
+            write_docx(
+                &root.join("Aff.docx"),
+                &format!(
+                    "{}{}",
+                    heading_xml(1, "Aff tag"),
+                    paragraph_xml("Card text")
+                ),
+            );
+            write_docx(
+                &root.join("Neg.docx"),
+                &format!(
+                    "{}{}",
+                    heading_xml(1, "Neg tag"),
+                    paragraph_xml("Card text")
+                ),
+            );
+        }
+        write_docx(
+            &broken_root.join("Notes.docx"),
+            &paragraph_xml("No headings here"),
+        );
+        std::fs::write(broken_root.join("Corrupt.docx"), b"").unwrap();
+
+        let mut reports = Vec::new();
+        for root in [&healthy_root, &broken_root] {
+            let root_path = root.to_string_lossy().into_owned();
+            add_root(app.clone(), root_path.clone()).unwrap();
+            index_root(
+                app.clone(),
+                root_path,
+                IndexOptions {
+                    rebuild_semantic: false,
+                    ..IndexOptions::default()
+                },
+            )
+            .unwrap();
+        }
+        write_docx(
+            &broken_root.join("Neg.docx"),
+            &format!(
+                "{}{}",
+                heading_xml(1, "Neg tag"),
+                paragraph_xml("Edited card text")
+            ),
+        );
+        for root in [&healthy_root, &broken_root] {
+            reports.push(root_health(
+                app.clone(),
+                root.to_string_lossy().into_owned(),
+            ));
+            let _ = std::fs::remove_dir_all(root);
         }
-    }
-    preview.heading_preview_html = build_task_result(
-        benchmark_preview_samples > 0,
-        &heading_preview_samples,
-        heading_preview_hits,
-        heading_preview_error,
-    );
 
-    Ok(BenchmarkReport {
-        root_path,
-        index_full,
-        index_incremental,
-        queries: benchmark_queries,
-        search,
-        preview,
-        generated_at_ms: now_ms(),
-        elapsed_ms: elapsed_ms(benchmark_started).round() as i64,
-    })
-}
+        let healthy = reports.remove(0).unwrap();
+        let broken = reports.remove(0).unwrap();
+        assert_eq!(
+            (
+                healthy.file_count,
+                healthy.parse_error_files,
+                healthy.out_of_date_files,
+                healthy.zero_heading_files
+            ),
+            (2, 0, 0, 0)
+        );
+        assert_eq!(
+            (
+                broken.file_count,
+                broken.parse_error_files,
+                broken.out_of_date_files,
+                broken.zero_heading_files
+            ),
+            (3, 1, 1, 1)
+        );
+        assert!(broken.score < healthy.score);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{latency_stats, query_candidates_from_text};
+    #[test]
+    fn resolve_capture_marker_finds_inserted_capture() {
+        let app = test_app("resolve-marker");
+        let root = temp_dir("resolve-marker-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let source = root.join("Aff.docx").to_string_lossy().into_owned();
+        let inserted = insert_capture(
+            app.clone(),
+            root_path,
+            source.clone(),
+            "Econ DA".to_string(),
+            "# Econ DA\nGrowth is steady".to_string(),
+            None,
+            Some("Blocks/Econ.docx".to_string()),
+            Some(1),
+            None,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let resolved =
+            resolve_capture_marker(app.clone(), format!(" {} ", inserted.marker)).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(resolved.marker, inserted.marker);
+        assert_eq!(resolved.source_path, source);
+        assert_eq!(resolved.target_relative_path, "Blocks/Econ.docx");
+        assert_eq!(resolved.target_path, inserted.capture_path);
+        assert_eq!(resolved.section_title, "Econ DA");
+        assert!(resolved.target_exists);
+        assert!(resolved.created_at_ms > 0);
+        assert!(resolve_capture_marker(app.clone(), "BF-999999".to_string()).is_err());
+        assert!(resolve_capture_marker(app, "card-12".to_string()).is_err());
+    }
 
     #[test]
-    fn query_candidates_produces_multiple_usable_forms() {
-        let candidates = query_candidates_from_text("The quick brown fox jumps over fences");
+    fn refresh_metadata_restores_author_rows_and_argument_types() {
+        let app = test_app("refresh-metadata");
+        let root = temp_dir("refresh-metadata-root");
+        let root_path = root.to_string_lossy().into_owned();
+        write_docx(
+            &root.join("Aff.docx"),
+            &format!(
+                "{}{}{}",
+                heading_xml(4, "Growth is steady"),
+                paragraph_xml("Smith, J., Doe, A. (2021). Journal of Testing"),
+                paragraph_xml("Card text")
+            ),
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        index_root(
+            app.clone(),
+            root_path,
+            IndexOptions {
+                rebuild_semantic: false,
+                ..IndexOptions::default()
+            },
+        )
+        .unwrap();
+
+        // Simulate rows written by older, weaker heuristics.
+        let connection = open_database(&app).unwrap();
+        let file_id = connection
+            .query_row("SELECT id FROM files", [], |row| row.get::<_, i64>(0))
+            .unwrap();
+        connection
+            .execute("DELETE FROM authors WHERE file_id = ?1", params![file_id])
+            .unwrap();
+        connection
+            .execute(
+                "UPDATE headings SET argument_type = 'analytic' WHERE file_id = ?1",
+                params![file_id],
+            )
+            .unwrap();
+
+        let stats = refresh_metadata(app.clone(), file_id).unwrap();
+        let authors = connection
+            .query_row(
+                "SELECT COUNT(*) FROM authors WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap();
+        let argument_type = connection
+            .query_row(
+                "SELECT argument_type FROM headings WHERE file_id = ?1",
+                params![file_id],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(stats.author_count, 1);
+        assert_eq!(stats.updated_headings, 1);
+        assert_eq!(stats.updated_chunks, 0);
+        assert_eq!(authors, 1);
+        assert_eq!(argument_type, "card");
+    }
 
-        assert!(candidates.contains(&"the quick brown".to_string()));
-        assert!(candidates.contains(&"the quick".to_string()));
-        assert!(candidates.contains(&"the".to_string()));
-        assert!(candidates.contains(&"over fences".to_string()));
+    #[test]
+    fn core_document_properties_are_stored_and_searchable() {
+        let app = test_app("core-properties");
+        let root = temp_dir("core-properties-root");
+        let root_path = root.to_string_lossy().into_owned();
+        let core = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>",
+            "<cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" ",
+            "xmlns:dc=\"http://purl.org/dc/elements/1.1/\">",
+            "<dc:title>Zephyr Hegemony Update</dc:title><dc:creator>Coach Rivera</dc:creator>",
+            "<cp:keywords>heg, primacy</cp:keywords></cp:coreProperties>"
+        );
+        write_docx_parts(
+            &root.join("Aff.docx"),
+            &[
+                (
+                    "word/document.xml",
+                    document_xml(&format!(
+                        "{}{}",
+                        heading_xml(1, "Plan text"),
+                        paragraph_xml("Card text")
+                    )),
+                ),
+                ("docProps/core.xml", core.to_string()),
+            ],
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        index_root(
+            app.clone(),
+            root_path.clone(),
+            IndexOptions {
+                rebuild_semantic: false,
+                ..IndexOptions::default()
+            },
+        )
+        .unwrap();
+
+        let connection = open_database(&app).unwrap();
+        let stored = connection
+            .query_row(
+                "SELECT doc_title, doc_creator, doc_keywords FROM files",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                },
+            )
+            .unwrap();
+        let hits = crate::async_runtime::block_on(search_index_hybrid(
+            app.clone(),
+            "zephyr hegemony".to_string(),
+            HybridSearchOptions {
+                root_path: Some(root_path),
+                limit: Some(10),
+                semantic_enabled: Some(false),
+                ..HybridSearchOptions::default()
+            },
+        ))
+        .unwrap()
+        .hits;
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(
+            stored,
+            (
+                Some("Zephyr Hegemony Update".to_string()),
+                Some("Coach Rivera".to_string()),
+                Some("heg, primacy".to_string())
+            )
+        );
+        assert!(hits
+            .iter()
+            .any(|hit| hit.kind == "file" && hit.relative_path == "Aff.docx"));
     }
 
     #[test]
-    fn latency_stats_computes_expected_percentiles() {
-        let stats = latency_stats(&[10.0, 20.0, 30.0, 40.0]);
+    fn enabling_paragraph_units_reindexes_unchanged_files_and_hits() {
+        let app = test_app("paragraph-units-reindex");
+        let root = temp_dir("paragraph-units-reindex-root");
+        let root_path = root.to_string_lossy().into_owned();
+        // Long enough that the second paragraph is cut across a chunk boundary.
+        write_docx(
+            &root.join("Aff.docx"),
+            &format!(
+                "{}{}{}",
+                heading_xml(1, "Economy DA"),
+                paragraph_xml(
+                    "Opening body paragraph that sets the context. "
+                        .repeat(28)
+                        .trim()
+                ),
+                paragraph_xml(&format!(
+                    "The zebra tariff spiral collapses global shipping. {}",
+                    "Tariffs compound. ".repeat(20).trim()
+                ))
+            ),
+        );
+        add_root(app.clone(), root_path.clone()).unwrap();
+        let index = |index_paragraphs: Option<bool>| {
+            index_root(
+                app.clone(),
+                root_path.clone(),
+                IndexOptions {
+                    index_paragraphs,
+                    rebuild_semantic: false,
+                    ..IndexOptions::default()
+                },
+            )
+            .unwrap()
+        };
+        let paragraph_hits = || {
+            crate::async_runtime::block_on(search_index(
+                app.clone(),
+                "zebra tariff spiral".to_string(),
+                Some(root_path.clone()),
+                Some(10),
+            ))
+            .unwrap()
+            .into_iter()
+            .filter(|hit| {
+                hit.kind == "paragraph" && hit.heading_text.as_deref() == Some("Economy DA")
+            })
+            .count()
+        };
 
-        assert_eq!(stats.runs, 4);
-        assert_eq!(stats.min_ms, 10.0);
-        assert_eq!(stats.p50_ms, 30.0);
-        assert_eq!(stats.p95_ms, 40.0);
-        assert_eq!(stats.max_ms, 40.0);
-        assert_eq!(stats.mean_ms, 25.0);
+        let first = index(Some(false));
+        let unchanged = index(Some(false));
+        let enabled = index(Some(true));
+        let enabled_hits = paragraph_hits();
+        let unspecified = index(None);
+        let unspecified_hits = paragraph_hits();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(first.updated, 1);
+        assert_eq!(unchanged.updated, 0);
+        assert_eq!(enabled.updated, 1);
+        assert_eq!(enabled_hits, 1);
+        assert_eq!(unspecified.updated, 0);
+        assert_eq!(unspecified_hits, 1);
     }
 }
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
 
 use crate::runtime::AppHandle;
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{
+    params, Connection, ErrorCode, OptionalExtension, Transaction, TransactionBehavior,
+};
 
 use crate::types::ExistingFileMeta;
 use crate::util::{now_ms, path_display};
@@ -19,6 +23,13 @@ const DATABASE_FILE_NAME: &str = "blockfile-meta-v2.sqlite3";
 const LEGACY_DATABASE_FILE_NAME: &str = "blockfile-index-v1.sqlite3";
 const LEGACY_SEMANTIC_DIR_NAME: &str = "semantic-lancedb";
 const LEGACY_SEMANTIC_META_FILE_NAME: &str = "semantic-index-meta-v1.json";
+const BUSY_TIMEOUT_MS: u64 = 5_000;
+const BUSY_RETRY_ATTEMPTS: u64 = 3;
+
+// Path plus file identity, so a database deleted or replaced on disk is set up again.
+type DatabaseIdentity = (PathBuf, u64, Option<SystemTime>);
+
+static INITIALIZED_DATABASES: OnceLock<Mutex<HashSet<DatabaseIdentity>>> = OnceLock::new();
 
 pub(crate) fn app_data_dir(app: &AppHandle) -> CommandResult<PathBuf> {
     let app_data = app
@@ -50,7 +61,7 @@ pub(crate) fn index_vector_dir(app: &AppHandle) -> CommandResult<PathBuf> {
     Ok(index_layout_dir(app)?.join(INDEX_VECTOR_DIR_NAME))
 }
 
-fn remove_path_if_exists(path: &PathBuf) -> CommandResult<()> {
+pub(crate) fn remove_path_if_exists(path: &PathBuf) -> CommandResult<()> {
     if !path.exists() {
         return Ok(());
     }
@@ -200,6 +211,167 @@ pub(crate) fn ensure_capture_schema(connection: &Connection) -> CommandResult<()
     Ok(())
 }
 
+pub(crate) fn ensure_root_schema(connection: &Connection) -> CommandResult<()> {
+    if !table_has_column(connection, "roots", "last_error")? {
+        connection
+            .execute("ALTER TABLE roots ADD COLUMN last_error TEXT", [])
+            .map_err(|error| format!("Could not add roots.last_error: {error}"))?;
+    }
+
+    if !table_has_column(connection, "roots", "failed_count")? {
+        connection
+            .execute(
+                "ALTER TABLE roots ADD COLUMN failed_count INTEGER NOT NULL DEFAULT 0",
+                [],
+            )
+            .map_err(|error| format!("Could not add roots.failed_count: {error}"))?;
+    }
+
+    if !table_has_column(connection, "roots", "chunk_options")? {
+        connection
+            .execute(
+                "ALTER TABLE roots ADD COLUMN chunk_options TEXT NOT NULL DEFAULT ''",
+                [],
+            )
+            .map_err(|error| format!("Could not add roots.chunk_options: {error}"))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn ensure_file_schema(connection: &Connection) -> CommandResult<()> {
+    if !table_has_column(connection, "files", "is_capture")? {
+        connection
+            .execute(
+                "ALTER TABLE files ADD COLUMN is_capture INTEGER NOT NULL DEFAULT 0",
+                [],
+            )
+            .map_err(|error| format!("Could not add files.is_capture: {error}"))?;
+    }
+
+    for column in ["doc_title", "doc_creator", "doc_keywords"] {
+        if !table_has_column(connection, "files", column)? {
+            connection
+                .execute(&format!("ALTER TABLE files ADD COLUMN {column} TEXT"), [])
+                .map_err(|error| format!("Could not add files.{column}: {error}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn mark_capture_files(
+    connection: &Connection,
+    root_id: i64,
+    default_target: &str,
+) -> CommandResult<()> {
+    connection
+        .execute(
+            "
+            UPDATE files
+            SET is_capture = (
+              relative_path = ?2
+              OR relative_path IN (
+                SELECT target_relative_path FROM captures WHERE root_id = ?1
+              )
+            )
+            WHERE root_id = ?1
+            ",
+            params![root_id, default_target],
+        )
+        .map_err(|error| format!("Could not mark capture files: {error}"))?;
+    Ok(())
+}
+
+pub(crate) fn ensure_chunk_schema(connection: &Connection) -> CommandResult<()> {
+    if !table_has_column(connection, "chunks", "kind")? {
+        connection
+            .execute(
+                "ALTER TABLE chunks ADD COLUMN kind TEXT NOT NULL DEFAULT 'chunk'",
+                [],
+            )
+            .map_err(|error| format!("Could not add chunks.kind: {error}"))?;
+    }
+
+    if !table_has_column(connection, "chunks", "content_hash")? {
+        connection
+            .execute(
+                "ALTER TABLE chunks ADD COLUMN content_hash TEXT NOT NULL DEFAULT ''",
+                [],
+            )
+            .map_err(|error| format!("Could not add chunks.content_hash: {error}"))?;
+    }
+
+    if !table_has_column(connection, "chunks", "argument_type")? {
+        connection
+            .execute("ALTER TABLE chunks ADD COLUMN argument_type TEXT", [])
+            .map_err(|error| format!("Could not add chunks.argument_type: {error}"))?;
+    }
+
+    if !table_has_column(connection, "headings", "argument_type")? {
+        connection
+            .execute("ALTER TABLE headings ADD COLUMN argument_type TEXT", [])
+            .map_err(|error| format!("Could not add headings.argument_type: {error}"))?;
+    }
+
+    Ok(())
+}
+
+fn is_busy_error(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+pub(crate) fn retry_busy<T>(
+    mut operation: impl FnMut() -> rusqlite::Result<T>,
+) -> rusqlite::Result<T> {
+    let mut attempt = 0_u64;
+    loop {
+        match operation() {
+            Err(error) if is_busy_error(&error) && attempt < BUSY_RETRY_ATTEMPTS => {
+                attempt += 1;
+                std::thread::sleep(Duration::from_millis(50 * attempt));
+            }
+            result => return result,
+        }
+    }
+}
+
+// Takes the write lock up front: a deferred transaction that upgrades to a writer while
+// another connection writes fails with SQLITE_BUSY without waiting on the busy timeout.
+pub(crate) fn begin_write_transaction(
+    connection: &mut Connection,
+) -> rusqlite::Result<Transaction<'_>> {
+    let connection: &Connection = connection;
+    retry_busy(move || Transaction::new_unchecked(connection, TransactionBehavior::Immediate))
+}
+
+fn initialized_databases() -> &'static Mutex<HashSet<DatabaseIdentity>> {
+    INITIALIZED_DATABASES.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn database_identity(db_path: &Path) -> Option<DatabaseIdentity> {
+    let metadata = fs::metadata(db_path).ok()?;
+    Some((
+        db_path.to_path_buf(),
+        file_index(&metadata),
+        metadata.created().ok(),
+    ))
+}
+
+#[cfg(unix)]
+fn file_index(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.ino()
+}
+
+#[cfg(not(unix))]
+fn file_index(_metadata: &fs::Metadata) -> u64 {
+    0
+}
+
 pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
     ensure_index_layout(app)?;
     let db_path = database_path(app)?;
@@ -209,12 +381,16 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
             path_display(&db_path)
         )
     })?;
-
     connection
-        .query_row("PRAGMA journal_mode = WAL", [], |row| {
+        .busy_timeout(Duration::from_millis(BUSY_TIMEOUT_MS))
+        .map_err(|error| format!("Could not set database busy timeout: {error}"))?;
+
+    retry_busy(|| {
+        connection.query_row("PRAGMA journal_mode = WAL", [], |row| {
             row.get::<_, String>(0)
         })
-        .map_err(|error| format!("Could not set journal mode: {error}"))?;
+    })
+    .map_err(|error| format!("Could not set journal mode: {error}"))?;
 
     connection
         .execute_batch(
@@ -222,12 +398,49 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
             PRAGMA foreign_keys = ON;
             PRAGMA synchronous = NORMAL;
             PRAGMA temp_store = MEMORY;
+            ",
+        )
+        .map_err(|error| format!("Could not configure index database: {error}"))?;
+    let _ = connection.query_row("PRAGMA cache_size = -65536", [], |row| row.get::<_, i64>(0));
+    let _ = connection.query_row("PRAGMA mmap_size = 268435456", [], |row| {
+        row.get::<_, i64>(0)
+    });
+    let _ = connection.query_row("PRAGMA wal_autocheckpoint = 1000", [], |row| {
+        row.get::<_, i64>(0)
+    });
+
+    let identity = database_identity(&db_path);
+    let initialized = identity.as_ref().is_some_and(|identity| {
+        initialized_databases()
+            .lock()
+            .is_ok_and(|databases| databases.contains(identity))
+    });
+    if !initialized {
+        retry_busy(|| initialize_schema(&connection))
+            .map_err(|error| format!("Could not initialize index database: {error}"))?;
+        ensure_root_schema(&connection)?;
+        ensure_file_schema(&connection)?;
+        ensure_capture_schema(&connection)?;
+        ensure_chunk_schema(&connection)?;
+        if let (Some(identity), Ok(mut databases)) = (identity, initialized_databases().lock()) {
+            databases.insert(identity);
+        }
+    }
+
+    Ok(connection)
+}
 
+fn initialize_schema(connection: &Connection) -> rusqlite::Result<()> {
+    connection.execute_batch(
+        "
             CREATE TABLE IF NOT EXISTS roots (
               id INTEGER PRIMARY KEY,
               path TEXT NOT NULL UNIQUE,
               added_at_ms INTEGER NOT NULL,
-              last_indexed_ms INTEGER NOT NULL DEFAULT 0
+              last_indexed_ms INTEGER NOT NULL DEFAULT 0,
+              last_error TEXT,
+              failed_count INTEGER NOT NULL DEFAULT 0,
+              chunk_options TEXT NOT NULL DEFAULT ''
             );
 
             CREATE TABLE IF NOT EXISTS files (
@@ -239,6 +452,10 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
               size INTEGER NOT NULL,
               file_hash TEXT NOT NULL DEFAULT '',
               heading_count INTEGER NOT NULL DEFAULT 0,
+              is_capture INTEGER NOT NULL DEFAULT 0,
+              doc_title TEXT,
+              doc_creator TEXT,
+              doc_keywords TEXT,
               UNIQUE(root_id, relative_path),
               FOREIGN KEY(root_id) REFERENCES roots(id) ON DELETE CASCADE
             );
@@ -252,6 +469,7 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
               normalized TEXT NOT NULL,
               file_name TEXT NOT NULL,
               relative_path TEXT NOT NULL,
+              argument_type TEXT,
               FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
             );
 
@@ -272,14 +490,17 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
               root_id INTEGER NOT NULL,
               file_id INTEGER NOT NULL,
               chunk_order INTEGER NOT NULL,
+              kind TEXT NOT NULL DEFAULT 'chunk',
               heading_order INTEGER,
               heading_level INTEGER,
               heading_text TEXT,
               author_text TEXT,
               chunk_text TEXT NOT NULL,
+              content_hash TEXT NOT NULL DEFAULT '',
               file_name TEXT NOT NULL,
               relative_path TEXT NOT NULL,
               absolute_path TEXT NOT NULL,
+              argument_type TEXT,
               FOREIGN KEY(root_id) REFERENCES roots(id) ON DELETE CASCADE,
               FOREIGN KEY(file_id) REFERENCES files(id) ON DELETE CASCADE
             );
@@ -296,6 +517,17 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
               FOREIGN KEY(root_id) REFERENCES roots(id) ON DELETE CASCADE
             );
 
+            CREATE TABLE IF NOT EXISTS capture_trash (
+              id INTEGER PRIMARY KEY,
+              root_id INTEGER NOT NULL,
+              target_relative_path TEXT NOT NULL,
+              heading_level INTEGER NOT NULL,
+              heading_text TEXT NOT NULL,
+              fragment_xml TEXT NOT NULL,
+              deleted_at_ms INTEGER NOT NULL,
+              FOREIGN KEY(root_id) REFERENCES roots(id) ON DELETE CASCADE
+            );
+
             CREATE INDEX IF NOT EXISTS idx_files_root_relative ON files(root_id, relative_path);
             CREATE INDEX IF NOT EXISTS idx_files_root_modified ON files(root_id, modified_ms DESC, id DESC);
             CREATE INDEX IF NOT EXISTS idx_headings_file ON headings(file_id);
@@ -309,21 +541,9 @@ pub(crate) fn open_database(app: &AppHandle) -> CommandResult<Connection> {
             CREATE INDEX IF NOT EXISTS idx_chunks_root_file_order ON chunks(root_id, file_id, chunk_order);
             CREATE INDEX IF NOT EXISTS idx_files_relative_length ON files(length(relative_path));
             CREATE INDEX IF NOT EXISTS idx_captures_root ON captures(root_id, id);
+            CREATE INDEX IF NOT EXISTS idx_capture_trash_target ON capture_trash(root_id, target_relative_path, id);
             ",
-        )
-        .map_err(|error| format!("Could not initialize index database: {error}"))?;
-
-    let _ = connection.query_row("PRAGMA cache_size = -65536", [], |row| row.get::<_, i64>(0));
-    let _ = connection.query_row("PRAGMA mmap_size = 268435456", [], |row| {
-        row.get::<_, i64>(0)
-    });
-    let _ = connection.query_row("PRAGMA wal_autocheckpoint = 1000", [], |row| {
-        row.get::<_, i64>(0)
-    });
-
-    ensure_capture_schema(&connection)?;
-
-    Ok(connection)
+    )
 }
 
 pub(crate) fn root_id(connection: &Connection, root_path: &str) -> CommandResult<Option<i64>> {
@@ -389,3 +609,96 @@ pub(crate) fn load_existing_files(
 
     Ok(metadata)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rusqlite::Connection;
+
+    use super::{begin_write_transaction, database_path, open_database};
+    use crate::test_support::test_app;
+
+    #[test]
+    fn concurrent_writer_waits_for_lock_instead_of_failing() {
+        let app = test_app("busy-timeout");
+        let holder = open_database(&app).unwrap();
+        holder
+            .execute_batch(
+                "BEGIN IMMEDIATE; INSERT INTO roots(path, added_at_ms) VALUES('/held', 0);",
+            )
+            .unwrap();
+
+        let contender_app = app.clone();
+        let contender = std::thread::spawn(move || {
+            let connection = open_database(&contender_app)?;
+            connection
+                .query_row("SELECT COUNT(*) FROM roots", [], |row| row.get::<_, i64>(0))
+                .map_err(|error| error.to_string())?;
+            connection
+                .execute(
+                    "INSERT INTO roots(path, added_at_ms) VALUES('/waiting', 0)",
+                    [],
+                )
+                .map_err(|error| error.to_string())?;
+            Ok::<(), String>(())
+        });
+
+        std::thread::sleep(Duration::from_millis(250));
+        holder.execute_batch("COMMIT").unwrap();
+
+        assert_eq!(contender.join().unwrap(), Ok(()));
+        let total = holder
+            .query_row("SELECT COUNT(*) FROM roots", [], |row| row.get::<_, i64>(0))
+            .unwrap();
+        assert_eq!(total, 2);
+    }
+
+    #[test]
+    fn write_transactions_wait_for_another_writer() {
+        let app = test_app("busy-write-transaction");
+        let holder = open_database(&app).unwrap();
+        holder.execute_batch("BEGIN IMMEDIATE;").unwrap();
+
+        let contender_app = app.clone();
+        let contender = std::thread::spawn(move || {
+            let mut connection = open_database(&contender_app)?;
+            let transaction =
+                begin_write_transaction(&mut connection).map_err(|error| error.to_string())?;
+            transaction
+                .execute(
+                    "INSERT INTO roots(path, added_at_ms) VALUES('/queued', 0)",
+                    [],
+                )
+                .map_err(|error| error.to_string())?;
+            transaction.commit().map_err(|error| error.to_string())
+        });
+
+        std::thread::sleep(Duration::from_millis(250));
+        holder.execute_batch("COMMIT").unwrap();
+
+        assert_eq!(contender.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn replaced_database_file_is_initialized_again() {
+        let app = test_app("replaced-database");
+        drop(open_database(&app).unwrap());
+        let db_path = database_path(&app).unwrap();
+        let replacement = db_path.with_extension("replacement");
+        Connection::open(&replacement)
+            .unwrap()
+            .execute_batch("CREATE TABLE unrelated(id INTEGER);")
+            .unwrap();
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", db_path.display()));
+        }
+        std::fs::rename(&replacement, &db_path).unwrap();
+
+        let connection = open_database(&app).unwrap();
+        let roots =
+            connection.query_row("SELECT COUNT(*) FROM roots", [], |row| row.get::<_, i64>(0));
+
+        assert_eq!(roots.unwrap(), 0);
+    }
+}
@@ -8,14 +8,24 @@ use roxmltree::{Document, Node};
 use zip::ZipArchive;
 
 use crate::docx_parse::{
-    attribute_value, has_tag, parse_docx_paragraphs, read_docx_part, read_zip_file,
-    resolve_insert_after_order,
+    attribute_value, corrupt_docx_reason, extract_paragraph_text, has_tag, parse_docx_paragraphs,
+    read_docx_part, read_zip_file, resolve_insert_after_order,
 };
 use crate::types::{RelationshipDef, SourceStyleDefinition, StyledSection};
 use crate::util::{is_probable_author_line, path_display};
 use crate::CommandResult;
 
 const CITATION_STYLE_PLACEHOLDER: &str = "__BF_CITATION_STYLE__";
+pub(crate) const DEFAULT_CAPTURE_BANNER_TEXT: &str = "Block File Captures";
+const MAIN_DOCUMENT_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml";
+const STYLES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml";
+const OFFICE_DOCUMENT_RELATIONSHIP_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument";
+const STYLES_RELATIONSHIP_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles";
+const MINIMAL_STYLES_XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><w:styles xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/></w:style></w:styles>";
 
 pub(crate) fn xml_escape_text(value: &str) -> String {
     value
@@ -47,6 +57,13 @@ pub(crate) fn paragraph_xml_bold(text: &str) -> String {
     )
 }
 
+pub(crate) fn paragraph_xml_citation(text: &str) -> String {
+    format!(
+        "<w:p><w:pPr><w:pStyle w:val=\"{CITATION_STYLE_PLACEHOLDER}\"/></w:pPr><w:r><w:rPr><w:b/></w:rPr><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+        xml_escape_text(text)
+    )
+}
+
 pub(crate) fn paragraph_xml_heading(level: i64, text: &str) -> String {
     let style_id = format!("Heading{}", level);
     format!(
@@ -56,11 +73,106 @@ pub(crate) fn paragraph_xml_heading(level: i64, text: &str) -> String {
     )
 }
 
+// Bookmarks are invisible in Word and travel with the paragraph when blocks are moved.
+pub(crate) fn paragraph_xml_with_bookmark(paragraph_xml: &str, id: i64, name: &str) -> String {
+    let bookmark = format!(
+        "<w:bookmarkStart w:id=\"{id}\" w:name=\"{}\"/><w:bookmarkEnd w:id=\"{id}\"/>",
+        xml_escape_attr(name)
+    );
+    if let Some(index) = paragraph_xml.find("</w:pPr>") {
+        let split = index + "</w:pPr>".len();
+        return format!(
+            "{}{bookmark}{}",
+            &paragraph_xml[..split],
+            &paragraph_xml[split..]
+        );
+    }
+    let Some(open_end) = paragraph_xml.find('>') else {
+        return paragraph_xml.to_string();
+    };
+    if paragraph_xml[..open_end].ends_with('/') {
+        return format!("{}>{bookmark}</w:p>", &paragraph_xml[..open_end - 1]);
+    }
+    format!(
+        "{}{bookmark}{}",
+        &paragraph_xml[..=open_end],
+        &paragraph_xml[open_end + 1..]
+    )
+}
+
+// Word needs bookmark ids to be unique within a document, and copied source paragraphs can
+// bring their own, so a new bookmark takes the id after the highest one in play.
+pub(crate) fn next_bookmark_id<'a>(xml_parts: impl IntoIterator<Item = &'a str>) -> i64 {
+    xml_parts
+        .into_iter()
+        .flat_map(|xml| xml.split("<w:bookmarkStart").skip(1))
+        .filter_map(|tag| {
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            let value = tag.split("w:id=\"").nth(1)?;
+            value[..value.find('"')?].parse::<i64>().ok()
+        })
+        .max()
+        .map_or(0, |id| id + 1)
+}
+
+// Index, in `parse_docx_paragraphs` order, of the paragraph that holds bookmark `name`.
+pub(crate) fn bookmarked_paragraph_index(
+    capture_path: &Path,
+    name: &str,
+) -> CommandResult<Option<usize>> {
+    let document_xml = read_docx_part(capture_path, "word/document.xml")?.ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'",
+            path_display(capture_path)
+        )
+    })?;
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse XML in '{}': {error}",
+            path_display(capture_path)
+        )
+    })?;
+    Ok(document
+        .descendants()
+        .filter(|node| has_tag(*node, "p"))
+        .position(|paragraph| {
+            paragraph.descendants().any(|node| {
+                has_tag(node, "bookmarkStart") && attribute_value(node, "name") == Some(name)
+            })
+        }))
+}
+
+fn fallback_paragraph_xml(line: &str) -> String {
+    let trimmed = line.trim();
+    let hashes = trimmed
+        .chars()
+        .take_while(|character| *character == '#')
+        .count();
+    if (1..=6).contains(&hashes) {
+        if let Some(text) = trimmed[hashes..].strip_prefix(' ') {
+            if !text.trim().is_empty() {
+                return paragraph_xml_heading(hashes as i64, text.trim());
+            }
+        }
+    }
+
+    if let Some(text) = trimmed
+        .strip_prefix("**")
+        .and_then(|value| value.strip_suffix("**"))
+    {
+        if !text.trim().is_empty() {
+            return paragraph_xml_bold(text);
+        }
+    }
+
+    paragraph_xml_plain(line)
+}
+
 pub(crate) fn fallback_styled_section(content: &str) -> StyledSection {
     let mut paragraph_xml = content
         .split('\n')
         .map(|line| line.strip_suffix('\r').unwrap_or(line))
-        .map(paragraph_xml_plain)
+        .map(fallback_paragraph_xml)
         .collect::<Vec<String>>();
 
     if paragraph_xml.is_empty() {
@@ -233,6 +345,12 @@ pub(crate) fn ensure_valid_capture_docx(capture_path: &Path) -> CommandResult<()
         return create_blank_docx(capture_path);
     }
 
+    if corrupt_docx_reason(capture_path).is_some() {
+        let backup_path = capture_path.with_extension("docx.bak");
+        let _ = fs::copy(capture_path, &backup_path);
+        return create_blank_docx(capture_path);
+    }
+
     let file = File::open(capture_path).map_err(|error| {
         format!(
             "Could not open capture docx '{}': {error}",
@@ -256,17 +374,185 @@ pub(crate) fn ensure_valid_capture_docx(capture_path: &Path) -> CommandResult<()
     create_blank_docx(capture_path)
 }
 
+fn ensure_relationship(relationships_xml: &str, rel_type: &str, target: &str) -> Option<String> {
+    let relationships = parse_relationships(relationships_xml);
+    if relationships
+        .values()
+        .any(|definition| definition.rel_type == rel_type)
+    {
+        return None;
+    }
+
+    let existing_ids = relationships.keys().cloned().collect::<HashSet<String>>();
+    let snippet = relationship_xml(
+        &next_relationship_id(&existing_ids),
+        &RelationshipDef {
+            rel_type: rel_type.to_string(),
+            target: target.to_string(),
+            target_mode: None,
+        },
+    );
+    if Document::parse(relationships_xml).is_ok() {
+        if let Some(close_index) = relationships_xml.rfind("</Relationships>") {
+            let mut updated = relationships_xml.to_string();
+            updated.insert_str(close_index, &snippet);
+            return Some(updated);
+        }
+    }
+
+    Some(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{snippet}</Relationships>"
+    ))
+}
+
+fn ensure_content_types(content_types_xml: &str, overrides: &[(&str, &str)]) -> Option<String> {
+    let parsed = Document::parse(content_types_xml).ok();
+    let mut defaults = HashSet::new();
+    let mut part_names = HashSet::new();
+    if let Some(document) = &parsed {
+        for node in document.descendants().filter(|node| node.is_element()) {
+            if has_tag(node, "Default") {
+                if let Some(extension) = attribute_value(node, "Extension") {
+                    defaults.insert(extension.to_ascii_lowercase());
+                }
+            } else if has_tag(node, "Override") {
+                if let Some(part_name) = attribute_value(node, "PartName") {
+                    part_names.insert(part_name.to_string());
+                }
+            }
+        }
+    }
+
+    let mut appended = Vec::new();
+    for (extension, content_type) in [
+        (
+            "rels",
+            "application/vnd.openxmlformats-package.relationships+xml",
+        ),
+        ("xml", "application/xml"),
+    ] {
+        if !defaults.contains(extension) {
+            appended.push(format!(
+                "<Default Extension=\"{extension}\" ContentType=\"{content_type}\"/>"
+            ));
+        }
+    }
+    for (part_name, content_type) in overrides {
+        if !part_names.contains(*part_name) {
+            appended.push(format!(
+                "<Override PartName=\"{part_name}\" ContentType=\"{content_type}\"/>"
+            ));
+        }
+    }
+    if appended.is_empty() {
+        return None;
+    }
+
+    if parsed.is_some() {
+        if let Some(close_index) = content_types_xml.rfind("</Types>") {
+            let mut updated = content_types_xml.to_string();
+            updated.insert_str(close_index, &appended.join(""));
+            return Some(updated);
+        }
+    }
+
+    Some(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">{}</Types>",
+        appended.join("")
+    ))
+}
+
+pub(crate) fn repair_capture_docx(capture_path: &Path) -> CommandResult<Vec<String>> {
+    let file = File::open(capture_path).map_err(|error| {
+        format!(
+            "Could not open capture docx '{}': {error}",
+            path_display(capture_path)
+        )
+    })?;
+    let mut archive = ZipArchive::new(file).map_err(|error| {
+        format!(
+            "Capture docx '{}' is not a readable zip archive: {error}",
+            path_display(capture_path)
+        )
+    })?;
+    if read_zip_file(&mut archive, "word/document.xml").is_none() {
+        return Err(format!(
+            "Capture docx '{}' has no readable word/document.xml; nothing can be recovered in place.",
+            path_display(capture_path)
+        ));
+    }
+
+    let mut replacements = HashMap::new();
+    if read_zip_file(&mut archive, "word/styles.xml").is_none() {
+        replacements.insert(
+            "word/styles.xml".to_string(),
+            MINIMAL_STYLES_XML.as_bytes().to_vec(),
+        );
+    }
+
+    let package_relationships = read_zip_file(&mut archive, "_rels/.rels").unwrap_or_default();
+    if let Some(updated) = ensure_relationship(
+        &package_relationships,
+        OFFICE_DOCUMENT_RELATIONSHIP_TYPE,
+        "word/document.xml",
+    ) {
+        replacements.insert("_rels/.rels".to_string(), updated.into_bytes());
+    }
+
+    let document_relationships =
+        read_zip_file(&mut archive, "word/_rels/document.xml.rels").unwrap_or_default();
+    if let Some(updated) = ensure_relationship(
+        &document_relationships,
+        STYLES_RELATIONSHIP_TYPE,
+        "styles.xml",
+    ) {
+        replacements.insert(
+            "word/_rels/document.xml.rels".to_string(),
+            updated.into_bytes(),
+        );
+    }
+
+    let content_types = read_zip_file(&mut archive, "[Content_Types].xml").unwrap_or_default();
+    if let Some(updated) = ensure_content_types(
+        &content_types,
+        &[
+            ("/word/document.xml", MAIN_DOCUMENT_CONTENT_TYPE),
+            ("/word/styles.xml", STYLES_CONTENT_TYPE),
+        ],
+    ) {
+        replacements.insert("[Content_Types].xml".to_string(), updated.into_bytes());
+    }
+    drop(archive);
+
+    let mut repaired_parts = replacements.keys().cloned().collect::<Vec<String>>();
+    repaired_parts.sort();
+    if !repaired_parts.is_empty() {
+        rewrite_docx_with_parts(capture_path, &replacements)?;
+    }
+    Ok(repaired_parts)
+}
+
+fn paragraph_has_content(paragraph: Node<'_, '_>) -> bool {
+    !extract_paragraph_text(paragraph).trim().is_empty()
+        || paragraph.descendants().any(|node| {
+            has_tag(node, "drawing") || has_tag(node, "pict") || has_tag(node, "object")
+        })
+}
+
 pub(crate) fn document_has_body_content(document_xml: &str) -> bool {
     let Ok(document) = Document::parse(document_xml) else {
-        return document_xml.contains("<w:p") || document_xml.contains("<w:tbl");
+        return document_xml.contains("<w:t") || document_xml.contains("<w:tbl");
     };
 
     let Some(body) = document.descendants().find(|node| has_tag(*node, "body")) else {
         return false;
     };
 
-    body.children()
-        .any(|node| node.is_element() && !has_tag(node, "sectPr"))
+    body.children().any(|node| {
+        node.is_element()
+            && !has_tag(node, "sectPr")
+            && (!has_tag(node, "p") || paragraph_has_content(node))
+    })
 }
 
 pub(crate) fn body_bounds(document_xml: &str) -> CommandResult<(usize, usize)> {
@@ -401,6 +687,41 @@ fn parse_style_ids(styles_xml: &str) -> HashSet<String> {
     ids
 }
 
+fn heading_style_xml(level: i64) -> String {
+    format!(
+        "<w:style w:type=\"paragraph\" w:styleId=\"Heading{level}\"><w:name w:val=\"heading {level}\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:uiPriority w:val=\"9\"/><w:qFormat/><w:pPr><w:keepNext/><w:outlineLvl w:val=\"{}\"/></w:pPr><w:rPr><w:b/></w:rPr></w:style>",
+        level - 1
+    )
+}
+
+// Fresh capture targets carry no heading styles, so a `HeadingN` paragraph would
+// render as body text in Word; add a minimal definition for each level in use.
+pub(crate) fn ensure_heading_styles(styles_xml: &str, paragraph_xml: &[String]) -> String {
+    let existing_ids = parse_style_ids(styles_xml);
+    let missing = (1..=9)
+        .filter(|level| {
+            let style_id = format!("Heading{level}");
+            let style_ref = format!("<w:pStyle w:val=\"{style_id}\"/>");
+            !existing_ids.contains(&style_id)
+                && paragraph_xml.iter().any(|xml| xml.contains(&style_ref))
+        })
+        .map(heading_style_xml)
+        .collect::<Vec<String>>();
+    if missing.is_empty() {
+        return styles_xml.to_string();
+    }
+
+    match styles_xml.rfind("</w:styles>") {
+        Some(styles_close) => format!(
+            "{}{}{}",
+            &styles_xml[..styles_close],
+            missing.join(""),
+            &styles_xml[styles_close..]
+        ),
+        None => styles_xml.to_string(),
+    }
+}
+
 fn collect_required_style_ids(
     requested_ids: &HashSet<String>,
     definitions: &HashMap<String, SourceStyleDefinition>,
@@ -852,12 +1173,29 @@ pub(crate) fn rewrite_docx_with_parts(
     }
 }
 
-pub(crate) fn append_capture_to_docx(
+pub(crate) fn write_section_docx(
+    output_path: &Path,
+    source_file_path: &Path,
+    styled_section: &StyledSection,
+) -> CommandResult<()> {
+    create_blank_docx(output_path)?;
+    insert_section_into_docx(
+        output_path,
+        source_file_path,
+        None,
+        None,
+        styled_section,
+        None,
+    )
+}
+
+pub(crate) fn insert_section_into_docx(
     capture_path: &Path,
     source_file_path: &Path,
     heading_level: Option<i64>,
     selected_target_heading_order: Option<i64>,
     styled_section: &StyledSection,
+    banner_text: Option<&str>,
 ) -> CommandResult<()> {
     if let Some(parent) = capture_path.parent() {
         fs::create_dir_all(parent).map_err(|error| {
@@ -915,6 +1253,8 @@ pub(crate) fn append_capture_to_docx(
         }
     }
 
+    target_styles_xml = ensure_heading_styles(&target_styles_xml, &section_paragraph_xml);
+
     let citation_paragraph_style_id = resolve_citation_paragraph_style_id(&target_styles_xml);
     apply_citation_style_placeholders(
         &mut section_paragraph_xml,
@@ -922,8 +1262,11 @@ pub(crate) fn append_capture_to_docx(
     );
 
     let mut fragment = String::new();
-    if !document_has_body_content(&target_document_xml) {
-        fragment.push_str(&paragraph_xml_bold("Block File Captures"));
+    if let Some(banner_text) = banner_text {
+        // A banner counts as body content, so it is only ever added to an empty target.
+        if !document_has_body_content(&target_document_xml) {
+            fragment.push_str(&paragraph_xml_bold(banner_text));
+        }
     }
 
     for paragraph in &section_paragraph_xml {
@@ -961,3 +1304,143 @@ pub(crate) fn append_capture_to_docx(
 
     rewrite_docx_with_parts(capture_path, &replacements)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        document_has_body_content, fallback_styled_section, insert_section_into_docx,
+        paragraph_xml_bold, paragraph_xml_heading, repair_capture_docx,
+        DEFAULT_CAPTURE_BANNER_TEXT,
+    };
+    use crate::docx_parse::{parse_docx_paragraphs, read_docx_part};
+    use crate::test_support::{paragraph_xml, temp_dir, write_docx};
+
+    #[test]
+    fn fallback_section_honors_heading_and_bold_markers() {
+        let section = fallback_styled_section("# Tag\r\n**word**\nplain **text**\n#hashtag");
+
+        assert_eq!(section.paragraph_xml[0], paragraph_xml_heading(1, "Tag"));
+        assert_eq!(section.paragraph_xml[1], paragraph_xml_bold("word"));
+        assert!(!section.paragraph_xml[2].contains("<w:b/>"));
+        assert!(section.paragraph_xml[2].contains("plain **text**"));
+        assert!(!section.paragraph_xml[3].contains("pStyle"));
+    }
+
+    #[test]
+    fn fallback_section_maps_markdown_headings_up_to_level_six() {
+        let section = fallback_styled_section("##### Impact\n###### Card\n####### Too deep");
+
+        assert_eq!(section.paragraph_xml[0], paragraph_xml_heading(5, "Impact"));
+        assert_eq!(section.paragraph_xml[1], paragraph_xml_heading(6, "Card"));
+        assert!(!section.paragraph_xml[2].contains("pStyle"));
+    }
+
+    #[test]
+    fn repair_adds_missing_styles_without_losing_body() {
+        let dir = temp_dir("repair-capture");
+        let path = dir.join("BlockFile-Captures.docx");
+        write_docx(
+            &path,
+            &format!(
+                "{}{}",
+                paragraph_xml("Kept card text"),
+                paragraph_xml("Second paragraph")
+            ),
+        );
+
+        let repaired = repair_capture_docx(&path).unwrap();
+        assert_eq!(
+            repaired,
+            vec![
+                "[Content_Types].xml".to_string(),
+                "word/_rels/document.xml.rels".to_string(),
+                "word/styles.xml".to_string(),
+            ]
+        );
+
+        let styles = read_docx_part(&path, "word/styles.xml").unwrap().unwrap();
+        assert!(styles.contains("w:styleId=\"Normal\""));
+        let content_types = read_docx_part(&path, "[Content_Types].xml")
+            .unwrap()
+            .unwrap();
+        assert!(content_types.contains("/word/styles.xml"));
+        let texts = parse_docx_paragraphs(&path)
+            .unwrap()
+            .into_iter()
+            .map(|paragraph| paragraph.text)
+            .collect::<Vec<String>>();
+        assert_eq!(texts, vec!["Kept card text", "Second paragraph"]);
+
+        assert!(repair_capture_docx(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn repeated_inserts_into_fresh_target_add_one_banner() {
+        let dir = temp_dir("capture-banner");
+        let target = dir.join("BlockFile-Captures.docx");
+        let source = dir.join("Source.docx");
+        write_docx(&source, &paragraph_xml("Source card"));
+        write_docx(
+            &target,
+            "<w:p/><w:p><w:r><w:t xml:space=\"preserve\">  </w:t></w:r></w:p>",
+        );
+
+        for tag in ["First tag", "Second tag"] {
+            let section = fallback_styled_section(&format!("# {tag}\nCard text"));
+            insert_section_into_docx(
+                &target,
+                &source,
+                Some(1),
+                None,
+                &section,
+                Some(DEFAULT_CAPTURE_BANNER_TEXT),
+            )
+            .unwrap();
+        }
+        let paragraphs = parse_docx_paragraphs(&target).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let banners = paragraphs
+            .iter()
+            .filter(|paragraph| paragraph.text == DEFAULT_CAPTURE_BANNER_TEXT)
+            .count();
+        assert_eq!(banners, 1);
+        assert!(paragraphs
+            .iter()
+            .any(|paragraph| paragraph.text == "Second tag"));
+        assert!(!document_has_body_content(
+            "<w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>\n  <w:sectPr/></w:body></w:document>"
+        ));
+    }
+
+    #[test]
+    fn inserts_into_banner_only_target_keep_one_banner() {
+        let dir = temp_dir("capture-banner-only");
+        let target = dir.join("BlockFile-Captures.docx");
+        let source = dir.join("Source.docx");
+        write_docx(&source, &paragraph_xml("Source card"));
+        write_docx(&target, &paragraph_xml_bold(DEFAULT_CAPTURE_BANNER_TEXT));
+
+        for tag in ["First tag", "Second tag"] {
+            let section = fallback_styled_section(&format!("# {tag}\nCard text"));
+            insert_section_into_docx(
+                &target,
+                &source,
+                Some(1),
+                None,
+                &section,
+                Some(DEFAULT_CAPTURE_BANNER_TEXT),
+            )
+            .unwrap();
+        }
+        let paragraphs = parse_docx_paragraphs(&target).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let banners = paragraphs
+            .iter()
+            .filter(|paragraph| paragraph.text == DEFAULT_CAPTURE_BANNER_TEXT)
+            .count();
+        assert_eq!(banners, 1);
+        assert_eq!(paragraphs[0].text, DEFAULT_CAPTURE_BANNER_TEXT);
+    }
+}
@@ -7,10 +7,21 @@ use roxmltree::{Document, Node};
 use zip::ZipArchive;
 
 use crate::search::normalize_for_search;
-use crate::types::{HeadingRange, ParsedHeading, ParsedParagraph};
+use crate::types::{
+    CommentBody, DocumentProperties, FileHeading, FileStyleUsage, HeadingNeighbors, HeadingRange,
+    HighlightedBlock, OutlineHeading, ParsedComment, ParsedHeading, ParsedParagraph,
+    PreviewDebugStats,
+};
 use crate::util::{is_probable_author_line, path_display};
 use crate::CommandResult;
 
+const OOXML_ATTRIBUTE_NAMESPACES: [&str; 4] = [
+    "http://schemas.openxmlformats.org/wordprocessingml/2006/main",
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+    "http://purl.oclc.org/ooxml/wordprocessingml/main",
+    "http://purl.oclc.org/ooxml/officeDocument/relationships",
+];
+
 pub(crate) fn has_tag(node: Node<'_, '_>, expected: &str) -> bool {
     node.is_element() && node.tag_name().name() == expected
 }
@@ -19,8 +30,13 @@ pub(crate) fn attribute_value<'a>(node: Node<'a, 'a>, key: &str) -> Option<&'a s
     if let Some(value) = node.attribute(key) {
         return Some(value);
     }
-    node.attributes()
-        .find_map(|attribute| (attribute.name().ends_with(key)).then_some(attribute.value()))
+    OOXML_ATTRIBUTE_NAMESPACES
+        .iter()
+        .find_map(|namespace| node.attribute((*namespace, key)))
+        .or_else(|| {
+            let namespace = node.tag_name().namespace()?;
+            node.attribute((namespace, key))
+        })
 }
 
 pub(crate) fn parse_trailing_level(value: &str) -> Option<i64> {
@@ -57,6 +73,23 @@ pub(crate) fn read_zip_file(archive: &mut ZipArchive<File>, entry_name: &str) ->
     Some(value)
 }
 
+const ZIP_LOCAL_HEADER_MAGIC: &[u8; 4] = b"PK\x03\x04";
+
+pub(crate) fn corrupt_docx_reason(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut magic = [0_u8; 4];
+    let read = file.read(&mut magic).ok()?;
+    if read == 0 {
+        return Some("empty file (0 bytes)".to_string());
+    }
+    if read < magic.len() || &magic != ZIP_LOCAL_HEADER_MAGIC {
+        return Some("not a zip archive".to_string());
+    }
+    ZipArchive::new(file)
+        .err()
+        .map(|error| format!("truncated or damaged zip archive: {error}"))
+}
+
 pub(crate) fn read_docx_part(path: &Path, part_name: &str) -> CommandResult<Option<String>> {
     let file = File::open(path)
         .map_err(|error| format!("Could not open '{}': {error}", path_display(path)))?;
@@ -169,10 +202,77 @@ pub(crate) fn run_highlight_class(run: Node<'_, '_>) -> Option<&'static str> {
     }
 }
 
+pub(crate) fn is_toc_style(value: &str) -> bool {
+    let compact = value
+        .chars()
+        .filter(|character| !character.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let Some(rest) = compact.strip_prefix("toc") else {
+        return false;
+    };
+    rest.is_empty() || rest == "heading" || rest.chars().all(|character| character.is_ascii_digit())
+}
+
+fn is_toc_instruction(instruction: &str) -> bool {
+    instruction
+        .trim_start()
+        .get(..3)
+        .is_some_and(|keyword| keyword.eq_ignore_ascii_case("toc"))
+}
+
+fn paragraph_has_toc_style(paragraph: Node<'_, '_>, style_map: &HashMap<String, String>) -> bool {
+    paragraph_style_id(paragraph).is_some_and(|style_id| {
+        is_toc_style(style_id)
+            || style_map
+                .get(style_id)
+                .is_some_and(|style_name| is_toc_style(style_name))
+    })
+}
+
+// A complex TOC field begins in one paragraph and its entries follow until the matching end.
+#[derive(Default)]
+struct TocFieldTracker {
+    open_fields: Vec<bool>,
+}
+
+impl TocFieldTracker {
+    fn in_toc(&self) -> bool {
+        self.open_fields.iter().any(|is_toc| *is_toc)
+    }
+
+    fn observe(&mut self, paragraph: Node<'_, '_>) -> bool {
+        let mut touched_toc = self.in_toc();
+        for node in paragraph.descendants().filter(|node| node.is_element()) {
+            if has_tag(node, "fldSimple") {
+                touched_toc |= attribute_value(node, "instr").is_some_and(is_toc_instruction);
+            } else if has_tag(node, "fldChar") {
+                match attribute_value(node, "fldCharType") {
+                    Some("begin") => self.open_fields.push(false),
+                    Some("end") => {
+                        self.open_fields.pop();
+                    }
+                    _ => {}
+                }
+            } else if has_tag(node, "instrText") {
+                let is_toc = node.text().is_some_and(is_toc_instruction);
+                if let Some(current) = self.open_fields.last_mut() {
+                    *current |= is_toc;
+                }
+                touched_toc |= is_toc;
+            }
+        }
+        touched_toc || self.in_toc()
+    }
+}
+
 pub(crate) fn detect_heading_level(
     paragraph: Node<'_, '_>,
     style_map: &HashMap<String, String>,
 ) -> Option<i64> {
+    if paragraph_has_toc_style(paragraph, style_map) {
+        return None;
+    }
     let paragraph_props = paragraph.children().find(|node| has_tag(*node, "pPr"))?;
 
     if let Some(outline_level_node) = paragraph_props
@@ -205,15 +305,19 @@ pub(crate) fn detect_heading_level(
     None
 }
 
-pub(crate) fn paragraph_style_label(
-    paragraph: Node<'_, '_>,
-    style_map: &HashMap<String, String>,
-) -> Option<String> {
+pub(crate) fn paragraph_style_id<'a>(paragraph: Node<'a, 'a>) -> Option<&'a str> {
     let paragraph_props = paragraph.children().find(|node| has_tag(*node, "pPr"))?;
     let style_node = paragraph_props
         .children()
         .find(|node| has_tag(*node, "pStyle"))?;
-    let style_id = attribute_value(style_node, "val")?;
+    attribute_value(style_node, "val")
+}
+
+pub(crate) fn paragraph_style_label(
+    paragraph: Node<'_, '_>,
+    style_map: &HashMap<String, String>,
+) -> Option<String> {
+    let style_id = paragraph_style_id(paragraph)?;
     let style_name = style_map
         .get(style_id)
         .cloned()
@@ -229,6 +333,12 @@ pub(crate) fn is_f8_cite_style(style_label: &str) -> bool {
 pub(crate) fn parse_docx_paragraphs(file_path: &Path) -> CommandResult<Vec<ParsedParagraph>> {
     let file = File::open(file_path)
         .map_err(|error| format!("Could not open '{}': {error}", path_display(file_path)))?;
+    if file.metadata().map(|metadata| metadata.len()).unwrap_or(0) == 0 {
+        return Err(format!(
+            "Could not read '{}': empty file (0 bytes)",
+            path_display(file_path)
+        ));
+    }
     let mut archive = ZipArchive::new(file)
         .map_err(|error| format!("Could not read '{}': {error}", path_display(file_path)))?;
 
@@ -250,12 +360,25 @@ pub(crate) fn parse_docx_paragraphs(file_path: &Path) -> CommandResult<Vec<Parse
 
     let mut order = 0_i64;
     let mut paragraphs = Vec::new();
+    let mut toc_fields = TocFieldTracker::default();
 
     for paragraph in document.descendants().filter(|node| has_tag(*node, "p")) {
-        let text = extract_paragraph_text(paragraph);
-
         order += 1;
         let style_label = paragraph_style_label(paragraph, &style_map);
+        let in_toc_field = toc_fields.observe(paragraph);
+        // TOC entries stay as blank placeholders so paragraph indices keep matching the XML nodes.
+        if in_toc_field || paragraph_has_toc_style(paragraph, &style_map) {
+            paragraphs.push(ParsedParagraph {
+                order,
+                text: String::new(),
+                heading_level: None,
+                style_label,
+                is_f8_cite: false,
+            });
+            continue;
+        }
+        let text = extract_paragraph_text(paragraph);
+
         let is_f8_cite = style_label
             .as_ref()
             .map(|label| is_f8_cite_style(label))
@@ -277,6 +400,299 @@ pub(crate) fn parse_docx_paragraphs(file_path: &Path) -> CommandResult<Vec<Parse
     Ok(paragraphs)
 }
 
+pub(crate) fn read_comment_bodies(comments_xml: &str) -> HashMap<String, CommentBody> {
+    let Ok(document) = Document::parse(comments_xml) else {
+        return HashMap::new();
+    };
+    document
+        .descendants()
+        .filter(|node| has_tag(*node, "comment"))
+        .filter_map(|comment| {
+            let id = attribute_value(comment, "id")?.to_string();
+            let text = comment
+                .descendants()
+                .filter(|node| has_tag(*node, "p"))
+                .map(extract_paragraph_text)
+                .filter(|line| !line.trim().is_empty())
+                .collect::<Vec<String>>()
+                .join("\n");
+            if text.trim().is_empty() {
+                return None;
+            }
+            let author = attribute_value(comment, "author")
+                .map(str::trim)
+                .filter(|author| !author.is_empty())
+                .map(str::to_string);
+            Some((id, CommentBody { author, text }))
+        })
+        .collect()
+}
+
+pub(crate) fn paragraph_comment_ids(paragraph: Node<'_, '_>, tag: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for node in paragraph.descendants().filter(|node| has_tag(*node, tag)) {
+        if let Some(id) = attribute_value(node, "id") {
+            if !ids.iter().any(|existing| existing == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    ids
+}
+
+// Title, author and keywords from docProps/core.xml; app.xml carries no searchable text.
+pub(crate) fn parse_docx_properties(file_path: &Path) -> CommandResult<DocumentProperties> {
+    let Some(core_xml) = read_docx_part(file_path, "docProps/core.xml")? else {
+        return Ok(DocumentProperties::default());
+    };
+    let Ok(document) = Document::parse(&core_xml) else {
+        return Ok(DocumentProperties::default());
+    };
+    let property = |tag: &str| {
+        document
+            .descendants()
+            .find(|node| has_tag(*node, tag))
+            .and_then(|node| node.text())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    Ok(DocumentProperties {
+        title: property("title"),
+        creator: property("creator"),
+        keywords: property("keywords"),
+    })
+}
+
+pub(crate) fn parse_docx_comments(file_path: &Path) -> CommandResult<Vec<ParsedComment>> {
+    let Some(comments_xml) = read_docx_part(file_path, "word/comments.xml")? else {
+        return Ok(Vec::new());
+    };
+    let mut bodies = read_comment_bodies(&comments_xml);
+    if bodies.is_empty() {
+        return Ok(Vec::new());
+    }
+    let document_xml = read_docx_part(file_path, "word/document.xml")?.ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'. Is this a valid docx file?",
+            path_display(file_path)
+        )
+    })?;
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse XML in '{}': {error}",
+            path_display(file_path)
+        )
+    })?;
+
+    // A comment belongs to the paragraph where its range starts, or else where it is referenced.
+    let mut comments = Vec::new();
+    let mut order = 0_i64;
+    for paragraph in document.descendants().filter(|node| has_tag(*node, "p")) {
+        order += 1;
+        let mut ids = paragraph_comment_ids(paragraph, "commentRangeStart");
+        ids.extend(paragraph_comment_ids(paragraph, "commentReference"));
+        for id in ids {
+            if let Some(body) = bodies.remove(&id) {
+                comments.push(ParsedComment {
+                    paragraph_order: order,
+                    author: body.author,
+                    text: body.text,
+                });
+            }
+        }
+    }
+    Ok(comments)
+}
+
+fn paragraph_highlighted_text(paragraph: Node<'_, '_>, colors: &[String]) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    let mut contiguous = false;
+    for run in paragraph.descendants().filter(|node| has_tag(*node, "r")) {
+        let matches = run_highlight_class(run)
+            .is_some_and(|class| colors.is_empty() || colors.iter().any(|color| color == class));
+        if !matches {
+            contiguous = false;
+            continue;
+        }
+        let text = extract_paragraph_text(run);
+        match segments.last_mut() {
+            Some(segment) if contiguous => segment.push_str(&text),
+            _ => segments.push(text),
+        }
+        contiguous = true;
+    }
+    segments
+        .iter()
+        .map(|segment| segment.trim())
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+pub(crate) fn extract_highlighted_blocks(
+    file_path: &Path,
+    colors: &[String],
+) -> CommandResult<Vec<HighlightedBlock>> {
+    let paragraph_levels = parse_docx_paragraphs(file_path)?
+        .into_iter()
+        .map(|paragraph| (paragraph.order, paragraph.heading_level))
+        .collect::<HashMap<i64, Option<i64>>>();
+    let document_xml = read_docx_part(file_path, "word/document.xml")?.ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'. Is this a valid docx file?",
+            path_display(file_path)
+        )
+    })?;
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse XML in '{}': {error}",
+            path_display(file_path)
+        )
+    })?;
+
+    let mut blocks: Vec<HighlightedBlock> = Vec::new();
+    let mut heading_order = None;
+    let mut order = 0_i64;
+    for paragraph in document.descendants().filter(|node| has_tag(*node, "p")) {
+        order += 1;
+        let Some(level) = paragraph_levels.get(&order) else {
+            continue;
+        };
+        if level.is_some() {
+            heading_order = Some(order);
+        }
+        let text = paragraph_highlighted_text(paragraph, colors);
+        if text.is_empty() {
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(block) if block.heading_order == heading_order => {
+                block.text.push('\n');
+                block.text.push_str(&text);
+            }
+            _ => blocks.push(HighlightedBlock {
+                heading_order,
+                text,
+            }),
+        }
+    }
+    Ok(blocks)
+}
+
+pub(crate) fn collect_docx_style_usage(file_path: &Path) -> CommandResult<Vec<FileStyleUsage>> {
+    let file = File::open(file_path)
+        .map_err(|error| format!("Could not open '{}': {error}", path_display(file_path)))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|error| format!("Could not read '{}': {error}", path_display(file_path)))?;
+
+    let document_xml = read_zip_file(&mut archive, "word/document.xml").ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'. Is this a valid docx file?",
+            path_display(file_path)
+        )
+    })?;
+    let style_map = read_style_map(read_zip_file(&mut archive, "word/styles.xml"));
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse XML in '{}': {error}",
+            path_display(file_path)
+        )
+    })?;
+
+    let mut usage: HashMap<String, FileStyleUsage> = HashMap::new();
+    for paragraph in document.descendants().filter(|node| has_tag(*node, "p")) {
+        let (Some(style_id), Some(style_label)) = (
+            paragraph_style_id(paragraph),
+            paragraph_style_label(paragraph, &style_map),
+        ) else {
+            continue;
+        };
+        let entry = usage.entry(style_id.to_string()).or_insert_with(|| {
+            let name = style_map
+                .get(style_id)
+                .cloned()
+                .unwrap_or_else(|| style_id.to_string());
+            let is_f8_cite = is_f8_cite_style(&style_label);
+            let heading_level = parse_trailing_level(style_id)
+                .or_else(|| parse_trailing_level(&name))
+                .filter(|_| !is_f8_cite);
+            FileStyleUsage {
+                style_id: style_id.to_string(),
+                name,
+                usage_count: 0,
+                heading_level,
+                is_f8_cite,
+            }
+        });
+        entry.usage_count += 1;
+    }
+
+    let mut styles = usage.into_values().collect::<Vec<FileStyleUsage>>();
+    styles.sort_by(|left, right| {
+        right
+            .usage_count
+            .cmp(&left.usage_count)
+            .then_with(|| left.style_id.cmp(&right.style_id))
+    });
+    Ok(styles)
+}
+
+// Counts only runs that carry text, so empty formatting-only runs don't skew the totals.
+pub(crate) fn collect_preview_debug_stats(file_path: &Path) -> CommandResult<PreviewDebugStats> {
+    let paragraphs = parse_docx_paragraphs(file_path)?;
+    let document_xml = read_docx_part(file_path, "word/document.xml")?.ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'. Is this a valid docx file?",
+            path_display(file_path)
+        )
+    })?;
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse XML in '{}': {error}",
+            path_display(file_path)
+        )
+    })?;
+
+    let mut stats = PreviewDebugStats {
+        paragraph_count: paragraphs.len(),
+        ..PreviewDebugStats::default()
+    };
+    for run in document.descendants().filter(|node| has_tag(*node, "r")) {
+        let has_text = run
+            .children()
+            .any(|node| has_tag(node, "t") && node.text().is_some_and(|text| !text.is_empty()));
+        if !has_text {
+            continue;
+        }
+        stats.run_count += 1;
+        if run_has_property(run, "b") {
+            stats.bold_runs += 1;
+        }
+        if run_has_property(run, "i") {
+            stats.italic_runs += 1;
+        }
+        if run_has_active_underline(run) {
+            stats.underline_runs += 1;
+        }
+        if run_highlight_class(run).is_some() {
+            stats.highlight_runs += 1;
+        }
+    }
+
+    let mut previous_f8_cite = false;
+    for paragraph in &paragraphs {
+        if let Some(level) = paragraph.heading_level {
+            *stats.heading_levels.entry(level).or_insert(0) += 1;
+        }
+        if paragraph.is_f8_cite && !previous_f8_cite {
+            stats.f8_cite_blocks += 1;
+        }
+        previous_f8_cite = paragraph.is_f8_cite;
+    }
+    Ok(stats)
+}
+
 pub(crate) fn build_heading_ranges(paragraphs: &[ParsedParagraph]) -> Vec<HeadingRange> {
     let mut heading_indices = Vec::new();
     for (index, paragraph) in paragraphs.iter().enumerate() {
@@ -316,6 +732,105 @@ pub(crate) fn build_heading_ranges(paragraphs: &[ParsedParagraph]) -> Vec<Headin
     ranges
 }
 
+fn outline_children(
+    paragraphs: &[ParsedParagraph],
+    ranges: &[HeadingRange],
+    position: &mut usize,
+    parent_level: i64,
+    parent_end: usize,
+) -> Vec<OutlineHeading> {
+    let mut children = Vec::new();
+    while let Some(range) = ranges.get(*position) {
+        if range.level <= parent_level || range.start_index >= parent_end {
+            break;
+        }
+        *position += 1;
+        let nested = outline_children(paragraphs, ranges, position, range.level, range.end_index);
+        children.push(OutlineHeading {
+            order: range.order,
+            level: range.level,
+            text: paragraphs[range.start_index].text.clone(),
+            children: nested,
+        });
+    }
+    children
+}
+
+pub(crate) fn heading_outline(paragraphs: &[ParsedParagraph]) -> Vec<OutlineHeading> {
+    let ranges = build_heading_ranges(paragraphs);
+    let mut position = 0;
+    outline_children(
+        paragraphs,
+        &ranges,
+        &mut position,
+        i64::MIN,
+        paragraphs.len(),
+    )
+}
+
+pub(crate) fn heading_neighbors(
+    paragraphs: &[ParsedParagraph],
+    headings: &[FileHeading],
+    heading_order: i64,
+) -> Option<HeadingNeighbors> {
+    let ranges = build_heading_ranges(paragraphs);
+    let position = ranges
+        .iter()
+        .position(|range| range.order == heading_order)?;
+    let current = &ranges[position];
+    let (before, after) = (&ranges[..position], &ranges[position + 1..]);
+
+    let parent = before
+        .iter()
+        .rev()
+        .find(|range| range.level < current.level);
+    let previous_sibling = before
+        .iter()
+        .rev()
+        .take_while(|range| range.level >= current.level)
+        .find(|range| range.level == current.level);
+    let next_sibling = after
+        .iter()
+        .take_while(|range| range.level >= current.level)
+        .find(|range| range.level == current.level);
+    let first_child = after
+        .first()
+        .filter(|range| range.level > current.level && range.start_index < current.end_index);
+
+    let heading_for = |range: Option<&HeadingRange>| {
+        let order = range?.order;
+        headings
+            .iter()
+            .find(|heading| heading.order == order)
+            .cloned()
+    };
+    Some(HeadingNeighbors {
+        parent: heading_for(parent),
+        previous_sibling: heading_for(previous_sibling),
+        next_sibling: heading_for(next_sibling),
+        first_child: heading_for(first_child),
+    })
+}
+
+pub(crate) fn heading_paths(paragraphs: &[ParsedParagraph]) -> HashMap<i64, Vec<String>> {
+    let ranges = build_heading_ranges(paragraphs);
+    ranges
+        .iter()
+        .map(|range| {
+            let ancestors = ranges
+                .iter()
+                .filter(|candidate| {
+                    candidate.level < range.level
+                        && candidate.start_index < range.start_index
+                        && range.start_index < candidate.end_index
+                })
+                .map(|candidate| paragraphs[candidate.start_index].text.clone())
+                .collect::<Vec<String>>();
+            (range.order, ancestors)
+        })
+        .collect()
+}
+
 pub(crate) fn resolve_insert_after_order(
     paragraphs: &[ParsedParagraph],
     selected_target_heading_order: Option<i64>,
@@ -411,3 +926,156 @@ pub(crate) fn extract_docx_headings_and_authors(
     let authors = crate::util::extract_author_candidates(&paragraphs);
     Ok((headings, authors))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_highlighted_blocks, is_toc_style, parse_docx_paragraphs};
+    use crate::test_support::{heading_xml, paragraph_xml, temp_dir, write_docx, write_docx_parts};
+
+    #[test]
+    fn headings_parse_with_alternate_namespace_prefixes() {
+        let dir = temp_dir("alternate-prefixes");
+        let path = dir.join("Exported.docx");
+        let document = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+            "<ns0:document xmlns:ns0=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\" ",
+            "xmlns:ext=\"urn:example:ext\"><ns0:body>",
+            "<ns0:p><ns0:pPr><ns0:pStyle ext:defaultval=\"Normal\" ns0:val=\"Title2\"/></ns0:pPr>",
+            "<ns0:r><ns0:t>Econ DA</ns0:t></ns0:r></ns0:p>",
+            "<ns0:p><ns0:r><ns0:t>Growth is steady</ns0:t></ns0:r></ns0:p>",
+            "</ns0:body></ns0:document>"
+        );
+        let styles = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+            "<s:styles xmlns:s=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">",
+            "<s:style s:type=\"paragraph\" s:styleId=\"Title2\"><s:name s:val=\"heading 2\"/></s:style>",
+            "</s:styles>"
+        );
+        write_docx_parts(
+            &path,
+            &[
+                ("word/document.xml", document.to_string()),
+                ("word/styles.xml", styles.to_string()),
+            ],
+        );
+
+        let paragraphs = parse_docx_paragraphs(&path).unwrap();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].heading_level, Some(2));
+        assert_eq!(
+            paragraphs[0].style_label.as_deref(),
+            Some("heading 2 (Title2)")
+        );
+        assert_eq!(paragraphs[1].heading_level, None);
+    }
+
+    #[test]
+    fn table_of_contents_paragraphs_are_excluded() {
+        let dir = temp_dir("toc-exclusion");
+        let path = dir.join("Toc.docx");
+        let toc_entry = |style: &str, text: &str| {
+            format!(
+                "<w:p><w:pPr><w:pStyle w:val=\"{style}\"/><w:outlineLvl w:val=\"0\"/></w:pPr><w:r><w:t>{text}</w:t></w:r></w:p>"
+            )
+        };
+        let body = [
+            toc_entry("TOCHeading", "Contents"),
+            concat!(
+                "<w:p><w:r><w:fldChar w:fldCharType=\"begin\"/></w:r>",
+                "<w:r><w:instrText xml:space=\"preserve\"> TOC \\o \"1-3\" \\h </w:instrText></w:r>",
+                "<w:r><w:fldChar w:fldCharType=\"separate\"/></w:r></w:p>"
+            )
+            .to_string(),
+            "<w:p><w:pPr><w:outlineLvl w:val=\"0\"/></w:pPr><w:r><w:t>Econ DA\t4</w:t></w:r></w:p>".to_string(),
+            "<w:p><w:r><w:t>Uniqueness\t5</w:t></w:r><w:r><w:fldChar w:fldCharType=\"end\"/></w:r></w:p>".to_string(),
+            "<w:p><w:fldSimple w:instr=\"TOC \\o\"><w:r><w:t>Politics DA\t9</w:t></w:r></w:fldSimple></w:p>".to_string(),
+            toc_entry("TOC2", "Link\t7"),
+            heading_xml(1, "Econ DA"),
+            heading_xml(2, "Uniqueness"),
+            paragraph_xml("Growth is steady."),
+        ]
+        .concat();
+        write_docx(&path, &body);
+
+        let paragraphs = parse_docx_paragraphs(&path).expect("parse toc docx");
+        assert_eq!(paragraphs.len(), 9);
+        let summary = paragraphs
+            .iter()
+            .filter(|paragraph| !paragraph.text.is_empty())
+            .map(|paragraph| {
+                (
+                    paragraph.order,
+                    paragraph.heading_level,
+                    paragraph.text.as_str(),
+                )
+            })
+            .collect::<Vec<(i64, Option<i64>, &str)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (7, Some(1), "Econ DA"),
+                (8, Some(2), "Uniqueness"),
+                (9, None, "Growth is steady."),
+            ]
+        );
+        assert!(is_toc_style("toc 3"));
+        assert!(!is_toc_style("Tocsin"));
+    }
+
+    #[test]
+    fn highlighted_runs_are_grouped_under_their_heading() {
+        let dir = temp_dir("highlighted-runs");
+        let path = dir.join("Highlights.docx");
+        let run = |highlight: Option<&str>, text: &str| {
+            match highlight {
+            Some(color) => format!(
+                "<w:r><w:rPr><w:highlight w:val=\"{color}\"/></w:rPr><w:t xml:space=\"preserve\">{text}</w:t></w:r>"
+            ),
+            None => format!("<w:r><w:t xml:space=\"preserve\">{text}</w:t></w:r>"),
+        }
+        };
+        let body = [
+            heading_xml(1, "Econ DA"),
+            format!(
+                "<w:p>{}{}{}{}</w:p>",
+                run(Some("yellow"), "Growth is "),
+                run(Some("yellow"), "resilient"),
+                run(None, " despite shocks and "),
+                run(Some("green"), "rates stay low"),
+            ),
+            format!(
+                "<w:p>{}</w:p>",
+                run(Some("darkYellow"), "Consumer spending up")
+            ),
+            heading_xml(1, "Politics DA"),
+            format!("<w:p>{}</w:p>", run(Some("cyan"), "Midterms are close")),
+        ]
+        .concat();
+        write_docx(&path, &body);
+
+        let summary = |colors: &[String]| {
+            extract_highlighted_blocks(&path, colors)
+                .expect("extract highlights")
+                .into_iter()
+                .map(|block| (block.heading_order, block.text))
+                .collect::<Vec<(Option<i64>, String)>>()
+        };
+        assert_eq!(
+            summary(&["yellow".to_string()]),
+            vec![(
+                Some(1),
+                "Growth is resilient\nConsumer spending up".to_string()
+            )]
+        );
+        assert_eq!(
+            summary(&[]),
+            vec![
+                (
+                    Some(1),
+                    "Growth is resilient rates stay low\nConsumer spending up".to_string()
+                ),
+                (Some(4), "Midterms are close".to_string()),
+            ]
+        );
+    }
+}
@@ -4,8 +4,12 @@ use crate::db::open_database;
 use crate::lexical;
 use crate::CommandResult;
 
-pub(crate) fn rebuild_lexical_index(app: &AppHandle) -> CommandResult<()> {
+pub(crate) fn rebuild_lexical_index(app: &AppHandle) -> CommandResult<usize> {
     let connection = open_database(app)?;
-    lexical::replace_all_documents_from_connection(app, &connection)?;
-    Ok(())
+    lexical::replace_all_documents_from_connection(app, &connection)
+}
+
+pub(crate) fn refresh_lexical_file(app: &AppHandle, file_id: i64) -> CommandResult<usize> {
+    let connection = open_database(app)?;
+    lexical::replace_file_documents_from_connection(app, &connection, file_id)
 }
@@ -1,12 +1,16 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::sync::{Mutex, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
 
 use crate::runtime::AppHandle;
-use rusqlite::Connection;
-use tantivy::collector::TopDocs;
-use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use rusqlite::{params, Connection};
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{
+    BooleanQuery, FuzzyTermQuery, Occur, Query, QueryParser, RegexQuery, TermQuery,
+};
 use tantivy::schema::{
     Field, IndexRecordOption, NumericOptions, Schema, TextFieldIndexing, TextOptions, Value,
     STORED, STRING, TEXT,
@@ -14,17 +18,64 @@ use tantivy::schema::{
 use tantivy::tokenizer::{LowerCaser, NgramTokenizer, TextAnalyzer};
 use tantivy::{doc, Index, IndexReader, ReloadPolicy, TantivyDocument, Term};
 
-use crate::db::index_lexical_dir;
-use crate::search::normalize_for_search;
-use crate::types::SearchHit;
+use crate::db::{app_data_dir, index_lexical_dir, open_database};
+use crate::logging;
+use crate::search::{fold_typography, normalize_for_search};
+use crate::types::{FileScope, LexicalTokenizerConfig, SearchHit};
 use crate::CommandResult;
 
 const PREFIX_TOKENIZER: &str = "bf_prefix";
 const NGRAM_TOKENIZER: &str = "bf_ngram";
+const TOKENIZER_CONFIG_FILE: &str = "tokenizer-config.txt";
+const TOKENIZER_SETTINGS_FILE_NAME: &str = "lexical-tokenizers.json";
+const MAX_TOKENIZER_GRAM: usize = 32;
+pub(crate) const DEFAULT_TOKENIZER_CONFIG: LexicalTokenizerConfig = LexicalTokenizerConfig {
+    prefix_min_gram: 2,
+    prefix_max_gram: 18,
+    ngram_min_gram: 3,
+    ngram_max_gram: 4,
+};
 const MIN_FETCH_MULTIPLIER: usize = 5;
 const MIN_FETCH_FLOOR: usize = 80;
 const MAX_FETCH_LIMIT: usize = 1_800;
 const CHUNK_PREVIEW_CHARS: usize = 240;
+const FUZZY_TRIGGER_RESULTS: usize = 5;
+const FUZZY_MIN_TERM_CHARS: usize = 4;
+const FUZZY_MAX_TERMS: usize = 4;
+const WILDCARD_MIN_LITERAL_CHARS: usize = 3;
+const WILDCARD_MAX_TERMS: usize = 6;
+const DEFAULT_WRITER_HEAP_BYTES: usize = 256_000_000;
+const MIN_WRITER_HEAP_BYTES: usize = 15_000_000;
+const MAX_WRITER_HEAP_BYTES: usize = 4_000_000_000;
+const DEFAULT_KIND_BOOST: f64 = 3.0;
+const MAX_KIND_BOOST: f64 = 100.0;
+pub(crate) const DEFAULT_FILE_NAME_BOOST: f64 = 1.0;
+const MAX_FILE_NAME_BOOST: f64 = 10.0;
+
+#[derive(Clone, Debug)]
+pub(crate) struct LexicalSearchOptions {
+    pub requested_root_ids: Vec<i64>,
+    pub argument_type: Option<String>,
+    pub file_scope: Option<FileScope>,
+    pub limit: usize,
+    pub file_name_only: bool,
+    pub file_name_boost: f64,
+    pub explain: bool,
+}
+
+impl LexicalSearchOptions {
+    pub(crate) fn new(requested_root_ids: &[i64], limit: usize) -> Self {
+        Self {
+            requested_root_ids: requested_root_ids.to_vec(),
+            argument_type: None,
+            file_scope: None,
+            limit,
+            file_name_only: false,
+            file_name_boost: DEFAULT_FILE_NAME_BOOST,
+            explain: false,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct LexicalDocument {
@@ -39,6 +90,8 @@ pub(crate) struct LexicalDocument {
     pub heading_order: Option<i64>,
     pub author_text: Option<String>,
     pub chunk_text: Option<String>,
+    pub content_hash: Option<String>,
+    pub argument_type: Option<String>,
 }
 
 #[derive(Clone)]
@@ -58,6 +111,8 @@ struct LexicalFields {
     query_text: Field,
     prefix_text: Field,
     ngram_text: Field,
+    content_hash: Field,
+    argument_type: Field,
 }
 
 struct LexicalRuntime {
@@ -66,7 +121,134 @@ struct LexicalRuntime {
     fields: LexicalFields,
 }
 
-static LEXICAL_RUNTIME: OnceLock<Mutex<LexicalRuntime>> = OnceLock::new();
+struct RegisteredRuntime {
+    fingerprint: String,
+    runtime: Arc<Mutex<LexicalRuntime>>,
+}
+
+type LexicalRuntimeRegistry = HashMap<PathBuf, RegisteredRuntime>;
+
+static LEXICAL_RUNTIMES: OnceLock<Mutex<LexicalRuntimeRegistry>> = OnceLock::new();
+static WRITER_HEAP_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_WRITER_HEAP_BYTES);
+static KIND_BOOST_BITS: AtomicU64 = AtomicU64::new(DEFAULT_KIND_BOOST.to_bits());
+static TOKENIZER_CONFIGS: OnceLock<Mutex<HashMap<PathBuf, LexicalTokenizerConfig>>> =
+    OnceLock::new();
+
+fn clamp_writer_heap_bytes(bytes: usize) -> usize {
+    bytes.clamp(MIN_WRITER_HEAP_BYTES, MAX_WRITER_HEAP_BYTES)
+}
+
+pub(crate) fn configure_writer_heap_bytes(bytes: usize) -> usize {
+    let clamped = clamp_writer_heap_bytes(bytes);
+    WRITER_HEAP_BYTES.store(clamped, Ordering::Relaxed);
+    clamped
+}
+
+fn writer_heap_bytes() -> usize {
+    WRITER_HEAP_BYTES.load(Ordering::Relaxed)
+}
+
+pub(crate) fn configure_kind_boost(boost: f64) -> f64 {
+    let clamped = if boost.is_finite() {
+        boost.clamp(0.0, MAX_KIND_BOOST)
+    } else {
+        DEFAULT_KIND_BOOST
+    };
+    KIND_BOOST_BITS.store(clamped.to_bits(), Ordering::Relaxed);
+    clamped
+}
+
+pub(crate) fn file_name_boost(boost: Option<f64>) -> f64 {
+    boost
+        .filter(|value| value.is_finite())
+        .map(|value| value.clamp(1.0, MAX_FILE_NAME_BOOST))
+        .unwrap_or(DEFAULT_FILE_NAME_BOOST)
+}
+
+fn tokenizer_configs() -> &'static Mutex<HashMap<PathBuf, LexicalTokenizerConfig>> {
+    TOKENIZER_CONFIGS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(crate) fn configure_tokenizers(
+    app: &AppHandle,
+    config: LexicalTokenizerConfig,
+) -> CommandResult<LexicalTokenizerConfig> {
+    let grams = [
+        ("prefix", config.prefix_min_gram, config.prefix_max_gram),
+        ("ngram", config.ngram_min_gram, config.ngram_max_gram),
+    ];
+    for (name, min_gram, max_gram) in grams {
+        if min_gram == 0 || min_gram > max_gram || max_gram > MAX_TOKENIZER_GRAM {
+            return Err(format!(
+                "Invalid {name} tokenizer grams {min_gram}..{max_gram}: expected 1 <= min <= max <= {MAX_TOKENIZER_GRAM}."
+            ));
+        }
+    }
+
+    let data_dir = app_data_dir(app)?;
+    let settings_path = data_dir.join(TOKENIZER_SETTINGS_FILE_NAME);
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|error| format!("Could not serialize lexical tokenizer config: {error}"))?;
+    fs::write(&settings_path, json).map_err(|error| {
+        format!(
+            "Could not write lexical tokenizer config '{}': {error}",
+            settings_path.display()
+        )
+    })?;
+
+    // Open runtimes notice the new fingerprint on their next use and rebuild then.
+    tokenizer_configs()
+        .lock()
+        .map_err(|_| "Could not lock lexical tokenizer config".to_string())?
+        .insert(data_dir, config);
+    Ok(config)
+}
+
+pub(crate) fn tokenizer_config(app: &AppHandle) -> CommandResult<LexicalTokenizerConfig> {
+    let data_dir = app_data_dir(app)?;
+    let mut configs = tokenizer_configs()
+        .lock()
+        .map_err(|_| "Could not lock lexical tokenizer config".to_string())?;
+    if let Some(config) = configs.get(&data_dir) {
+        return Ok(*config);
+    }
+    let config = load_tokenizer_config(&data_dir.join(TOKENIZER_SETTINGS_FILE_NAME));
+    configs.insert(data_dir, config);
+    Ok(config)
+}
+
+// Reads the grams saved by an earlier configure call, so a restart keeps them instead of
+// rebuilding the index with the defaults.
+fn load_tokenizer_config(settings_path: &Path) -> LexicalTokenizerConfig {
+    let Ok(raw) = fs::read_to_string(settings_path) else {
+        return DEFAULT_TOKENIZER_CONFIG;
+    };
+    serde_json::from_str(&raw).unwrap_or_else(|error| {
+        logging::warn(&format!(
+            "Ignoring unreadable lexical tokenizer config '{}': {error}",
+            settings_path.display()
+        ));
+        DEFAULT_TOKENIZER_CONFIG
+    })
+}
+
+fn tokenizer_fingerprint(config: &LexicalTokenizerConfig) -> String {
+    format!(
+        "prefix:{}-{};ngram:{}-{};typography:1",
+        config.prefix_min_gram,
+        config.prefix_max_gram,
+        config.ngram_min_gram,
+        config.ngram_max_gram
+    )
+}
+
+fn kind_boost(kind: &str) -> f64 {
+    if kind == "heading" || kind == "file" {
+        f64::from_bits(KIND_BOOST_BITS.load(Ordering::Relaxed))
+    } else {
+        0.0
+    }
+}
 
 fn indexed_text_options(tokenizer: &str) -> TextOptions {
     TextOptions::default().set_indexing_options(
@@ -98,6 +280,8 @@ fn build_schema() -> Schema {
     builder.add_text_field("query_text", indexed_text_options("default"));
     builder.add_text_field("prefix_text", indexed_text_options(PREFIX_TOKENIZER));
     builder.add_text_field("ngram_text", indexed_text_options(NGRAM_TOKENIZER));
+    builder.add_text_field("content_hash", STRING | STORED);
+    builder.add_text_field("argument_type", STRING | STORED);
 
     builder.build()
 }
@@ -107,12 +291,15 @@ fn has_required_fields(schema: &Schema) -> bool {
         && schema.get_field("prefix_text").is_ok()
         && schema.get_field("ngram_text").is_ok()
         && schema.get_field("chunk_preview").is_ok()
+        && schema.get_field("content_hash").is_ok()
+        && schema.get_field("argument_type").is_ok()
 }
 
-fn register_tokenizers(index: &Index) -> CommandResult<()> {
-    let prefix_tokenizer = NgramTokenizer::new(2, 18, true)
-        .map_err(|error| format!("Could not build lexical prefix tokenizer: {error}"))?;
-    let ngram_tokenizer = NgramTokenizer::new(3, 4, false)
+fn register_tokenizers(index: &Index, config: &LexicalTokenizerConfig) -> CommandResult<()> {
+    let prefix_tokenizer =
+        NgramTokenizer::new(config.prefix_min_gram, config.prefix_max_gram, true)
+            .map_err(|error| format!("Could not build lexical prefix tokenizer: {error}"))?;
+    let ngram_tokenizer = NgramTokenizer::new(config.ngram_min_gram, config.ngram_max_gram, false)
         .map_err(|error| format!("Could not build lexical ngram tokenizer: {error}"))?;
 
     index.tokenizers().register(
@@ -153,40 +340,51 @@ fn lexical_fields(schema: &Schema) -> CommandResult<LexicalFields> {
         query_text: field(schema, "query_text")?,
         prefix_text: field(schema, "prefix_text")?,
         ngram_text: field(schema, "ngram_text")?,
+        content_hash: field(schema, "content_hash")?,
+        argument_type: field(schema, "argument_type")?,
     })
 }
 
-fn init_runtime(app: &AppHandle) -> CommandResult<LexicalRuntime> {
+fn init_runtime(
+    path: &Path,
+    config: &LexicalTokenizerConfig,
+) -> CommandResult<(LexicalRuntime, bool)> {
     let schema = build_schema();
-    let path = index_lexical_dir(app)?;
-    fs::create_dir_all(&path).map_err(|error| {
+    fs::create_dir_all(path).map_err(|error| {
         format!(
             "Could not create lexical index directory '{}': {error}",
             path.display()
         )
     })?;
 
-    let recreate = match Index::open_in_dir(&path) {
-        Ok(index) => !has_required_fields(&index.schema()),
-        Err(_) => true,
+    let fingerprint = tokenizer_fingerprint(config);
+    let stored_fingerprint = fs::read_to_string(path.join(TOKENIZER_CONFIG_FILE))
+        .map(|value| value.trim().to_string())
+        .unwrap_or_else(|_| tokenizer_fingerprint(&DEFAULT_TOKENIZER_CONFIG));
+    let (recreate, rebuilt_existing) = match Index::open_in_dir(path) {
+        Ok(index) => {
+            let stale = !has_required_fields(&index.schema()) || stored_fingerprint != fingerprint;
+            (stale, stale)
+        }
+        Err(_) => (true, false),
     };
 
     let index = if recreate {
-        let _ = fs::remove_dir_all(&path);
-        fs::create_dir_all(&path).map_err(|error| {
+        let _ = fs::remove_dir_all(path);
+        fs::create_dir_all(path).map_err(|error| {
             format!(
                 "Could not reset lexical index directory '{}': {error}",
                 path.display()
             )
         })?;
-        Index::create_in_dir(&path, schema.clone())
+        Index::create_in_dir(path, schema.clone())
             .map_err(|error| format!("Could not recreate lexical index: {error}"))?
     } else {
-        Index::open_in_dir(&path)
+        Index::open_in_dir(path)
             .map_err(|error| format!("Could not open lexical index: {error}"))?
     };
 
-    register_tokenizers(&index)?;
+    register_tokenizers(&index, config)?;
     let fields = lexical_fields(&index.schema())?;
     let reader = index
         .reader_builder()
@@ -194,22 +392,70 @@ fn init_runtime(app: &AppHandle) -> CommandResult<LexicalRuntime> {
         .try_into()
         .map_err(|error| format!("Could not build lexical index reader: {error}"))?;
 
-    Ok(LexicalRuntime {
-        index,
-        reader,
-        fields,
-    })
+    Ok((
+        LexicalRuntime {
+            index,
+            reader,
+            fields,
+        },
+        rebuilt_existing,
+    ))
+}
+
+fn lexical_runtimes() -> &'static Mutex<LexicalRuntimeRegistry> {
+    LEXICAL_RUNTIMES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn lexical_runtime(app: &AppHandle) -> CommandResult<Arc<Mutex<LexicalRuntime>>> {
+    let path = index_lexical_dir(app)?;
+    let config = tokenizer_config(app)?;
+    let fingerprint = tokenizer_fingerprint(&config);
+    // The registry stays locked for the whole swap so no caller picks up a half-built index.
+    let mut runtimes = lexical_runtimes()
+        .lock()
+        .map_err(|_| "Could not lock lexical runtime registry".to_string())?;
+    let previous = match runtimes.get(&path) {
+        Some(entry) if entry.fingerprint == fingerprint => return Ok(Arc::clone(&entry.runtime)),
+        Some(entry) => Some(Arc::clone(&entry.runtime)),
+        None => None,
+    };
+    // Waits out searches and writes on the old tokenizers before their directory is replaced.
+    let _previous_guard = previous
+        .as_ref()
+        .map(|runtime| runtime.lock())
+        .transpose()
+        .map_err(|_| "Could not lock lexical runtime".to_string())?;
+
+    let (runtime, rebuilt_existing) = init_runtime(&path, &config)?;
+    if rebuilt_existing {
+        logging::warn("Lexical index layout changed; rebuilding lexical index from database.");
+        let connection = open_database(app)?;
+        write_documents_from_connection(&runtime, &connection, None)?;
+    }
+    // Written last, so a failed rebuild is retried on the next use instead of being trusted.
+    let config_path = path.join(TOKENIZER_CONFIG_FILE);
+    fs::write(&config_path, &fingerprint).map_err(|error| {
+        format!(
+            "Could not write lexical tokenizer config '{}': {error}",
+            config_path.display()
+        )
+    })?;
+
+    let runtime = Arc::new(Mutex::new(runtime));
+    runtimes.insert(
+        path,
+        RegisteredRuntime {
+            fingerprint,
+            runtime: Arc::clone(&runtime),
+        },
+    );
+    Ok(runtime)
 }
 
-fn lexical_runtime(app: &AppHandle) -> CommandResult<&'static Mutex<LexicalRuntime>> {
-    if let Some(runtime) = LEXICAL_RUNTIME.get() {
-        return Ok(runtime);
+pub(crate) fn reset_runtimes() {
+    if let Ok(mut runtimes) = lexical_runtimes().lock() {
+        runtimes.clear();
     }
-    let runtime = init_runtime(app)?;
-    let _ = LEXICAL_RUNTIME.set(Mutex::new(runtime));
-    LEXICAL_RUNTIME
-        .get()
-        .ok_or_else(|| "Could not initialize lexical runtime".to_string())
 }
 
 fn field_text(document: &TantivyDocument, field: Field) -> Option<String> {
@@ -245,6 +491,135 @@ fn ngrams_for_query(normalized_query: &str) -> String {
     ngrams.join(" ")
 }
 
+fn fuzzy_distance(term: &str) -> u8 {
+    if term.chars().count() >= 8 {
+        2
+    } else {
+        1
+    }
+}
+
+fn fuzzy_terms(normalized_query: &str) -> Vec<(String, u8)> {
+    normalized_query
+        .split_whitespace()
+        .filter(|token| token.chars().count() >= FUZZY_MIN_TERM_CHARS)
+        .take(FUZZY_MAX_TERMS)
+        .map(|token| (token.to_string(), fuzzy_distance(token)))
+        .collect()
+}
+
+fn fuzzy_fields(fields: &LexicalFields, file_name_only: bool) -> Vec<Field> {
+    if file_name_only {
+        vec![fields.file_name]
+    } else {
+        vec![fields.heading_text, fields.file_name]
+    }
+}
+
+fn fuzzy_query(
+    fields: &LexicalFields,
+    normalized_query: &str,
+    file_name_only: bool,
+) -> Option<Box<dyn Query>> {
+    let target_fields = fuzzy_fields(fields, file_name_only);
+
+    let clauses = fuzzy_terms(normalized_query)
+        .into_iter()
+        .map(|(token, distance)| {
+            let alternatives = target_fields
+                .iter()
+                .map(|field| {
+                    let term = Term::from_field_text(*field, &token);
+                    let query: Box<dyn Query> = Box::new(FuzzyTermQuery::new(term, distance, true));
+                    (Occur::Should, query)
+                })
+                .collect::<Vec<(Occur, Box<dyn Query>)>>();
+            let query: Box<dyn Query> = Box::new(BooleanQuery::new(alternatives));
+            (Occur::Must, query)
+        })
+        .collect::<Vec<(Occur, Box<dyn Query>)>>();
+
+    if clauses.is_empty() {
+        return None;
+    }
+    Some(Box::new(BooleanQuery::new(clauses)))
+}
+
+fn wildcard_token_patterns(token: &str) -> Option<Vec<String>> {
+    if !token.contains('*') {
+        return Some(
+            normalize_for_search(token)
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        );
+    }
+
+    let parts = token
+        .split('*')
+        .map(normalize_for_search)
+        .collect::<Vec<String>>();
+    let literal_chars = parts.iter().map(|part| part.chars().count()).sum::<usize>();
+    if literal_chars < WILDCARD_MIN_LITERAL_CHARS || parts.iter().any(|part| part.contains(' ')) {
+        return None;
+    }
+    Some(vec![parts.join(".*")])
+}
+
+fn wildcard_patterns(query: &str) -> Vec<String> {
+    let folded = fold_typography(query);
+    let has_inner_wildcard = folded
+        .split_whitespace()
+        .any(|token| token.trim_end_matches('*').contains('*'));
+    if !has_inner_wildcard {
+        return Vec::new();
+    }
+
+    let mut patterns = Vec::new();
+    for token in folded.split_whitespace() {
+        let Some(token_patterns) = wildcard_token_patterns(token) else {
+            return Vec::new();
+        };
+        patterns.extend(token_patterns);
+    }
+    if patterns.len() > WILDCARD_MAX_TERMS {
+        return Vec::new();
+    }
+    patterns
+}
+
+fn wildcard_fields(fields: &LexicalFields, file_name_only: bool) -> Vec<Field> {
+    if file_name_only {
+        vec![fields.file_name]
+    } else {
+        vec![fields.heading_text, fields.query_text]
+    }
+}
+
+fn wildcard_query(
+    fields: &LexicalFields,
+    patterns: &[String],
+    file_name_only: bool,
+) -> Option<Box<dyn Query>> {
+    let target_fields = wildcard_fields(fields, file_name_only);
+
+    let mut clauses = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        let mut alternatives = Vec::with_capacity(target_fields.len());
+        for field in &target_fields {
+            let query: Box<dyn Query> = Box::new(RegexQuery::from_pattern(pattern, *field).ok()?);
+            alternatives.push((Occur::Should, query));
+        }
+        let query: Box<dyn Query> = Box::new(BooleanQuery::new(alternatives));
+        clauses.push((Occur::Must, query));
+    }
+
+    if clauses.is_empty() {
+        return None;
+    }
+    Some(Box::new(BooleanQuery::new(clauses)))
+}
+
 fn dedupe_key(hit: &SearchHit) -> String {
     format!(
         "{}:{}:{}:{}:{}",
@@ -256,6 +631,16 @@ fn dedupe_key(hit: &SearchHit) -> String {
     )
 }
 
+fn is_new_hit(seen: &mut HashSet<String>, hit: &SearchHit) -> bool {
+    let new_content = hit
+        .content_hash
+        .as_ref()
+        .map(|hash| seen.insert(format!("hash:{hash}")))
+        .unwrap_or(true);
+    let new_key = seen.insert(dedupe_key(hit));
+    new_content && new_key
+}
+
 fn build_hit(
     document: &TantivyDocument,
     fields: &LexicalFields,
@@ -282,6 +667,10 @@ fn build_hit(
         "author".to_string()
     } else if kind == "file" {
         "file".to_string()
+    } else if kind == "paragraph" {
+        "paragraph".to_string()
+    } else if kind == "comment" {
+        "comment".to_string()
     } else {
         "heading".to_string()
     };
@@ -296,7 +685,11 @@ fn build_hit(
         heading_level,
         heading_text,
         heading_order,
-        score,
+        score: score - kind_boost(&kind),
+        explanation: None,
+        content_hash: field_text(document, fields.content_hash),
+        heading_path: None,
+        argument_type: field_text(document, fields.argument_type),
     })
 }
 
@@ -323,18 +716,18 @@ fn add_document_to_writer(
     let author_text = entry.author_text.clone().unwrap_or_default();
     let chunk_text = entry.chunk_text.clone().unwrap_or_default();
     let chunk_preview = preview_text_for_chunk(&chunk_text);
-    let query_text = format!(
+    let query_text = fold_typography(&format!(
         "{}\n{}\n{}\n{}",
         heading_text, author_text, entry.file_name, entry.relative_path
-    );
-    let prefix_text = format!(
+    ));
+    let prefix_text = fold_typography(&format!(
         "{} {} {} {}",
         heading_text, author_text, entry.file_name, entry.relative_path
-    );
-    let ngram_text = format!(
+    ));
+    let ngram_text = fold_typography(&format!(
         "{} {} {} {} {}",
         heading_text, author_text, chunk_preview, entry.file_name, entry.relative_path
-    );
+    ));
 
     let mut document = doc!(
         fields.kind => entry.kind.as_str(),
@@ -361,9 +754,15 @@ fn add_document_to_writer(
         document.add_text(fields.author_text, author_text);
     }
     if !chunk_text.is_empty() {
-        document.add_text(fields.chunk_text, chunk_text);
+        document.add_text(fields.chunk_text, fold_typography(&chunk_text));
         document.add_text(fields.chunk_preview, chunk_preview);
     }
+    if let Some(content_hash) = entry.content_hash.as_deref() {
+        document.add_text(fields.content_hash, content_hash);
+    }
+    if let Some(argument_type) = entry.argument_type.as_deref() {
+        document.add_text(fields.argument_type, argument_type);
+    }
 
     writer.add_document(document).map_err(|error| {
         format!(
@@ -377,47 +776,94 @@ fn add_document_to_writer(
 pub(crate) fn replace_all_documents_from_connection(
     app: &AppHandle,
     connection: &Connection,
-) -> CommandResult<()> {
+) -> CommandResult<usize> {
+    replace_documents_from_connection(app, connection, None)
+}
+
+pub(crate) fn replace_file_documents_from_connection(
+    app: &AppHandle,
+    connection: &Connection,
+    file_id: i64,
+) -> CommandResult<usize> {
+    replace_documents_from_connection(app, connection, Some(file_id))
+}
+
+// With a file id only that file's documents are swapped; otherwise the whole index is rebuilt.
+fn replace_documents_from_connection(
+    app: &AppHandle,
+    connection: &Connection,
+    file_id: Option<i64>,
+) -> CommandResult<usize> {
     let runtime = lexical_runtime(app)?;
     let runtime = runtime
         .lock()
         .map_err(|_| "Could not lock lexical runtime".to_string())?;
+    write_documents_from_connection(&runtime, connection, file_id)
+}
 
+fn write_documents_from_connection(
+    runtime: &LexicalRuntime,
+    connection: &Connection,
+    file_id: Option<i64>,
+) -> CommandResult<usize> {
     let mut writer = runtime
         .index
-        .writer(256_000_000)
+        .writer(writer_heap_bytes())
         .map_err(|error| format!("Could not create lexical index writer: {error}"))?;
 
-    writer
-        .delete_all_documents()
-        .map_err(|error| format!("Could not clear lexical index: {error}"))?;
+    match file_id {
+        Some(file_id) => {
+            writer.delete_term(Term::from_field_u64(
+                runtime.fields.file_id,
+                u64::try_from(file_id).unwrap_or(0),
+            ));
+        }
+        None => {
+            writer
+                .delete_all_documents()
+                .map_err(|error| format!("Could not clear lexical index: {error}"))?;
+        }
+    }
+    let mut document_count = 0_usize;
 
     {
         let mut statement = connection
             .prepare(
                 "
-                SELECT root_id, id, relative_path, absolute_path
+                SELECT root_id, id, relative_path, absolute_path, doc_title, doc_creator, doc_keywords
                 FROM files
+                WHERE ?1 IS NULL OR id = ?1
                 ORDER BY root_id ASC, relative_path ASC
                 ",
             )
             .map_err(|error| format!("Could not prepare lexical file rows query: {error}"))?;
 
         let rows = statement
-            .query_map([], |row| {
+            .query_map(params![file_id], |row| {
                 Ok((
                     row.get::<_, i64>(0)?,
                     row.get::<_, i64>(1)?,
                     row.get::<_, String>(2)?,
                     row.get::<_, String>(3)?,
+                    [
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                        row.get::<_, Option<String>>(6)?,
+                    ],
                 ))
             })
             .map_err(|error| format!("Could not read lexical file rows: {error}"))?;
 
         for row in rows {
-            let (root_id, file_id, relative_path, absolute_path) =
+            let (root_id, file_id, relative_path, absolute_path, properties) =
                 row.map_err(|error| format!("Could not parse lexical file row: {error}"))?;
             let file_name = crate::util::file_name_from_relative(&relative_path);
+            // Word title/author/keywords ride along as the file document's body text.
+            let properties_text = properties
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join("\n");
             let entry = LexicalDocument {
                 root_id,
                 file_id,
@@ -429,9 +875,12 @@ pub(crate) fn replace_all_documents_from_connection(
                 heading_text: None,
                 heading_order: None,
                 author_text: None,
-                chunk_text: None,
+                chunk_text: Some(properties_text).filter(|text| !text.is_empty()),
+                content_hash: None,
+                argument_type: None,
             };
             add_document_to_writer(&mut writer, &runtime.fields, &entry)?;
+            document_count += 1;
         }
     }
 
@@ -446,16 +895,18 @@ pub(crate) fn replace_all_documents_from_connection(
                   f.absolute_path,
                   h.level,
                   h.text,
-                  h.heading_order
+                  h.heading_order,
+                  h.argument_type
                 FROM headings h
                 JOIN files f ON f.id = h.file_id
+                WHERE ?1 IS NULL OR f.id = ?1
                 ORDER BY f.root_id ASC, f.id ASC, h.heading_order ASC
                 ",
             )
             .map_err(|error| format!("Could not prepare lexical heading rows query: {error}"))?;
 
         let rows = statement
-            .query_map([], |row| {
+            .query_map(params![file_id], |row| {
                 Ok((
                     row.get::<_, i64>(0)?,
                     row.get::<_, i64>(1)?,
@@ -464,6 +915,7 @@ pub(crate) fn replace_all_documents_from_connection(
                     row.get::<_, i64>(4)?,
                     row.get::<_, String>(5)?,
                     row.get::<_, i64>(6)?,
+                    row.get::<_, Option<String>>(7)?,
                 ))
             })
             .map_err(|error| format!("Could not read lexical heading rows: {error}"))?;
@@ -477,6 +929,7 @@ pub(crate) fn replace_all_documents_from_connection(
                 level,
                 heading_text,
                 heading_order,
+                argument_type,
             ) = row.map_err(|error| format!("Could not parse lexical heading row: {error}"))?;
             let file_name = crate::util::file_name_from_relative(&relative_path);
             let entry = LexicalDocument {
@@ -491,8 +944,11 @@ pub(crate) fn replace_all_documents_from_connection(
                 heading_order: Some(heading_order),
                 author_text: None,
                 chunk_text: None,
+                content_hash: None,
+                argument_type,
             };
             add_document_to_writer(&mut writer, &runtime.fields, &entry)?;
+            document_count += 1;
         }
     }
 
@@ -509,13 +965,14 @@ pub(crate) fn replace_all_documents_from_connection(
                   a.author_order
                 FROM authors a
                 JOIN files f ON f.id = a.file_id
+                WHERE ?1 IS NULL OR f.id = ?1
                 ORDER BY f.root_id ASC, f.id ASC, a.author_order ASC
                 ",
             )
             .map_err(|error| format!("Could not prepare lexical author rows query: {error}"))?;
 
         let rows = statement
-            .query_map([], |row| {
+            .query_map(params![file_id], |row| {
                 Ok((
                     row.get::<_, i64>(0)?,
                     row.get::<_, i64>(1)?,
@@ -543,8 +1000,11 @@ pub(crate) fn replace_all_documents_from_connection(
                 heading_order: Some(author_order),
                 author_text: Some(author_text),
                 chunk_text: None,
+                content_hash: None,
+                argument_type: None,
             };
             add_document_to_writer(&mut writer, &runtime.fields, &entry)?;
+            document_count += 1;
         }
     }
 
@@ -561,15 +1021,19 @@ pub(crate) fn replace_all_documents_from_connection(
                   heading_text,
                   heading_order,
                   author_text,
-                  chunk_text
+                  chunk_text,
+                  kind,
+                  content_hash,
+                  argument_type
                 FROM chunks
+                WHERE ?1 IS NULL OR file_id = ?1
                 ORDER BY root_id ASC, file_id ASC, chunk_order ASC
                 ",
             )
             .map_err(|error| format!("Could not prepare lexical chunk rows query: {error}"))?;
 
         let rows = statement
-            .query_map([], |row| {
+            .query_map(params![file_id], |row| {
                 Ok((
                     row.get::<_, i64>(0)?,
                     row.get::<_, i64>(1)?,
@@ -580,6 +1044,9 @@ pub(crate) fn replace_all_documents_from_connection(
                     row.get::<_, Option<i64>>(6)?,
                     row.get::<_, Option<String>>(7)?,
                     row.get::<_, String>(8)?,
+                    row.get::<_, String>(9)?,
+                    row.get::<_, String>(10)?,
+                    row.get::<_, Option<String>>(11)?,
                 ))
             })
             .map_err(|error| format!("Could not read lexical chunk rows: {error}"))?;
@@ -595,6 +1062,9 @@ pub(crate) fn replace_all_documents_from_connection(
                 heading_order,
                 author_text,
                 chunk_text,
+                chunk_kind,
+                content_hash,
+                argument_type,
             ) = row.map_err(|error| format!("Could not parse lexical chunk row: {error}"))?;
 
             if chunk_text.trim().is_empty() {
@@ -605,7 +1075,7 @@ pub(crate) fn replace_all_documents_from_connection(
             let entry = LexicalDocument {
                 root_id,
                 file_id,
-                kind: "chunk".to_string(),
+                kind: chunk_kind,
                 file_name,
                 relative_path,
                 absolute_path,
@@ -614,8 +1084,11 @@ pub(crate) fn replace_all_documents_from_connection(
                 heading_order,
                 author_text,
                 chunk_text: Some(chunk_text),
+                content_hash: Some(content_hash).filter(|hash| !hash.is_empty()),
+                argument_type,
             };
             add_document_to_writer(&mut writer, &runtime.fields, &entry)?;
+            document_count += 1;
         }
     }
 
@@ -627,15 +1100,13 @@ pub(crate) fn replace_all_documents_from_connection(
         .reload()
         .map_err(|error| format!("Could not reload lexical reader: {error}"))?;
 
-    Ok(())
+    Ok(document_count)
 }
 
 pub(crate) fn search(
     app: &AppHandle,
     query: &str,
-    requested_root_id: Option<i64>,
-    limit: usize,
-    file_name_only: bool,
+    options: &LexicalSearchOptions,
 ) -> CommandResult<Vec<SearchHit>> {
     let started = Instant::now();
     let normalized = normalize_for_search(query);
@@ -647,120 +1118,412 @@ pub(crate) fn search(
     let runtime = runtime
         .lock()
         .map_err(|_| "Could not lock lexical runtime".to_string())?;
-    let searcher = runtime.reader.searcher();
+    let mut results = search_runtime(&runtime, &normalized, &wildcard_patterns(query), options)?;
+    apply_file_name_boost(&mut results, &normalized, options.file_name_boost);
 
-    let target_limit = limit.clamp(10, 400);
-    let fetch_limit = target_limit
-        .saturating_mul(MIN_FETCH_MULTIPLIER)
-        .clamp(MIN_FETCH_FLOOR, MAX_FETCH_LIMIT);
-    let mut results = Vec::new();
-    let mut seen = HashSet::new();
+    if started.elapsed().as_millis() > 80 {
+        logging::warn(&format!(
+            "Lexical search exceeded 80ms budget: {}ms query='{}'",
+            started.elapsed().as_millis(),
+            normalized
+        ));
+    }
+
+    Ok(results)
+}
+
+fn compare_hits(left: &SearchHit, right: &SearchHit) -> std::cmp::Ordering {
+    left.score
+        .partial_cmp(&right.score)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then(left.relative_path.cmp(&right.relative_path))
+        .then(
+            left.heading_order
+                .unwrap_or(0)
+                .cmp(&right.heading_order.unwrap_or(0)),
+        )
+        .then(left.kind.cmp(&right.kind))
+}
 
-    let lexical_fields = if file_name_only {
-        vec![runtime.fields.file_name]
+fn file_name_matches(hit: &SearchHit, normalized: &str) -> bool {
+    if hit.kind == "file" {
+        return true;
+    }
+    let file_name = normalize_for_search(&hit.file_name);
+    normalized
+        .split_whitespace()
+        .all(|token| file_name.contains(token))
+}
+
+fn apply_file_name_boost(hits: &mut [SearchHit], normalized: &str, boost: f64) {
+    if boost <= DEFAULT_FILE_NAME_BOOST {
+        return;
+    }
+    for hit in hits.iter_mut() {
+        if file_name_matches(hit, normalized) {
+            hit.score /= boost;
+        }
+    }
+    hits.sort_by(compare_hits);
+}
+
+fn exact_tier_fields(fields: &LexicalFields, file_name_only: bool) -> Vec<Field> {
+    if file_name_only {
+        vec![fields.file_name]
     } else {
         vec![
-            runtime.fields.query_text,
-            runtime.fields.heading_text,
-            runtime.fields.author_text,
-            runtime.fields.file_name,
-            runtime.fields.relative_path,
-            runtime.fields.chunk_text,
+            fields.query_text,
+            fields.heading_text,
+            fields.author_text,
+            fields.file_name,
+            fields.relative_path,
+            fields.chunk_text,
         ]
-    };
-    let prefix_fields = if file_name_only {
-        vec![runtime.fields.file_name]
+    }
+}
+
+fn prefix_tier_fields(fields: &LexicalFields, file_name_only: bool) -> Vec<Field> {
+    if file_name_only {
+        vec![fields.file_name]
     } else {
         vec![
-            runtime.fields.prefix_text,
-            runtime.fields.heading_text,
-            runtime.fields.file_name,
-            runtime.fields.relative_path,
+            fields.prefix_text,
+            fields.heading_text,
+            fields.file_name,
+            fields.relative_path,
         ]
+    }
+}
+
+fn prefix_query_text(normalized: &str) -> String {
+    normalized
+        .split_whitespace()
+        .map(|token| format!("{token}*"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn file_id_query(fields: &LexicalFields, file_id: i64) -> Option<Box<dyn Query>> {
+    let file_id = u64::try_from(file_id).ok()?;
+    let term = Term::from_field_u64(fields.file_id, file_id);
+    Some(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+}
+
+fn filtered_query(
+    fields: &LexicalFields,
+    parsed: Box<dyn Query>,
+    requested_root_ids: &[i64],
+    argument_type: Option<&str>,
+    file_scope: Option<&FileScope>,
+) -> Option<Box<dyn Query>> {
+    if requested_root_ids.is_empty() && argument_type.is_none() && file_scope.is_none() {
+        return Some(parsed);
+    }
+    let mut clauses = vec![(Occur::Must, parsed)];
+    if let Some(argument_type) = argument_type {
+        let type_term = Term::from_field_text(fields.argument_type, argument_type);
+        let type_query: Box<dyn Query> =
+            Box::new(TermQuery::new(type_term, IndexRecordOption::Basic));
+        clauses.push((Occur::Must, type_query));
+    }
+    if let Some(file_scope) = file_scope {
+        let file_queries = file_scope
+            .file_ids
+            .iter()
+            .filter_map(|file_id| file_id_query(fields, *file_id));
+        if file_scope.include {
+            let file_clauses = file_queries
+                .map(|query| (Occur::Should, query))
+                .collect::<Vec<(Occur, Box<dyn Query>)>>();
+            if file_clauses.is_empty() {
+                return None;
+            }
+            let file_query: Box<dyn Query> = Box::new(BooleanQuery::new(file_clauses));
+            clauses.push((Occur::Must, file_query));
+        } else {
+            clauses.extend(file_queries.map(|query| (Occur::MustNot, query)));
+        }
+    }
+    if requested_root_ids.is_empty() {
+        return Some(Box::new(BooleanQuery::new(clauses)));
+    }
+    let root_clauses = requested_root_ids
+        .iter()
+        .filter_map(|root_id| u64::try_from(*root_id).ok())
+        .map(|root_id| {
+            let root_term = Term::from_field_u64(fields.root_id, root_id);
+            let term_query: Box<dyn Query> =
+                Box::new(TermQuery::new(root_term, IndexRecordOption::Basic));
+            (Occur::Should, term_query)
+        })
+        .collect::<Vec<(Occur, Box<dyn Query>)>>();
+    if root_clauses.is_empty() {
+        return None;
+    }
+    let root_query: Box<dyn Query> = Box::new(BooleanQuery::new(root_clauses));
+    clauses.push((Occur::Must, root_query));
+    Some(Box::new(BooleanQuery::new(clauses)))
+}
+
+pub(crate) fn count_matches(
+    app: &AppHandle,
+    query: &str,
+    requested_root_ids: &[i64],
+) -> CommandResult<usize> {
+    let normalized = normalize_for_search(query);
+    if normalized.is_empty() {
+        return Ok(0);
+    }
+
+    let runtime = lexical_runtime(app)?;
+    let runtime = runtime
+        .lock()
+        .map_err(|_| "Could not lock lexical runtime".to_string())?;
+    count_runtime(&runtime, &normalized, requested_root_ids)
+}
+
+pub(crate) fn search_exact_term(
+    app: &AppHandle,
+    term: &str,
+    options: &LexicalSearchOptions,
+) -> CommandResult<Vec<SearchHit>> {
+    let runtime = lexical_runtime(app)?;
+    let runtime = runtime
+        .lock()
+        .map_err(|_| "Could not lock lexical runtime".to_string())?;
+    exact_term_runtime(&runtime, term, options)
+}
+
+fn exact_term_runtime(
+    runtime: &LexicalRuntime,
+    term: &str,
+    options: &LexicalSearchOptions,
+) -> CommandResult<Vec<SearchHit>> {
+    let file_name_only = options.file_name_only;
+    let fields = fuzzy_fields(&runtime.fields, file_name_only);
+    let clauses = fields
+        .iter()
+        .map(|field| {
+            let term = Term::from_field_text(*field, term);
+            let query: Box<dyn Query> =
+                Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+            (Occur::Should, query)
+        })
+        .collect::<Vec<(Occur, Box<dyn Query>)>>();
+    let combined: Box<dyn Query> = Box::new(BooleanQuery::new(clauses));
+    let Some(query) = filtered_query(
+        &runtime.fields,
+        combined,
+        &options.requested_root_ids,
+        options.argument_type.as_deref(),
+        options.file_scope.as_ref(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let target_limit = options.limit.clamp(10, 400);
+    let searcher = runtime.reader.searcher();
+    let docs = searcher
+        .search(&query, &TopDocs::with_limit(target_limit))
+        .map_err(|error| format!("Lexical term search failed: {error}"))?;
+    let schema = runtime.index.schema();
+    let field_names = fields
+        .iter()
+        .map(|field| schema.get_field_name(*field))
+        .collect::<Vec<&str>>()
+        .join(",");
+
+    let mut results = Vec::with_capacity(docs.len());
+    let mut seen = HashSet::new();
+    for (rank, (raw_score, address)) in docs.into_iter().enumerate() {
+        let document = searcher
+            .doc::<TantivyDocument>(address)
+            .map_err(|error| format!("Could not read lexical result document: {error}"))?;
+        let score = 1_000.0 + f64::from(rank as u32);
+        let Some(mut hit) = build_hit(&document, &runtime.fields, score, file_name_only) else {
+            continue;
+        };
+        if options.explain {
+            hit.explanation = Some(format!(
+                "tier=term fields={field_names} rank={rank} tantivy_score={raw_score:.4}"
+            ));
+        }
+        if is_new_hit(&mut seen, &hit) {
+            results.push(hit);
+        }
+    }
+
+    results.sort_by(compare_hits);
+    Ok(results)
+}
+
+fn count_runtime(
+    runtime: &LexicalRuntime,
+    normalized: &str,
+    requested_root_ids: &[i64],
+) -> CommandResult<usize> {
+    let mut clauses = Vec::new();
+    for (query_text, fields) in [
+        (
+            normalized.to_string(),
+            exact_tier_fields(&runtime.fields, false),
+        ),
+        (
+            prefix_query_text(normalized),
+            prefix_tier_fields(&runtime.fields, false),
+        ),
+    ] {
+        let mut parser = QueryParser::for_index(&runtime.index, fields);
+        parser.set_conjunction_by_default();
+        if let Ok(parsed) = parser.parse_query(&query_text) {
+            clauses.push((Occur::Should, parsed));
+        }
+    }
+    if clauses.is_empty() {
+        return Ok(0);
+    }
+
+    let combined: Box<dyn Query> = Box::new(BooleanQuery::new(clauses));
+    let Some(query) = filtered_query(&runtime.fields, combined, requested_root_ids, None, None)
+    else {
+        return Ok(0);
     };
+    runtime
+        .reader
+        .searcher()
+        .search(&query, &Count)
+        .map_err(|error| format!("Lexical count execution failed: {error}"))
+}
+
+fn search_runtime(
+    runtime: &LexicalRuntime,
+    normalized: &str,
+    wildcard_patterns: &[String],
+    options: &LexicalSearchOptions,
+) -> CommandResult<Vec<SearchHit>> {
+    let requested_root_ids = options.requested_root_ids.as_slice();
+    let argument_type = options.argument_type.as_deref();
+    let file_scope = options.file_scope.as_ref();
+    let limit = options.limit;
+    let file_name_only = options.file_name_only;
+    let explain = options.explain;
+    let searcher = runtime.reader.searcher();
+
+    let target_limit = limit.clamp(10, 400);
+    let fetch_limit = target_limit
+        .saturating_mul(MIN_FETCH_MULTIPLIER)
+        .clamp(MIN_FETCH_FLOOR, MAX_FETCH_LIMIT);
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+
+    let lexical_fields = exact_tier_fields(&runtime.fields, file_name_only);
+    let prefix_fields = prefix_tier_fields(&runtime.fields, file_name_only);
     let ngram_fields = if file_name_only {
         Vec::new()
     } else {
         vec![runtime.fields.ngram_text]
     };
 
-    let run_tier = |query_text: &str,
-                    fields: Vec<Field>,
-                    conjunction: bool|
-     -> CommandResult<Vec<TantivyDocument>> {
-        let mut parser = QueryParser::for_index(&runtime.index, fields);
-        if conjunction {
-            parser.set_conjunction_by_default();
-        }
-        let parsed = match parser.parse_query(query_text) {
-            Ok(parsed) => parsed,
-            Err(_) => return Ok(Vec::new()),
-        };
-        let query: Box<dyn Query> = if let Some(root_id) = requested_root_id {
-            let Ok(root_id_u64) = u64::try_from(root_id) else {
-                return Ok(Vec::new());
-            };
-            let root_term = Term::from_field_u64(runtime.fields.root_id, root_id_u64);
-            let root_query: Box<dyn Query> =
-                Box::new(TermQuery::new(root_term, IndexRecordOption::Basic));
-            Box::new(BooleanQuery::new(vec![
-                (Occur::Must, parsed),
-                (Occur::Must, root_query),
-            ]))
-        } else {
-            parsed
+    let run_query = |parsed: Box<dyn Query>| -> CommandResult<Vec<(f32, TantivyDocument)>> {
+        let Some(query) = filtered_query(
+            &runtime.fields,
+            parsed,
+            requested_root_ids,
+            argument_type,
+            file_scope,
+        ) else {
+            return Ok(Vec::new());
         };
 
         let docs = searcher
             .search(&query, &TopDocs::with_limit(fetch_limit))
             .map_err(|error| format!("Lexical search execution failed: {error}"))?;
         let mut output = Vec::with_capacity(docs.len());
-        for (_score, address) in docs {
+        for (score, address) in docs {
             let doc = searcher
                 .doc::<TantivyDocument>(address)
                 .map_err(|error| format!("Could not read lexical result document: {error}"))?;
-            output.push(doc);
+            output.push((score, doc));
         }
         Ok(output)
     };
 
+    let parse_tier =
+        |query_text: &str, fields: &[Field], conjunction: bool| -> Option<Box<dyn Query>> {
+            if query_text.trim().is_empty() {
+                return None;
+            }
+            let mut parser = QueryParser::for_index(&runtime.index, fields.to_vec());
+            if conjunction {
+                parser.set_conjunction_by_default();
+            }
+            parser.parse_query(query_text).ok()
+        };
+
+    let explain_hit =
+        |hit: &mut SearchHit, tier: &str, fields: &[Field], rank: usize, score: f32| {
+            if !explain {
+                return;
+            }
+            let schema = runtime.index.schema();
+            let field_names = fields
+                .iter()
+                .map(|field| schema.get_field_name(*field))
+                .collect::<Vec<&str>>()
+                .join(",");
+            hit.explanation = Some(format!(
+                "tier={tier} fields={field_names} rank={rank} tantivy_score={score:.4}"
+            ));
+        };
+
     let mut tiers = vec![
-        (normalized.clone(), lexical_fields, true, 1_000.0_f64),
         (
-            normalized
-                .split_whitespace()
-                .map(|token| format!("{token}*"))
-                .collect::<Vec<String>>()
-                .join(" "),
+            "exact",
+            parse_tier(normalized, &lexical_fields, true),
+            lexical_fields,
+            1_000.0_f64,
+        ),
+        (
+            "prefix",
+            parse_tier(&prefix_query_text(normalized), &prefix_fields, true),
             prefix_fields,
-            true,
             2_000.0_f64,
         ),
     ];
+    // Leading and infix wildcards are regex scans, so they only run when the
+    // exact and prefix tiers leave room in the result set.
+    if !wildcard_patterns.is_empty() {
+        tiers.push((
+            "wildcard",
+            wildcard_query(&runtime.fields, wildcard_patterns, file_name_only),
+            wildcard_fields(&runtime.fields, file_name_only),
+            2_500.0_f64,
+        ));
+    }
     if !ngram_fields.is_empty() {
         tiers.push((
-            ngrams_for_query(&normalized),
+            "ngram",
+            parse_tier(&ngrams_for_query(normalized), &ngram_fields, false),
             ngram_fields,
-            false,
             3_000.0_f64,
         ));
     }
 
-    for (query_text, fields, conjunction, score_base) in tiers {
-        if query_text.trim().is_empty() {
+    for (tier, query, fields, score_base) in tiers {
+        let Some(query) = query else {
             continue;
-        }
-        let tier_documents = run_tier(&query_text, fields, conjunction)?;
-        for (rank, document) in tier_documents.into_iter().enumerate() {
+        };
+        let tier_documents = run_query(query)?;
+        for (rank, (raw_score, document)) in tier_documents.into_iter().enumerate() {
             if results.len() >= target_limit {
                 break;
             }
             let score = score_base + f64::from(rank as u32);
-            let Some(hit) = build_hit(&document, &runtime.fields, score, file_name_only) else {
+            let Some(mut hit) = build_hit(&document, &runtime.fields, score, file_name_only) else {
                 continue;
             };
-            let key = dedupe_key(&hit);
-            if !seen.insert(key) {
+            explain_hit(&mut hit, tier, &fields, rank, raw_score);
+            if !is_new_hit(&mut seen, &hit) {
                 continue;
             }
             results.push(hit);
@@ -770,13 +1533,558 @@ pub(crate) fn search(
         }
     }
 
-    if started.elapsed().as_millis() > 80 {
-        eprintln!(
-            "Lexical search exceeded 80ms budget: {}ms query='{}'",
-            started.elapsed().as_millis(),
-            normalized
-        );
+    if results.len() < FUZZY_TRIGGER_RESULTS {
+        if let Some(query) = fuzzy_query(&runtime.fields, normalized, file_name_only) {
+            let target_fields = fuzzy_fields(&runtime.fields, file_name_only);
+            for (rank, (raw_score, document)) in run_query(query)?.into_iter().enumerate() {
+                if results.len() >= target_limit {
+                    break;
+                }
+                let score = 4_000.0 + f64::from(rank as u32);
+                let Some(mut hit) = build_hit(&document, &runtime.fields, score, file_name_only)
+                else {
+                    continue;
+                };
+                explain_hit(&mut hit, "fuzzy", &target_fields, rank, raw_score);
+                if is_new_hit(&mut seen, &hit) {
+                    results.push(hit);
+                }
+            }
+        }
     }
 
+    results.sort_by(compare_hits);
+    results.truncate(target_limit);
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_app;
+    use crate::util::chunk_content_hash;
+
+    #[test]
+    fn fuzzy_tier_matches_one_edit_heading_typo() {
+        let index = Index::create_in_ram(build_schema());
+        register_tokenizers(&index, &DEFAULT_TOKENIZER_CONFIG).unwrap();
+        let fields = lexical_fields(&index.schema()).unwrap();
+
+        let mut writer = index.writer(15_000_000).unwrap();
+        for (order, heading) in ["Solvency crisis advantage", "Tariff spillover"]
+            .into_iter()
+            .enumerate()
+        {
+            let entry = LexicalDocument {
+                root_id: 1,
+                file_id: 1,
+                kind: "heading".to_string(),
+                file_name: "Economy.docx".to_string(),
+                relative_path: "Economy.docx".to_string(),
+                absolute_path: "/tmp/Economy.docx".to_string(),
+                heading_level: Some(1),
+                heading_text: Some(heading.to_string()),
+                heading_order: Some(order as i64),
+                author_text: None,
+                chunk_text: None,
+                content_hash: None,
+                argument_type: None,
+            };
+            add_document_to_writer(&mut writer, &fields, &entry).unwrap();
+        }
+        writer.commit().unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        let query = fuzzy_query(&fields, "solvancy", false).unwrap();
+        let docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(docs.len(), 1);
+
+        let document = searcher.doc::<TantivyDocument>(docs[0].1).unwrap();
+        let hit = build_hit(&document, &fields, 4_000.0, false).unwrap();
+        assert_eq!(hit.kind, "heading");
+        assert_eq!(
+            hit.heading_text.as_deref(),
+            Some("Solvency crisis advantage")
+        );
+    }
+
+    fn heading_document(
+        file_id: i64,
+        relative_path: &str,
+        order: i64,
+        text: &str,
+    ) -> LexicalDocument {
+        LexicalDocument {
+            root_id: 1,
+            file_id,
+            kind: "heading".to_string(),
+            file_name: relative_path.to_string(),
+            relative_path: relative_path.to_string(),
+            absolute_path: format!("/debate/{relative_path}"),
+            heading_level: Some(2),
+            heading_text: Some(text.to_string()),
+            heading_order: Some(order),
+            author_text: None,
+            chunk_text: None,
+            content_hash: None,
+            argument_type: None,
+        }
+    }
+
+    fn runtime_with(entries: &[LexicalDocument]) -> LexicalRuntime {
+        let index = Index::create_in_ram(build_schema());
+        register_tokenizers(&index, &DEFAULT_TOKENIZER_CONFIG).unwrap();
+        let fields = lexical_fields(&index.schema()).unwrap();
+        let mut writer = index.writer(15_000_000).unwrap();
+        for entry in entries {
+            add_document_to_writer(&mut writer, &fields, entry).unwrap();
+        }
+        writer.commit().unwrap();
+        let reader = index.reader().unwrap();
+        LexicalRuntime {
+            index,
+            reader,
+            fields,
+        }
+    }
+
+    #[test]
+    fn repeated_search_returns_identical_order() {
+        let runtime = runtime_with(&[
+            heading_document(3, "Neg/Politics DA.docx", 4, "Midterms link"),
+            heading_document(1, "Aff/Plan.docx", 2, "Midterms impact"),
+            heading_document(2, "Aff/Solvency.docx", 7, "Midterms turn"),
+            heading_document(1, "Aff/Plan.docx", 9, "Midterms uniqueness"),
+        ]);
+
+        let first = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        let second = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        let order = |hits: &[SearchHit]| {
+            hits.iter()
+                .map(|hit| (hit.relative_path.clone(), hit.heading_order, hit.score))
+                .collect::<Vec<(String, Option<i64>, f64)>>()
+        };
+
+        assert_eq!(first.len(), 4);
+        assert_eq!(order(&first), order(&second));
+        assert!(first
+            .windows(2)
+            .all(|pair| compare_hits(&pair[0], &pair[1]) != std::cmp::Ordering::Greater));
+    }
+
+    #[test]
+    fn argument_type_filter_excludes_other_types() {
+        let mut card = heading_document(1, "Aff/Plan.docx", 2, "Midterms impact");
+        card.argument_type = Some("card".to_string());
+        let mut analytic = heading_document(2, "Neg/Politics DA.docx", 4, "Midterms overview");
+        analytic.argument_type = Some("analytic".to_string());
+        let runtime = runtime_with(&[card, analytic]);
+
+        let both = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        assert_eq!(both.len(), 2);
+
+        let cards = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions {
+                argument_type: Some("card".to_string()),
+                ..LexicalSearchOptions::new(&[], 20)
+            },
+        )
+        .unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].relative_path, "Aff/Plan.docx");
+        assert_eq!(cards[0].argument_type.as_deref(), Some("card"));
+    }
+
+    #[test]
+    fn writer_heap_is_clamped_to_a_usable_size() {
+        assert_eq!(clamp_writer_heap_bytes(64_000_000), 64_000_000);
+        assert_eq!(clamp_writer_heap_bytes(1_000), MIN_WRITER_HEAP_BYTES);
+        assert_eq!(clamp_writer_heap_bytes(usize::MAX), MAX_WRITER_HEAP_BYTES);
+        let index = Index::create_in_ram(build_schema());
+        assert!(index
+            .writer::<TantivyDocument>(clamp_writer_heap_bytes(1_000))
+            .is_ok());
+    }
+
+    #[test]
+    fn fuzzy_terms_skip_short_tokens_and_scale_distance() {
+        assert_eq!(
+            fuzzy_terms("the solvancy counterplann"),
+            vec![("solvancy".to_string(), 2), ("counterplann".to_string(), 2)]
+        );
+        assert_eq!(fuzzy_terms("econ da"), vec![("econ".to_string(), 1)]);
+    }
+
+    #[test]
+    fn explain_mode_reports_tier_and_raw_score() {
+        let runtime = runtime_with(&[
+            heading_document(1, "Aff/Plan.docx", 2, "Midterms impact"),
+            heading_document(2, "Neg/Politics DA.docx", 4, "Solvency turns"),
+        ]);
+
+        let plain = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        assert!(plain.iter().all(|hit| hit.explanation.is_none()));
+
+        let explained = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions {
+                explain: true,
+                ..LexicalSearchOptions::new(&[], 20)
+            },
+        )
+        .unwrap();
+        assert!(!explained.is_empty());
+        let explanation = explained[0].explanation.as_deref().unwrap();
+        assert!(explanation.starts_with("tier=exact fields="));
+        assert!(explanation.contains("heading_text"));
+        assert!(explanation.contains("tantivy_score="));
+
+        let prefix = search_runtime(
+            &runtime,
+            "midt",
+            &[],
+            &LexicalSearchOptions {
+                explain: true,
+                ..LexicalSearchOptions::new(&[], 20)
+            },
+        )
+        .unwrap();
+        assert!(prefix[0]
+            .explanation
+            .as_deref()
+            .unwrap()
+            .starts_with("tier=prefix"));
+    }
+
+    #[test]
+    fn heading_hits_outrank_chunk_hits_with_close_scores() {
+        let mut chunk = heading_document(1, "Chunk.docx", 3, "Economy advantage");
+        chunk.kind = "chunk".to_string();
+        chunk.chunk_text = Some("Midterms swing on the economy.".to_string());
+        let runtime = runtime_with(&[
+            chunk,
+            heading_document(2, "Heading.docx", 5, "Midterms link"),
+        ]);
+
+        let hits = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].relative_path, "Heading.docx");
+        assert!(hits[1].score - hits[0].score < 10.0);
+    }
+
+    #[test]
+    fn identical_chunk_text_in_two_files_collapses() {
+        let card = "Midterms hinge on the economy and turnout.";
+        let chunk_entry = |file_id: i64, relative_path: &str| {
+            let mut entry = heading_document(file_id, relative_path, 3, "Economy link");
+            entry.kind = "chunk".to_string();
+            entry.chunk_text = Some(card.to_string());
+            entry.content_hash = Some(chunk_content_hash(card));
+            entry
+        };
+        let runtime = runtime_with(&[
+            chunk_entry(1, "Aff/Politics.docx"),
+            chunk_entry(2, "Neg/Politics Copy.docx"),
+        ]);
+
+        let hits = search_runtime(
+            &runtime,
+            "turnout",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].content_hash.as_deref(),
+            Some(chunk_content_hash(card).as_str())
+        );
+    }
+
+    #[test]
+    fn count_matches_agrees_with_full_search() {
+        let runtime = runtime_with(&[
+            heading_document(1, "Aff/Plan.docx", 2, "Midterms impact"),
+            heading_document(2, "Neg/Politics.docx", 4, "Midterms link"),
+            heading_document(3, "Neg/Solvency.docx", 6, "Solvency turns"),
+        ]);
+
+        let hits = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        assert_eq!(
+            count_runtime(&runtime, "midterms", &[]).unwrap(),
+            hits.len()
+        );
+        assert_eq!(count_runtime(&runtime, "midterms", &[2]).unwrap(), 0);
+    }
+
+    #[test]
+    fn root_filter_restricts_hits_to_requested_roots() {
+        let entries = (1..=3)
+            .map(|root_id| {
+                let mut entry =
+                    heading_document(root_id, &format!("Root{root_id}.docx"), 1, "Midterms DA");
+                entry.root_id = root_id;
+                entry
+            })
+            .collect::<Vec<LexicalDocument>>();
+        let runtime = runtime_with(&entries);
+
+        let hits = search_runtime(
+            &runtime,
+            "midterms",
+            &[],
+            &LexicalSearchOptions::new(&[1, 3], 20),
+        )
+        .unwrap();
+        let mut root_file_ids = hits.iter().map(|hit| hit.file_id).collect::<Vec<i64>>();
+        root_file_ids.sort_unstable();
+
+        assert_eq!(root_file_ids, vec![1, 3]);
+        assert_eq!(count_runtime(&runtime, "midterms", &[1, 3]).unwrap(), 2);
+    }
+
+    #[test]
+    fn lexical_runtime_is_bound_per_data_dir() {
+        let first = lexical_runtime(&test_app("lexical-dir-a")).unwrap();
+        let second = lexical_runtime(&test_app("lexical-dir-b")).unwrap();
+
+        assert!(!Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn changed_tokenizer_config_recreates_index() {
+        let path = index_lexical_dir(&test_app("lexical-tokenizer")).unwrap();
+        let custom = LexicalTokenizerConfig {
+            prefix_min_gram: 3,
+            prefix_max_gram: 12,
+            ngram_min_gram: 2,
+            ngram_max_gram: 3,
+        };
+        let populate = |runtime: &LexicalRuntime| {
+            let mut writer = runtime.index.writer(15_000_000).unwrap();
+            add_document_to_writer(
+                &mut writer,
+                &runtime.fields,
+                &heading_document(1, "Aff/Plan.docx", 1, "Quokka advantage"),
+            )
+            .unwrap();
+            writer.commit().unwrap();
+            runtime.reader.reload().unwrap();
+        };
+
+        let (first, changed) = init_runtime(&path, &DEFAULT_TOKENIZER_CONFIG).unwrap();
+        assert!(!changed);
+        populate(&first);
+        drop(first);
+
+        let (reopened, changed) = init_runtime(&path, &DEFAULT_TOKENIZER_CONFIG).unwrap();
+        assert!(!changed);
+        assert_eq!(count_runtime(&reopened, "quokka", &[]).unwrap(), 1);
+        drop(reopened);
+
+        let (abandoned, changed) = init_runtime(&path, &custom).unwrap();
+        assert!(changed);
+        drop(abandoned);
+
+        // Without a completed rebuild the new fingerprint is never recorded.
+        let (recreated, changed) = init_runtime(&path, &custom).unwrap();
+        assert!(changed);
+        assert_eq!(count_runtime(&recreated, "quokka", &[]).unwrap(), 0);
+        populate(&recreated);
+
+        let hits =
+            search_runtime(&recreated, "quok", &[], &LexicalSearchOptions::new(&[], 20)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].heading_text.as_deref(), Some("Quokka advantage"));
+        assert!(configure_tokenizers(
+            &test_app("lexical-tokenizer-invalid"),
+            LexicalTokenizerConfig {
+                prefix_min_gram: 13,
+                ..custom
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn configured_tokenizers_survive_a_restart() {
+        let app = test_app("lexical-tokenizer-restart");
+        let custom = LexicalTokenizerConfig {
+            prefix_min_gram: 3,
+            prefix_max_gram: 12,
+            ..DEFAULT_TOKENIZER_CONFIG
+        };
+
+        assert_eq!(tokenizer_config(&app).unwrap(), DEFAULT_TOKENIZER_CONFIG);
+        configure_tokenizers(&app, custom).unwrap();
+        // A fresh process starts without the in-memory config.
+        tokenizer_configs()
+            .lock()
+            .unwrap()
+            .remove(&app_data_dir(&app).unwrap());
+
+        assert_eq!(tokenizer_config(&app).unwrap(), custom);
+        assert_eq!(
+            tokenizer_config(&test_app("lexical-tokenizer-other")).unwrap(),
+            DEFAULT_TOKENIZER_CONFIG
+        );
+    }
+
+    #[test]
+    fn exact_term_search_matches_whole_acronym_only() {
+        let runtime = runtime_with(&[
+            heading_document(1, "Neg/Consult CP.docx", 2, "Consult solves"),
+            heading_document(2, "Neg/Cpanel.docx", 3, "Cpanel outage"),
+            heading_document(3, "Aff/Answers.docx", 4, "AT: CP competition"),
+        ]);
+
+        let hits = exact_term_runtime(&runtime, "cp", &LexicalSearchOptions::new(&[], 20)).unwrap();
+        let mut paths = hits
+            .iter()
+            .map(|hit| hit.relative_path.as_str())
+            .collect::<Vec<&str>>();
+        paths.sort_unstable();
+        assert_eq!(paths, vec!["Aff/Answers.docx", "Neg/Consult CP.docx"]);
+    }
+
+    #[test]
+    fn wildcard_patterns_require_inner_wildcard_and_literal_chars() {
+        assert_eq!(
+            wildcard_patterns("Sol*ency advantage"),
+            vec!["sol.*ency".to_string(), "advantage".to_string()]
+        );
+        assert_eq!(wildcard_patterns("*cleus"), vec![".*cleus".to_string()]);
+        assert!(wildcard_patterns("midt*").is_empty());
+        assert!(wildcard_patterns("*us").is_empty());
+        assert!(wildcard_patterns("s*y").is_empty());
+    }
+
+    #[test]
+    fn suffix_wildcard_matches_heading_term() {
+        let runtime = runtime_with(&[
+            heading_document(1, "Aff/One.docx", 2, "Clean energy transition"),
+            heading_document(2, "Neg/Two.docx", 4, "Nucleus of the advantage"),
+        ]);
+
+        let hits = search_runtime(
+            &runtime,
+            "cleus",
+            &wildcard_patterns("*cleus"),
+            &LexicalSearchOptions {
+                explain: true,
+                ..LexicalSearchOptions::new(&[], 20)
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            hits[0].heading_text.as_deref(),
+            Some("Nucleus of the advantage")
+        );
+        assert!(hits[0]
+            .explanation
+            .as_deref()
+            .unwrap()
+            .starts_with("tier=wildcard fields=heading_text,query_text"));
+    }
+
+    #[test]
+    fn infix_wildcard_matches_heading_term() {
+        let runtime = runtime_with(&[
+            heading_document(1, "Aff/One.docx", 2, "Emergency powers"),
+            heading_document(2, "Aff/Two.docx", 4, "Solvency deficit"),
+            heading_document(3, "Neg/Three.docx", 6, "Solar panels"),
+        ]);
+
+        let hits = search_runtime(
+            &runtime,
+            "sol ency",
+            &wildcard_patterns("sol*ency"),
+            &LexicalSearchOptions {
+                explain: true,
+                ..LexicalSearchOptions::new(&[], 20)
+            },
+        )
+        .unwrap();
+        assert_eq!(hits[0].heading_text.as_deref(), Some("Solvency deficit"));
+        assert!(hits[0]
+            .explanation
+            .as_deref()
+            .unwrap()
+            .starts_with("tier=wildcard"));
+        assert!(hits
+            .iter()
+            .all(|hit| hit.heading_text.as_deref() != Some("Solar panels")));
+    }
+
+    #[test]
+    fn file_name_boost_lifts_file_name_match_above_body_match() {
+        let file_entry = LexicalDocument {
+            kind: "file".to_string(),
+            heading_level: None,
+            heading_text: None,
+            heading_order: None,
+            ..heading_document(2, "Neg/Politics DA.docx", 0, "")
+        };
+        let runtime = runtime_with(&[
+            heading_document(1, "Aff/Plan.docx", 2, "Politic risk"),
+            file_entry,
+        ]);
+
+        let mut hits = search_runtime(
+            &runtime,
+            "politic",
+            &[],
+            &LexicalSearchOptions::new(&[], 20),
+        )
+        .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].kind, "heading");
+
+        apply_file_name_boost(&mut hits, "politic", file_name_boost(Some(3.0)));
+        assert_eq!(hits[0].kind, "file");
+        assert_eq!(hits[0].relative_path, "Neg/Politics DA.docx");
+        assert_eq!(file_name_boost(Some(f64::NAN)), DEFAULT_FILE_NAME_BOOST);
+        assert_eq!(file_name_boost(Some(50.0)), MAX_FILE_NAME_BOOST);
+    }
+}
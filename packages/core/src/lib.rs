@@ -166,10 +166,14 @@ mod docx_capture;
 mod docx_parse;
 mod indexer;
 mod lexical;
+mod logging;
 mod preview;
 mod query_engine;
 mod search;
 mod semantic;
+#[cfg(test)]
+mod test_support;
+mod text_parse;
 mod types;
 mod util;
 mod vector;
@@ -195,6 +199,12 @@ fn current_app_handle() -> CommandResult<AppHandle> {
         .ok_or_else(|| "Core backend is not configured".to_string())
 }
 
+fn reset_runtimes() {
+    lexical::reset_runtimes();
+    semantic::reset_semantic_runtime();
+    query_engine::clear_query_cache();
+}
+
 fn set_app_handle(app_handle: AppHandle) -> CommandResult<()> {
     let mut writer = app_handle_cell()
         .write()
@@ -233,12 +243,132 @@ struct AddRootArgs {
 #[serde(rename_all = "camelCase")]
 struct GetIndexSnapshotArgs {
     path: String,
+    modified_since_ms: Option<i64>,
+    skip_folders: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteCaptureTargetArgs {
+    root_path: String,
+    target_path: String,
+    confirm: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RenameCaptureTargetArgs {
+    root_path: String,
+    old_target: String,
+    new_target: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FormatCitationArgs {
+    file_id: i64,
+    heading_order: i64,
+    style: Option<types::CitationStyle>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetChunkContextArgs {
+    file_id: i64,
+    heading_order: i64,
+    before: Option<usize>,
+    after: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigureIndexerArgs {
+    writer_heap_bytes: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigureIndexParallelismArgs {
+    threads: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSemanticEnabledArgs {
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSemanticModelArgs {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigureSearchRankingArgs {
+    kind_boost: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigureShortQueriesArgs {
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigureLexicalTokenizersArgs {
+    prefix_min_gram: Option<usize>,
+    prefix_max_gram: Option<usize>,
+    ngram_min_gram: Option<usize>,
+    ngram_max_gram: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelSemanticRebuildArgs {
+    clear: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigureSemanticLimitsArgs {
+    max_tokens: Option<usize>,
+    max_chars: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigurePreviewLimitsArgs {
+    max_chars_per_heading: Option<usize>,
+    max_total_chars: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifyIndexArgs {
+    root_path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RootHealthArgs {
+    root_path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportIndexSnapshotArgs {
+    root_path: String,
+    output_path: String,
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ListCaptureTargetsArgs {
     root_path: String,
+    sort: Option<types::CaptureTargetSort>,
 }
 
 #[derive(Deserialize)]
@@ -246,6 +376,7 @@ struct ListCaptureTargetsArgs {
 struct CaptureTargetPreviewArgs {
     root_path: String,
     target_path: String,
+    with_anchors: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -256,6 +387,50 @@ struct DeleteCaptureHeadingArgs {
     heading_order: i64,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteCaptureHeadingsArgs {
+    root_path: String,
+    target_path: String,
+    heading_orders: Vec<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListTrashArgs {
+    root_path: String,
+    target_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RestoreCaptureHeadingArgs {
+    root_path: String,
+    target_path: String,
+    trash_id: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepairCaptureTargetArgs {
+    root_path: String,
+    target_path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportCaptureHtmlArgs {
+    root_path: String,
+    target_path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaptureTargetStatsArgs {
+    root_path: String,
+    target_path: String,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MoveCaptureHeadingArgs {
@@ -265,6 +440,34 @@ struct MoveCaptureHeadingArgs {
     target_heading_order: i64,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewMoveCaptureHeadingArgs {
+    root_path: String,
+    target_path: String,
+    source_heading_order: i64,
+    target_heading_order: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReorderCaptureHeadingArgs {
+    root_path: String,
+    target_path: String,
+    heading_order: i64,
+    new_sibling_index: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MoveHeadingAcrossTargetsArgs {
+    root_path: String,
+    source_target: String,
+    source_heading_order: i64,
+    dest_target: String,
+    dest_heading_order: Option<i64>,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AddCaptureHeadingArgs {
@@ -275,10 +478,30 @@ struct AddCaptureHeadingArgs {
     selected_target_heading_order: Option<i64>,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetCaptureBannerArgs {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetDefaultCaptureTargetArgs {
+    name: Option<String>,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct IndexRootArgs {
     path: String,
+    #[serde(flatten)]
+    options: types::IndexOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToFileUriArgs {
+    path: String,
 }
 
 #[derive(Deserialize)]
@@ -287,6 +510,92 @@ struct GetFilePreviewArgs {
     file_id: i64,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetFilePreviewsArgs {
+    file_ids: Vec<i64>,
+    max_headings_each: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveCaptureMarkerArgs {
+    marker: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportCapturesArgs {
+    root_path: String,
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FilesByAuthorArgs {
+    root_path: String,
+    author_query: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportHeadingDocxArgs {
+    file_id: i64,
+    heading_order: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetFileOutlineArgs {
+    file_id: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshMetadataArgs {
+    file_id: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetHeadingNeighborsArgs {
+    file_id: i64,
+    heading_order: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetHighlightedTextArgs {
+    file_id: i64,
+    colors: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetHeadingXmlArgs {
+    file_id: i64,
+    heading_order: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListFileStylesArgs {
+    file_id: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewDebugStatsArgs {
+    file_id: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffFilesArgs {
+    file_id_a: i64,
+    file_id_b: i64,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GetHeadingPreviewHtmlArgs {
@@ -306,16 +615,46 @@ struct InsertCaptureArgs {
     heading_level: Option<i64>,
     heading_order: Option<i64>,
     selected_target_heading_order: Option<i64>,
+    under_marker: Option<String>,
+    with_citation_header: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewCaptureArgs {
+    source_path: String,
+    heading_order: Option<i64>,
+    content: String,
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SearchIndexHybridArgs {
+    query: String,
+    #[serde(flatten)]
+    options: types::HybridSearchOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CountMatchesArgs {
     query: String,
     root_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FindSimilarArgs {
+    file_id: i64,
+    heading_order: i64,
     limit: Option<usize>,
-    file_name_only: Option<bool>,
-    semantic_enabled: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextSimilarityArgs {
+    a: String,
+    b: String,
 }
 
 #[derive(Deserialize)]
@@ -337,8 +676,59 @@ fn to_json_value<T: Serialize>(value: T) -> CommandResult<Value> {
     serde_json::to_value(value).map_err(|error| format!("Could not serialize command result: {error}"))
 }
 
+fn app_data_dir_override(args: &Value) -> Option<PathBuf> {
+    args.get("appDataDir")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+fn scoped_app_handle(base: CommandResult<AppHandle>, args: &Value) -> CommandResult<AppHandle> {
+    let Some(app_data_dir) = app_data_dir_override(args) else {
+        return base;
+    };
+    let resource_dir = base.ok().and_then(|app| app.path().resource_dir().ok());
+    Ok(AppHandle::new(app_data_dir, resource_dir))
+}
+
 fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
-    let app = current_app_handle()?;
+    let app = scoped_app_handle(current_app_handle(), &request.args)?;
+    dispatch_command(app, request)
+}
+
+fn invoke_response(result: CommandResult<Value>) -> InvokeResponse {
+    match result {
+        Ok(value) => InvokeResponse {
+            ok: true,
+            value: Some(value),
+            error: None,
+        },
+        Err(error) => InvokeResponse {
+            ok: false,
+            value: None,
+            error: Some(error),
+        },
+    }
+}
+
+fn invoke_batch(app: &AppHandle, requests: Vec<Value>) -> Vec<InvokeResponse> {
+    requests
+        .into_iter()
+        .map(|request| {
+            invoke_response(
+                serde_json::from_value::<InvokeRequest>(request)
+                    .map_err(|error| error.to_string())
+                    .and_then(|request| {
+                        let app = scoped_app_handle(Ok(app.clone()), &request.args)?;
+                        dispatch_command(app, request)
+                    }),
+            )
+        })
+        .collect()
+}
+
+fn dispatch_command(app: AppHandle, request: InvokeRequest) -> CommandResult<Value> {
     let InvokeRequest { command, args } = request;
 
     match command.as_str() {
@@ -350,13 +740,58 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
             let _: EmptyArgs = parse_args(args)?;
             to_json_value(commands::list_roots(app)?)
         }
+        "rebuild_lexical_index_now" => {
+            let _: EmptyArgs = parse_args(args)?;
+            to_json_value(commands::rebuild_lexical_index_now(app)?)
+        }
+        "refresh_metadata" => {
+            let args: RefreshMetadataArgs = parse_args(args)?;
+            to_json_value(commands::refresh_metadata(app, args.file_id)?)
+        }
+        "get_global_stats" => {
+            let _: EmptyArgs = parse_args(args)?;
+            to_json_value(commands::get_global_stats(app)?)
+        }
         "get_index_snapshot" => {
             let args: GetIndexSnapshotArgs = parse_args(args)?;
-            to_json_value(commands::get_index_snapshot(app, args.path)?)
+            to_json_value(commands::get_index_snapshot(
+                app,
+                args.path,
+                args.modified_since_ms,
+                args.skip_folders,
+            )?)
+        }
+        "verify_index" => {
+            let args: VerifyIndexArgs = parse_args(args)?;
+            to_json_value(commands::verify_index(app, args.root_path)?)
+        }
+        "root_health" => {
+            let args: RootHealthArgs = parse_args(args)?;
+            to_json_value(commands::root_health(app, args.root_path)?)
+        }
+        "export_index_snapshot" => {
+            let args: ExportIndexSnapshotArgs = parse_args(args)?;
+            to_json_value(commands::export_index_snapshot(
+                app,
+                args.root_path,
+                args.output_path,
+            )?)
+        }
+        "export_captures" => {
+            let args: ExportCapturesArgs = parse_args(args)?;
+            to_json_value(commands::export_captures(app, args.root_path, args.format)?)
+        }
+        "resolve_capture_marker" => {
+            let args: ResolveCaptureMarkerArgs = parse_args(args)?;
+            to_json_value(commands::resolve_capture_marker(app, args.marker)?)
         }
         "list_capture_targets" => {
             let args: ListCaptureTargetsArgs = parse_args(args)?;
-            to_json_value(commands::list_capture_targets(app, args.root_path)?)
+            to_json_value(commands::list_capture_targets(
+                app,
+                args.root_path,
+                args.sort,
+            )?)
         }
         "get_capture_target_preview" => {
             let args: CaptureTargetPreviewArgs = parse_args(args)?;
@@ -364,6 +799,7 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
                 app,
                 args.root_path,
                 args.target_path,
+                args.with_anchors,
             )?)
         }
         "delete_capture_heading" => {
@@ -375,6 +811,70 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
                 args.heading_order,
             )?)
         }
+        "delete_capture_headings" => {
+            let args: DeleteCaptureHeadingsArgs = parse_args(args)?;
+            to_json_value(commands::delete_capture_headings(
+                app,
+                args.root_path,
+                args.target_path,
+                args.heading_orders,
+            )?)
+        }
+        "list_trash" => {
+            let args: ListTrashArgs = parse_args(args)?;
+            to_json_value(commands::list_trash(app, args.root_path, args.target_path)?)
+        }
+        "restore_capture_heading" => {
+            let args: RestoreCaptureHeadingArgs = parse_args(args)?;
+            to_json_value(commands::restore_capture_heading(
+                app,
+                args.root_path,
+                args.target_path,
+                args.trash_id,
+            )?)
+        }
+        "repair_capture_target" => {
+            let args: RepairCaptureTargetArgs = parse_args(args)?;
+            to_json_value(commands::repair_capture_target(
+                app,
+                args.root_path,
+                args.target_path,
+            )?)
+        }
+        "delete_capture_target" => {
+            let args: DeleteCaptureTargetArgs = parse_args(args)?;
+            to_json_value(commands::delete_capture_target(
+                app,
+                args.root_path,
+                args.target_path,
+                args.confirm,
+            )?)
+        }
+        "rename_capture_target" => {
+            let args: RenameCaptureTargetArgs = parse_args(args)?;
+            to_json_value(commands::rename_capture_target(
+                app,
+                args.root_path,
+                args.old_target,
+                args.new_target,
+            )?)
+        }
+        "export_capture_html" => {
+            let args: ExportCaptureHtmlArgs = parse_args(args)?;
+            to_json_value(commands::export_capture_html(
+                app,
+                args.root_path,
+                args.target_path,
+            )?)
+        }
+        "capture_target_stats" => {
+            let args: CaptureTargetStatsArgs = parse_args(args)?;
+            to_json_value(commands::capture_target_stats(
+                app,
+                args.root_path,
+                args.target_path,
+            )?)
+        }
         "move_capture_heading" => {
             let args: MoveCaptureHeadingArgs = parse_args(args)?;
             to_json_value(commands::move_capture_heading(
@@ -385,6 +885,37 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
                 args.target_heading_order,
             )?)
         }
+        "preview_move_capture_heading" => {
+            let args: PreviewMoveCaptureHeadingArgs = parse_args(args)?;
+            to_json_value(commands::preview_move_capture_heading(
+                app,
+                args.root_path,
+                args.target_path,
+                args.source_heading_order,
+                args.target_heading_order,
+            )?)
+        }
+        "reorder_capture_heading" => {
+            let args: ReorderCaptureHeadingArgs = parse_args(args)?;
+            to_json_value(commands::reorder_capture_heading(
+                app,
+                args.root_path,
+                args.target_path,
+                args.heading_order,
+                args.new_sibling_index,
+            )?)
+        }
+        "move_heading_across_targets" => {
+            let args: MoveHeadingAcrossTargetsArgs = parse_args(args)?;
+            to_json_value(commands::move_heading_across_targets(
+                app,
+                args.root_path,
+                args.source_target,
+                args.source_heading_order,
+                args.dest_target,
+                args.dest_heading_order,
+            )?)
+        }
         "add_capture_heading" => {
             let args: AddCaptureHeadingArgs = parse_args(args)?;
             to_json_value(commands::add_capture_heading(
@@ -396,14 +927,154 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
                 args.selected_target_heading_order,
             )?)
         }
+        "set_capture_banner" => {
+            let args: SetCaptureBannerArgs = parse_args(args)?;
+            to_json_value(commands::set_capture_banner(app, args.text)?)
+        }
+        "set_default_capture_target" => {
+            let args: SetDefaultCaptureTargetArgs = parse_args(args)?;
+            to_json_value(commands::set_default_capture_target(app, args.name)?)
+        }
         "index_root" => {
             let args: IndexRootArgs = parse_args(args)?;
-            to_json_value(commands::index_root(app, args.path)?)
+            to_json_value(commands::index_root(app, args.path, args.options)?)
+        }
+        "configure_indexer" => {
+            let args: ConfigureIndexerArgs = parse_args(args)?;
+            to_json_value(commands::configure_indexer(args.writer_heap_bytes)?)
+        }
+        "configure_index_parallelism" => {
+            let args: ConfigureIndexParallelismArgs = parse_args(args)?;
+            to_json_value(commands::configure_index_parallelism(args.threads)?)
+        }
+        "get_semantic_readiness" => {
+            let _: EmptyArgs = parse_args(args)?;
+            to_json_value(commands::get_semantic_readiness(app)?)
+        }
+        "set_semantic_enabled" => {
+            let args: SetSemanticEnabledArgs = parse_args(args)?;
+            to_json_value(commands::set_semantic_enabled(args.enabled)?)
+        }
+        "set_semantic_model" => {
+            let args: SetSemanticModelArgs = parse_args(args)?;
+            to_json_value(commands::set_semantic_model(app, args.name)?)
+        }
+        "cancel_semantic_rebuild" => {
+            let args: CancelSemanticRebuildArgs = parse_args(args)?;
+            to_json_value(commands::cancel_semantic_rebuild(app, args.clear)?)
+        }
+        "configure_semantic_limits" => {
+            let args: ConfigureSemanticLimitsArgs = parse_args(args)?;
+            to_json_value(commands::configure_semantic_limits(
+                args.max_tokens,
+                args.max_chars,
+            )?)
+        }
+        "configure_search_ranking" => {
+            let args: ConfigureSearchRankingArgs = parse_args(args)?;
+            to_json_value(commands::configure_search_ranking(args.kind_boost)?)
+        }
+        "configure_short_queries" => {
+            let args: ConfigureShortQueriesArgs = parse_args(args)?;
+            to_json_value(commands::configure_short_queries(args.enabled)?)
+        }
+        "flush_query_cache" => {
+            let _: EmptyArgs = parse_args(args)?;
+            to_json_value(commands::flush_query_cache(app)?)
+        }
+        "configure_lexical_tokenizers" => {
+            let args: ConfigureLexicalTokenizersArgs = parse_args(args)?;
+            to_json_value(commands::configure_lexical_tokenizers(
+                app,
+                args.prefix_min_gram,
+                args.prefix_max_gram,
+                args.ngram_min_gram,
+                args.ngram_max_gram,
+            )?)
+        }
+        "configure_preview_limits" => {
+            let args: ConfigurePreviewLimitsArgs = parse_args(args)?;
+            to_json_value(commands::configure_preview_limits(
+                args.max_chars_per_heading,
+                args.max_total_chars,
+            )?)
+        }
+        "to_file_uri" => {
+            let args: ToFileUriArgs = parse_args(args)?;
+            to_json_value(commands::to_file_uri(args.path)?)
         }
         "get_file_preview" => {
             let args: GetFilePreviewArgs = parse_args(args)?;
             to_json_value(commands::get_file_preview(app, args.file_id)?)
         }
+        "get_file_previews" => {
+            let args: GetFilePreviewsArgs = parse_args(args)?;
+            to_json_value(commands::get_file_previews(
+                app,
+                args.file_ids,
+                args.max_headings_each,
+            )?)
+        }
+        "export_heading_docx" => {
+            let args: ExportHeadingDocxArgs = parse_args(args)?;
+            to_json_value(commands::export_heading_docx(
+                app,
+                args.file_id,
+                args.heading_order,
+            )?)
+        }
+        "get_file_outline" => {
+            let args: GetFileOutlineArgs = parse_args(args)?;
+            to_json_value(commands::get_file_outline(app, args.file_id)?)
+        }
+        "get_heading_neighbors" => {
+            let args: GetHeadingNeighborsArgs = parse_args(args)?;
+            to_json_value(commands::get_heading_neighbors(
+                app,
+                args.file_id,
+                args.heading_order,
+            )?)
+        }
+        "get_highlighted_text" => {
+            let args: GetHighlightedTextArgs = parse_args(args)?;
+            to_json_value(commands::get_highlighted_text(
+                app,
+                args.file_id,
+                args.colors,
+            )?)
+        }
+        "get_heading_xml" => {
+            let args: GetHeadingXmlArgs = parse_args(args)?;
+            to_json_value(commands::get_heading_xml(
+                app,
+                args.file_id,
+                args.heading_order,
+            )?)
+        }
+        "list_file_styles" => {
+            let args: ListFileStylesArgs = parse_args(args)?;
+            to_json_value(commands::list_file_styles(app, args.file_id)?)
+        }
+        "preview_debug_stats" => {
+            let args: PreviewDebugStatsArgs = parse_args(args)?;
+            to_json_value(commands::preview_debug_stats(app, args.file_id)?)
+        }
+        "find_duplicate_files" => {
+            let _: EmptyArgs = parse_args(args)?;
+            to_json_value(commands::find_duplicate_files(app)?)
+        }
+        "files_by_author" => {
+            let args: FilesByAuthorArgs = parse_args(args)?;
+            to_json_value(commands::files_by_author(
+                app,
+                args.root_path,
+                args.author_query,
+            )?)
+        }
+        "diff_files" => {
+            let args: DiffFilesArgs = parse_args(args)?;
+            to_json_value(commands::diff_files(app, args.file_id_a, args.file_id_b)?)
+        }
         "get_heading_preview_html" => {
             let args: GetHeadingPreviewHtmlArgs = parse_args(args)?;
             to_json_value(commands::get_heading_preview_html(
@@ -412,6 +1083,25 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
                 args.heading_order,
             )?)
         }
+        "get_chunk_context" => {
+            let args: GetChunkContextArgs = parse_args(args)?;
+            to_json_value(commands::get_chunk_context(
+                app,
+                args.file_id,
+                args.heading_order,
+                args.before,
+                args.after,
+            )?)
+        }
+        "format_citation" => {
+            let args: FormatCitationArgs = parse_args(args)?;
+            to_json_value(commands::format_citation(
+                app,
+                args.file_id,
+                args.heading_order,
+                args.style,
+            )?)
+        }
         "insert_capture" => {
             let args: InsertCaptureArgs = parse_args(args)?;
             to_json_value(commands::insert_capture(
@@ -425,19 +1115,48 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
                 args.heading_level,
                 args.heading_order,
                 args.selected_target_heading_order,
+                args.under_marker,
+                args.with_citation_header.unwrap_or(false),
+            )?)
+        }
+        "preview_capture" => {
+            let args: PreviewCaptureArgs = parse_args(args)?;
+            to_json_value(commands::preview_capture(
+                app,
+                args.source_path,
+                args.heading_order,
+                args.content,
             )?)
         }
         "search_index_hybrid" => {
             let args: SearchIndexHybridArgs = parse_args(args)?;
             to_json_value(async_runtime::block_on(commands::search_index_hybrid(
+                app,
+                args.query,
+                args.options,
+            ))?)
+        }
+        "count_matches" => {
+            let args: CountMatchesArgs = parse_args(args)?;
+            to_json_value(async_runtime::block_on(commands::count_matches(
                 app,
                 args.query,
                 args.root_path,
+            ))?)
+        }
+        "find_similar" => {
+            let args: FindSimilarArgs = parse_args(args)?;
+            to_json_value(async_runtime::block_on(commands::find_similar(
+                app,
+                args.file_id,
+                args.heading_order,
                 args.limit,
-                args.file_name_only,
-                args.semantic_enabled,
             ))?)
         }
+        "text_similarity" => {
+            let args: TextSimilarityArgs = parse_args(args)?;
+            to_json_value(commands::text_similarity(app, args.a, args.b)?)
+        }
         "benchmark_root_performance" => {
             let args: BenchmarkRootPerformanceArgs = parse_args(args)?;
             to_json_value(async_runtime::block_on(commands::benchmark_root_performance(
@@ -454,7 +1173,7 @@ fn invoke_command(request: InvokeRequest) -> CommandResult<Value> {
     }
 }
 
-fn response_json_pointer(response: InvokeResponse) -> *mut c_char {
+fn response_json_pointer<T: Serialize>(response: T) -> *mut c_char {
     let raw = serde_json::to_string(&response).unwrap_or_else(|error| {
         format!(
             "{{\"ok\":false,\"error\":\"Could not serialize response: {error}\"}}"
@@ -487,6 +1206,25 @@ pub extern "C" fn core_set_event_callback(callback: Option<CoreEventCallback>) {
     set_event_callback(callback);
 }
 
+pub type CoreLogCallback = extern "C" fn(*const c_char, *const c_char);
+
+#[no_mangle]
+pub extern "C" fn core_set_log_callback(callback: Option<CoreLogCallback>) {
+    logging::set_log_callback(callback);
+}
+
+#[no_mangle]
+pub extern "C" fn core_set_log_level(level_ptr: *const c_char) -> c_int {
+    let Ok(level) = (unsafe { pointer_to_string(level_ptr) }) else {
+        return 0;
+    };
+    let Some(level) = logging::LogLevel::parse(&level) else {
+        return 0;
+    };
+    logging::set_log_level(level);
+    1
+}
+
 #[no_mangle]
 pub extern "C" fn core_configure(
     app_data_dir_ptr: *const c_char,
@@ -509,34 +1247,40 @@ pub extern "C" fn core_configure(
     });
 
     let app_handle = AppHandle::new(PathBuf::from(app_data_dir), resource_dir);
-    if set_app_handle(app_handle).is_err() {
+    if set_app_handle(app_handle.clone()).is_err() {
         return 0;
     }
+    reset_runtimes();
+    semantic::load_semantic_model(&app_handle);
 
     1
 }
 
 #[no_mangle]
 pub extern "C" fn core_invoke_json(request_ptr: *const c_char) -> *mut c_char {
-    let response = match unsafe { pointer_to_string(request_ptr) }
-        .and_then(|raw| serde_json::from_str::<InvokeRequest>(&raw).map_err(|error| error.to_string()))
-        .and_then(invoke_command)
-    {
-        Ok(value) => InvokeResponse {
-            ok: true,
-            value: Some(value),
-            error: None,
-        },
-        Err(error) => InvokeResponse {
-            ok: false,
-            value: None,
-            error: Some(error),
-        },
-    };
+    let response = invoke_response(
+        unsafe { pointer_to_string(request_ptr) }
+            .and_then(|raw| {
+                serde_json::from_str::<InvokeRequest>(&raw).map_err(|error| error.to_string())
+            })
+            .and_then(invoke_command),
+    );
 
     response_json_pointer(response)
 }
 
+#[no_mangle]
+pub extern "C" fn core_invoke_batch_json(requests_ptr: *const c_char) -> *mut c_char {
+    let batch = unsafe { pointer_to_string(requests_ptr) }
+        .and_then(|raw| serde_json::from_str::<Vec<Value>>(&raw).map_err(|error| error.to_string()))
+        .and_then(|requests| current_app_handle().map(|app| invoke_batch(&app, requests)));
+
+    match batch {
+        Ok(responses) => response_json_pointer(responses),
+        Err(error) => response_json_pointer(invoke_response(Err(error))),
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn core_free_str(s: *mut c_char) {
     if s.is_null() {
@@ -546,3 +1290,101 @@ pub extern "C" fn core_free_str(s: *mut c_char) {
         drop(CString::from_raw(s));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use std::path::PathBuf;
+
+    use super::{invoke_batch, invoke_command, InvokeRequest};
+    use crate::test_support::{heading_xml, temp_dir, test_app, write_docx};
+
+    #[test]
+    fn batch_isolates_per_request_failures() {
+        let app = test_app("invoke-batch");
+        let responses = invoke_batch(
+            &app,
+            vec![
+                json!({ "command": "list_roots", "args": {} }),
+                json!({ "command": "no_such_command" }),
+                json!({ "args": {} }),
+                json!({ "command": "list_roots" }),
+            ],
+        );
+
+        let summary = responses
+            .iter()
+            .map(|response| (response.ok, response.error.is_some()))
+            .collect::<Vec<(bool, bool)>>();
+        assert_eq!(
+            summary,
+            vec![(true, false), (false, true), (false, true), (true, false)]
+        );
+        assert_eq!(
+            responses[1].error.as_deref(),
+            Some("Unknown command: no_such_command")
+        );
+        assert_eq!(responses[0].value, Some(json!([])));
+    }
+
+    #[test]
+    fn app_data_dir_override_keeps_indexes_separate() {
+        let first_dir = temp_dir("override-data-a");
+        let second_dir = temp_dir("override-data-b");
+        let root = temp_dir("override-root");
+        let request = |command: &str, args: serde_json::Value| {
+            serde_json::from_value::<InvokeRequest>(json!({ "command": command, "args": args }))
+                .unwrap()
+        };
+
+        invoke_command(request(
+            "add_root",
+            json!({ "path": root, "appDataDir": first_dir }),
+        ))
+        .unwrap();
+        let first_roots =
+            invoke_command(request("list_roots", json!({ "appDataDir": first_dir }))).unwrap();
+        let second_roots =
+            invoke_command(request("list_roots", json!({ "appDataDir": second_dir }))).unwrap();
+
+        assert_eq!(first_roots.as_array().map(Vec::len), Some(1));
+        assert_eq!(second_roots, json!([]));
+    }
+
+    #[test]
+    fn search_stays_bound_to_the_data_dir_it_indexed() {
+        let request = |command: &str, args: serde_json::Value| {
+            serde_json::from_value::<InvokeRequest>(json!({ "command": command, "args": args }))
+                .unwrap()
+        };
+        let index_into = |label: &str, heading: &str| {
+            let data_dir = temp_dir(&format!("rebind-data-{label}"));
+            let root = temp_dir(&format!("rebind-root-{label}"));
+            write_docx(&root.join("Blocks.docx"), &heading_xml(2, heading));
+            invoke_command(request(
+                "index_root",
+                json!({ "path": root, "rebuildSemantic": false, "appDataDir": data_dir }),
+            ))
+            .unwrap();
+            data_dir
+        };
+        let search = |data_dir: &PathBuf, query: &str| {
+            invoke_command(request(
+                "search_index_hybrid",
+                json!({ "query": query, "semanticEnabled": false, "appDataDir": data_dir }),
+            ))
+            .unwrap()["hits"]
+                .as_array()
+                .map(Vec::len)
+                .unwrap_or(0)
+        };
+
+        let first = index_into("first", "Midterms DA");
+        let second = index_into("second", "Tariffs CP");
+
+        assert!(search(&first, "midterms") > 0);
+        assert!(search(&second, "tariffs") > 0);
+        assert_eq!(search(&second, "midterms"), 0);
+    }
+}
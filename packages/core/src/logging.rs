@@ -0,0 +1,118 @@
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+pub type LogCallback = extern "C" fn(*const c_char, *const c_char);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+impl LogLevel {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(Self::Off),
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" | "trace" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Warn as u8);
+static LOG_CALLBACK: RwLock<Option<LogCallback>> = RwLock::new(None);
+
+pub(crate) fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn set_log_callback(callback: Option<LogCallback>) {
+    if let Ok(mut writer) = LOG_CALLBACK.write() {
+        *writer = callback;
+    }
+}
+
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level as u8 <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+pub(crate) fn log(level: LogLevel, message: &str) {
+    if !enabled(level) {
+        return;
+    }
+
+    let callback = LOG_CALLBACK.read().ok().and_then(|reader| *reader);
+    let Some(callback) = callback else {
+        eprintln!("[{}] {message}", level.as_str());
+        return;
+    };
+
+    let (Ok(level_c), Ok(message_c)) = (CString::new(level.as_str()), CString::new(message)) else {
+        return;
+    };
+    callback(level_c.as_ptr(), message_c.as_ptr());
+}
+
+pub(crate) fn error(message: &str) {
+    log(LogLevel::Error, message);
+}
+
+pub(crate) fn warn(message: &str) {
+    log(LogLevel::Warn, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::sync::Mutex;
+
+    use super::{error, set_log_callback, set_log_level, warn, LogLevel};
+
+    static CAPTURED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    extern "C" fn capture(level: *const c_char, message: *const c_char) {
+        let level = unsafe { CStr::from_ptr(level) }.to_string_lossy();
+        let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+        CAPTURED.lock().unwrap().push(format!("{level}:{message}"));
+    }
+
+    #[test]
+    fn log_level_filters_captured_messages() {
+        set_log_callback(Some(capture));
+        set_log_level(LogLevel::Error);
+        warn("filter-test warn");
+        error("filter-test error");
+        set_log_level(LogLevel::Warn);
+        set_log_callback(None);
+
+        let captured = CAPTURED
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.contains("filter-test"))
+            .cloned()
+            .collect::<Vec<String>>();
+        assert_eq!(captured, vec!["error:filter-test error".to_string()]);
+        assert_eq!(LogLevel::parse(" Warning "), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse("loud"), None);
+    }
+}
@@ -1,17 +1,166 @@
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 use roxmltree::{Document, Node};
 use zip::ZipArchive;
 
 use crate::docx_parse::{
-    build_heading_ranges, has_tag, html_escape, parse_docx_paragraphs, read_zip_file,
-    run_has_active_underline, run_has_property, run_highlight_class,
+    build_heading_ranges, has_tag, heading_paths, html_escape, paragraph_comment_ids,
+    read_comment_bodies, read_docx_part, read_zip_file, run_has_active_underline, run_has_property,
+    run_highlight_class,
 };
-use crate::types::{FileHeading, TaggedBlock};
+use crate::search::normalize_for_search;
+use crate::text_parse::{is_plain_text_path, parse_source_paragraphs};
+use crate::types::{CommentBody, FileHeading, ParsedParagraph, PreviewLimits, TaggedBlock};
 use crate::util::{is_probable_author_line, path_display};
 use crate::CommandResult;
 
+pub(crate) const PREVIEW_TRUNCATION_MARKER: &str = "…";
+const DEFAULT_MAX_CHARS_PER_HEADING: usize = 60_000;
+const DEFAULT_MAX_TOTAL_CHARS: usize = 1_000_000;
+const MIN_PREVIEW_CHARS: usize = 200;
+const MAX_PREVIEW_CHARS: usize = 20_000_000;
+const PREVIEW_CACHE_CAPACITY: usize = 64;
+const MAX_ANCHOR_SLUG_CHARS: usize = 48;
+
+const EXPORT_HTML_STYLE: &str = "body{font-family:Calibri,Arial,sans-serif;max-width:820px;margin:2rem auto;padding:0 1rem;line-height:1.45;color:#111}\
+p{margin:0 0 .5rem}\
+.bf-preview-h1{font-size:1.8rem;font-weight:700;text-align:center;margin-top:1.5rem}\
+.bf-preview-h2{font-size:1.5rem;font-weight:700;text-align:center;margin-top:1.25rem}\
+.bf-preview-h3{font-size:1.25rem;font-weight:700;text-decoration:underline;margin-top:1rem}\
+.bf-preview-h4{font-size:1.05rem;font-weight:700;margin-top:.75rem}\
+.bf-run-bold{font-weight:700}\
+.bf-run-italic{font-style:italic}\
+.bf-run-underline{text-decoration:underline}\
+.bf-run-smallcaps{font-variant:small-caps}\
+.bf-hl-yellow{background:#ffff00}\
+.bf-hl-green{background:#00ff00}\
+.bf-hl-cyan{background:#00ffff}\
+.bf-hl-magenta{background:#ff00ff}\
+.bf-hl-blue{background:#0000ff;color:#fff}\
+.bf-hl-gray{background:#c0c0c0}\
+.bf-preview-link{color:#1a56db;text-decoration:underline}\
+.bf-comment{margin-left:.35rem;padding:0 .25rem;border-left:3px solid #e3a008;background:#fdf6b2;color:#5a4500;font-size:.85em}\
+.bf-preview-truncated{color:#666}";
+
+static MAX_CHARS_PER_HEADING: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_CHARS_PER_HEADING);
+static MAX_TOTAL_CHARS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_TOTAL_CHARS);
+
+type PreviewContent = (Vec<FileHeading>, Vec<TaggedBlock>, i64);
+
+#[derive(Default)]
+struct PreviewCache {
+    order: VecDeque<(i64, i64)>,
+    entries: HashMap<(i64, i64), (String, PreviewContent)>,
+}
+
+impl PreviewCache {
+    fn get(&mut self, key: (i64, i64), path: &str) -> Option<PreviewContent> {
+        let (cached_path, content) = self.entries.get(&key)?;
+        if cached_path != path {
+            return None;
+        }
+        let content = content.clone();
+        self.order.retain(|item| *item != key);
+        self.order.push_back(key);
+        Some(content)
+    }
+
+    fn put(&mut self, key: (i64, i64), path: String, content: PreviewContent) {
+        let stale = self
+            .entries
+            .keys()
+            .filter(|(file_id, _)| *file_id == key.0)
+            .copied()
+            .collect::<Vec<(i64, i64)>>();
+        for stale_key in stale {
+            self.entries.remove(&stale_key);
+        }
+        self.order.retain(|(file_id, _)| *file_id != key.0);
+
+        self.order.push_back(key);
+        self.entries.insert(key, (path, content));
+        while self.order.len() > PREVIEW_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+static PREVIEW_CACHE: OnceLock<Mutex<PreviewCache>> = OnceLock::new();
+
+fn preview_cache() -> &'static Mutex<PreviewCache> {
+    PREVIEW_CACHE.get_or_init(|| Mutex::new(PreviewCache::default()))
+}
+
+pub(crate) fn clear_preview_cache() {
+    if let Ok(mut cache) = preview_cache().lock() {
+        *cache = PreviewCache::default();
+    }
+}
+
+pub(crate) fn cached_preview_content<F>(
+    file_id: i64,
+    modified_ms: i64,
+    file_path: &Path,
+    parse: F,
+) -> CommandResult<PreviewContent>
+where
+    F: FnOnce(&Path) -> CommandResult<PreviewContent>,
+{
+    let key = (file_id, modified_ms);
+    let path = path_display(file_path);
+    if let Ok(mut cache) = preview_cache().lock() {
+        if let Some(content) = cache.get(key, &path) {
+            return Ok(content);
+        }
+    }
+
+    let content = parse(file_path)?;
+    if let Ok(mut cache) = preview_cache().lock() {
+        cache.put(key, path, content.clone());
+    }
+    Ok(content)
+}
+
+pub(crate) fn configure_preview_limits(
+    max_chars_per_heading: Option<usize>,
+    max_total_chars: Option<usize>,
+) -> PreviewLimits {
+    if let Some(value) = max_chars_per_heading {
+        MAX_CHARS_PER_HEADING.store(
+            value.clamp(MIN_PREVIEW_CHARS, MAX_PREVIEW_CHARS),
+            Ordering::Relaxed,
+        );
+    }
+    if let Some(value) = max_total_chars {
+        MAX_TOTAL_CHARS.store(
+            value.clamp(MIN_PREVIEW_CHARS, MAX_PREVIEW_CHARS),
+            Ordering::Relaxed,
+        );
+    }
+    clear_preview_cache();
+    preview_limits()
+}
+
+pub(crate) fn preview_limits() -> PreviewLimits {
+    PreviewLimits {
+        max_chars_per_heading: MAX_CHARS_PER_HEADING.load(Ordering::Relaxed),
+        max_total_chars: MAX_TOTAL_CHARS.load(Ordering::Relaxed),
+    }
+}
+
+fn truncate_preview_text(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((byte_index, _)) => format!("{}{PREVIEW_TRUNCATION_MARKER}", &text[..byte_index]),
+        None => text.to_string(),
+    }
+}
+
 fn push_escaped_text_with_breaks(target: &mut String, text: &str) {
     for (index, segment) in text.split('\n').enumerate() {
         if index > 0 {
@@ -115,10 +264,31 @@ pub(crate) fn preview_paragraph_class(heading_level: Option<i64>) -> &'static st
     }
 }
 
+fn push_comment_annotations(
+    target: &mut String,
+    paragraph_node: Node<'_, '_>,
+    comments: &HashMap<String, CommentBody>,
+) {
+    for id in paragraph_comment_ids(paragraph_node, "commentReference") {
+        let Some(comment) = comments.get(&id) else {
+            continue;
+        };
+        let title = comment
+            .author
+            .as_deref()
+            .map(|author| format!(" title=\"{}\"", html_escape(author)))
+            .unwrap_or_default();
+        target.push_str(&format!("<span class=\"bf-comment\"{title}>"));
+        push_escaped_text_with_breaks(target, &comment.text);
+        target.push_str("</span>");
+    }
+}
+
 pub(crate) fn render_preview_paragraph(
     paragraph_node: Node<'_, '_>,
     heading_level: Option<i64>,
     fallback_text: &str,
+    comments: &HashMap<String, CommentBody>,
 ) -> String {
     let mut body = String::new();
     for child in paragraph_node.children() {
@@ -131,6 +301,7 @@ pub(crate) fn render_preview_paragraph(
     if body.trim().is_empty() {
         body.push_str("&nbsp;");
     }
+    push_comment_annotations(&mut body, paragraph_node, comments);
 
     format!(
         "<p class=\"{}\">{body}</p>",
@@ -138,11 +309,66 @@ pub(crate) fn render_preview_paragraph(
     )
 }
 
+fn read_preview_document_xml(file_path: &Path) -> CommandResult<String> {
+    let file = File::open(file_path)
+        .map_err(|error| format!("Could not open '{}': {error}", path_display(file_path)))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|error| format!("Could not read '{}': {error}", path_display(file_path)))?;
+    read_zip_file(&mut archive, "word/document.xml").ok_or_else(|| {
+        format!(
+            "Missing word/document.xml in '{}'. Is this a valid docx file?",
+            path_display(file_path)
+        )
+    })
+}
+
+fn read_preview_comments(file_path: &Path) -> HashMap<String, CommentBody> {
+    read_docx_part(file_path, "word/comments.xml")
+        .ok()
+        .flatten()
+        .map(|comments_xml| read_comment_bodies(&comments_xml))
+        .unwrap_or_default()
+}
+
+fn push_limited_paragraphs(
+    html: &mut String,
+    paragraphs: &[ParsedParagraph],
+    max_chars: usize,
+    render: impl Fn(usize) -> String,
+) {
+    let mut remaining = max_chars;
+    for (index, paragraph) in paragraphs.iter().enumerate() {
+        let text_chars = paragraph.text.chars().count();
+        if text_chars > remaining {
+            let mut body = String::new();
+            push_escaped_text_with_breaks(
+                &mut body,
+                &truncate_preview_text(&paragraph.text, remaining),
+            );
+            html.push_str(&format!(
+                "<p class=\"{} bf-preview-truncated\">{body}</p>",
+                preview_paragraph_class(paragraph.heading_level)
+            ));
+            return;
+        }
+        remaining -= text_chars;
+        html.push_str(&render(index));
+    }
+}
+
 pub(crate) fn extract_heading_preview_html(
     file_path: &Path,
     heading_order: i64,
 ) -> CommandResult<String> {
-    let paragraphs = parse_docx_paragraphs(file_path)?;
+    extract_heading_preview_html_with_limits(file_path, heading_order, preview_limits())
+}
+
+fn extract_heading_preview_html_with_limits(
+    file_path: &Path,
+    heading_order: i64,
+    limits: PreviewLimits,
+) -> CommandResult<String> {
+    let paragraphs = parse_source_paragraphs(file_path)?;
     let heading_ranges = build_heading_ranges(&paragraphs);
     let Some(target_range) = heading_ranges
         .iter()
@@ -150,17 +376,25 @@ pub(crate) fn extract_heading_preview_html(
     else {
         return Ok(String::new());
     };
+    let max_chars = limits.max_chars_per_heading.min(limits.max_total_chars);
+
+    if is_plain_text_path(file_path) {
+        let end = target_range.end_index.min(paragraphs.len());
+        let start = target_range.start_index.min(end);
+        let section = &paragraphs[start..end];
+        let mut html = String::new();
+        push_limited_paragraphs(&mut html, section, max_chars, |index| {
+            let mut body = String::new();
+            push_escaped_text_with_breaks(&mut body, &section[index].text);
+            format!(
+                "<p class=\"{}\">{body}</p>",
+                preview_paragraph_class(section[index].heading_level)
+            )
+        });
+        return Ok(html);
+    }
 
-    let file = File::open(file_path)
-        .map_err(|error| format!("Could not open '{}': {error}", path_display(file_path)))?;
-    let mut archive = ZipArchive::new(file)
-        .map_err(|error| format!("Could not read '{}': {error}", path_display(file_path)))?;
-    let document_xml = read_zip_file(&mut archive, "word/document.xml").ok_or_else(|| {
-        format!(
-            "Missing word/document.xml in '{}'. Is this a valid docx file?",
-            path_display(file_path)
-        )
-    })?;
+    let document_xml = read_preview_document_xml(file_path)?;
     let document = Document::parse(&document_xml).map_err(|error| {
         format!(
             "Could not parse preview XML '{}': {error}",
@@ -182,24 +416,80 @@ pub(crate) fn extract_heading_preview_html(
         return Ok(String::new());
     }
 
+    let comments = read_preview_comments(file_path);
     let mut html = String::new();
-    for index in start..end {
-        let paragraph_node = paragraph_nodes[index];
-        let paragraph_meta = &paragraphs[index];
-        html.push_str(&render_preview_paragraph(
-            paragraph_node,
+    push_limited_paragraphs(&mut html, &paragraphs[start..end], max_chars, |offset| {
+        let paragraph_meta = &paragraphs[start + offset];
+        render_preview_paragraph(
+            paragraph_nodes[start + offset],
             paragraph_meta.heading_level,
             &paragraph_meta.text,
+            &comments,
+        )
+    });
+
+    Ok(html)
+}
+
+pub(crate) fn render_document_html(file_path: &Path, title: &str) -> CommandResult<String> {
+    let paragraphs = parse_source_paragraphs(file_path)?;
+    let document_xml = read_preview_document_xml(file_path)?;
+    let document = Document::parse(&document_xml).map_err(|error| {
+        format!(
+            "Could not parse preview XML '{}': {error}",
+            path_display(file_path)
+        )
+    })?;
+    let paragraph_nodes = document
+        .descendants()
+        .filter(|node| has_tag(*node, "p"))
+        .collect::<Vec<Node<'_, '_>>>();
+
+    let comments = read_preview_comments(file_path);
+    let mut body = String::new();
+    for (paragraph_node, paragraph_meta) in paragraph_nodes.iter().zip(paragraphs.iter()) {
+        body.push_str(&render_preview_paragraph(
+            *paragraph_node,
+            paragraph_meta.heading_level,
+            &paragraph_meta.text,
+            &comments,
         ));
     }
 
-    Ok(html)
+    Ok(format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title><style>{EXPORT_HTML_STYLE}</style></head><body>{body}</body></html>",
+        html_escape(title)
+    ))
 }
 
-pub(crate) fn extract_preview_content(
-    file_path: &Path,
-) -> CommandResult<(Vec<FileHeading>, Vec<TaggedBlock>)> {
-    let paragraphs = parse_docx_paragraphs(file_path)?;
+pub(crate) fn heading_anchor_id(order: i64, text: &str) -> String {
+    let slug = normalize_for_search(text)
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join("-");
+    let slug = slug.chars().take(MAX_ANCHOR_SLUG_CHARS).collect::<String>();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        format!("h{order}")
+    } else {
+        format!("h{order}-{slug}")
+    }
+}
+
+pub(crate) fn extract_preview_content(file_path: &Path) -> CommandResult<PreviewContent> {
+    extract_preview_content_with_limits(file_path, preview_limits())
+}
+
+pub(crate) fn preview_headings(paragraphs: &[ParsedParagraph]) -> Vec<FileHeading> {
+    preview_headings_with_limits(paragraphs, preview_limits())
+}
+
+fn preview_headings_with_limits(
+    paragraphs: &[ParsedParagraph],
+    limits: PreviewLimits,
+) -> Vec<FileHeading> {
+    let mut remaining_total = limits.max_total_chars;
+    let mut paths = heading_paths(paragraphs);
 
     let mut heading_indices = Vec::new();
     for (index, paragraph) in paragraphs.iter().enumerate() {
@@ -232,7 +522,10 @@ pub(crate) fn extract_preview_content(
             .iter()
             .map(|entry| entry.text.as_str())
             .collect::<Vec<&str>>();
-        let copy_text = section_lines.join("\n");
+        let section_text = section_lines.join("\n");
+        let budget = limits.max_chars_per_heading.min(remaining_total);
+        remaining_total -= section_text.chars().count().min(budget);
+        let copy_text = truncate_preview_text(&section_text, budget);
 
         headings.push(FileHeading {
             id: paragraph.order,
@@ -240,8 +533,19 @@ pub(crate) fn extract_preview_content(
             level,
             text: paragraph.text.clone(),
             copy_text,
+            heading_path: paths.remove(&paragraph.order),
+            anchor_id: None,
         });
     }
+    headings
+}
+
+fn extract_preview_content_with_limits(
+    file_path: &Path,
+    limits: PreviewLimits,
+) -> CommandResult<PreviewContent> {
+    let paragraphs = parse_source_paragraphs(file_path)?;
+    let headings = preview_headings_with_limits(&paragraphs, limits);
 
     let mut f8_cites = Vec::new();
     let mut cursor = 0_usize;
@@ -277,5 +581,150 @@ pub(crate) fn extract_preview_content(
         });
     }
 
-    Ok((headings, f8_cites))
+    let word_count = paragraphs
+        .iter()
+        .map(|paragraph| paragraph.text.split_whitespace().count())
+        .sum::<usize>();
+
+    Ok((
+        headings,
+        f8_cites,
+        i64::try_from(word_count).unwrap_or(i64::MAX),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{
+        cached_preview_content, extract_heading_preview_html_with_limits, extract_preview_content,
+        extract_preview_content_with_limits, PREVIEW_TRUNCATION_MARKER,
+    };
+    use crate::test_support::{heading_xml, paragraph_xml, temp_dir, write_docx};
+    use crate::types::PreviewLimits;
+
+    #[test]
+    fn preview_limits_truncate_with_marker() {
+        let dir = temp_dir("preview-limits");
+        let path = dir.join("Cards.docx");
+        let body = "x".repeat(300);
+        write_docx(
+            &path,
+            &format!(
+                "{}{}{}{}",
+                heading_xml(1, "First"),
+                paragraph_xml(&body),
+                heading_xml(1, "Second"),
+                paragraph_xml(&body)
+            ),
+        );
+
+        let html = extract_heading_preview_html_with_limits(
+            &path,
+            1,
+            PreviewLimits {
+                max_chars_per_heading: 250,
+                max_total_chars: 10_000,
+            },
+        )
+        .unwrap();
+        let (headings, _, _) = extract_preview_content_with_limits(
+            &path,
+            PreviewLimits {
+                max_chars_per_heading: 1_000,
+                max_total_chars: 400,
+            },
+        )
+        .unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(html.contains("bf-preview-truncated"));
+        assert!(html.contains(&format!("{}{PREVIEW_TRUNCATION_MARKER}", "x".repeat(245))));
+        assert!(!html.contains(&body));
+
+        assert_eq!(headings.len(), 2);
+        assert!(!headings[0].copy_text.ends_with(PREVIEW_TRUNCATION_MARKER));
+        assert!(headings[1].copy_text.ends_with(PREVIEW_TRUNCATION_MARKER));
+        assert!(headings[1].copy_text.chars().count() < 100);
+    }
+
+    #[test]
+    fn unchanged_file_preview_is_served_from_cache() {
+        let dir = temp_dir("preview-cache");
+        let path = dir.join("Cards.docx");
+        write_docx(&path, &heading_xml(2, "Midterms DA"));
+        let parses = AtomicUsize::new(0);
+        let counted_parse = |file_path: &std::path::Path| {
+            parses.fetch_add(1, Ordering::Relaxed);
+            extract_preview_content(file_path)
+        };
+
+        let first = cached_preview_content(-42, 1_000, &path, counted_parse).unwrap();
+        let second = cached_preview_content(-42, 1_000, &path, counted_parse).unwrap();
+        let _ = cached_preview_content(-42, 2_000, &path, counted_parse).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(first.0.len(), 1);
+        assert_eq!(second.0[0].text, "Midterms DA");
+        assert_eq!(parses.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn heading_paths_list_ancestors_in_order() {
+        let dir = temp_dir("preview-heading-path");
+        let path = dir.join("Neg.docx");
+        write_docx(
+            &path,
+            &format!(
+                "{}{}{}{}{}",
+                heading_xml(1, "Politics DA"),
+                heading_xml(2, "1NC"),
+                heading_xml(3, "Uniqueness"),
+                heading_xml(2, "2NC"),
+                heading_xml(1, "Econ DA")
+            ),
+        );
+
+        let (headings, _, _) = extract_preview_content(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let paths = headings
+            .iter()
+            .map(|heading| (heading.text.as_str(), heading.heading_path.clone().unwrap()))
+            .collect::<Vec<(&str, Vec<String>)>>();
+        assert_eq!(
+            paths,
+            vec![
+                ("Politics DA", vec![]),
+                ("1NC", vec!["Politics DA".to_string()]),
+                (
+                    "Uniqueness",
+                    vec!["Politics DA".to_string(), "1NC".to_string()]
+                ),
+                ("2NC", vec!["Politics DA".to_string()]),
+                ("Econ DA", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn preview_counts_words_across_all_paragraphs() {
+        let dir = temp_dir("preview-word-count");
+        let path = dir.join("Count.docx");
+        write_docx(
+            &path,
+            &format!(
+                "{}{}{}",
+                heading_xml(1, "Econ DA"),
+                paragraph_xml("Growth is  steady across sectors."),
+                paragraph_xml("")
+            ),
+        );
+
+        let (_, _, word_count) = extract_preview_content(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(word_count, 7);
+    }
 }
@@ -1,13 +1,24 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::time::{Duration, Instant};
 
 use futures::future;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
 use crate::runtime::AppHandle;
 
-use crate::db::{open_database, root_id};
-use crate::lexical;
+use crate::db::{index_meta_dir, open_database, root_id};
+use crate::docx_parse::heading_paths;
+use crate::lexical::{self, LexicalSearchOptions};
+use crate::logging;
 use crate::search::{normalize_for_search, MAX_QUERY_CHARS};
-use crate::types::SearchHit;
+use crate::semantic::root_fingerprint_ms;
+use crate::types::{
+    FileScope, HybridSearchOptions, ParsedParagraph, SearchComplete, SearchHit, SearchResponse,
+    SearchResultBatch, SearchScope,
+};
 use crate::util::{canonicalize_folder, now_ms, path_display};
 use crate::vector::{self, VECTOR_MIN_QUERY_CHARS};
 use crate::CommandResult;
@@ -15,15 +26,43 @@ use crate::CommandResult;
 const DEFAULT_RESULT_LIMIT: usize = 120;
 const CACHE_CAPACITY: usize = 480;
 const CACHE_TTL_MS: i64 = 120_000;
+const CACHE_FLUSH_INTERVAL_MS: i64 = 30_000;
+const QUERY_CACHE_FILE_NAME: &str = "query-cache.json";
 const LEXICAL_SOFT_BUDGET_MS: u64 = 60;
 const HYBRID_SOFT_BUDGET_MS: u64 = 180;
+const DEFAULT_MIN_QUERY_CHARS: usize = 2;
+const ACRONYM_MAX_CHARS: usize = 4;
+pub(crate) const DEFAULT_NEAR_DUPLICATE_THRESHOLD: f64 = 0.8;
+const NEAR_DUPLICATE_SHINGLE_WORDS: usize = 3;
+pub(crate) const SEARCH_RESULT_EVENT: &str = "search-result";
+pub(crate) const SEARCH_COMPLETE_EVENT: &str = "search-complete";
+const QUERY_STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on",
+    "or", "that", "the", "to", "was", "were", "with",
+];
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct CacheEntry {
     created_at_ms: i64,
     results: Vec<SearchHit>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedCacheEntry {
+    key: String,
+    #[serde(flatten)]
+    entry: CacheEntry,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQueryCache {
+    root_fingerprint_ms: i64,
+    entries: Vec<PersistedCacheEntry>,
+}
+
 #[derive(Default)]
 struct QueryCache {
     order: VecDeque<String>,
@@ -57,14 +96,174 @@ impl QueryCache {
             }
         }
     }
+
+    fn snapshot(
+        &self,
+        key_prefix: &str,
+        root_fingerprint_ms: i64,
+        now: i64,
+    ) -> PersistedQueryCache {
+        let entries = self
+            .order
+            .iter()
+            .filter(|key| key.starts_with(key_prefix))
+            .filter_map(|key| {
+                let entry = self.entries.get(key)?;
+                (now - entry.created_at_ms <= CACHE_TTL_MS).then(|| PersistedCacheEntry {
+                    key: key.clone(),
+                    entry: entry.clone(),
+                })
+            })
+            .collect();
+        PersistedQueryCache {
+            root_fingerprint_ms,
+            entries,
+        }
+    }
+
+    fn restore(
+        &mut self,
+        persisted: PersistedQueryCache,
+        root_fingerprint_ms: i64,
+        now: i64,
+    ) -> usize {
+        if persisted.root_fingerprint_ms != root_fingerprint_ms {
+            return 0;
+        }
+        let mut restored = 0;
+        for PersistedCacheEntry { key, entry } in persisted.entries {
+            if now - entry.created_at_ms > CACHE_TTL_MS || self.entries.contains_key(&key) {
+                continue;
+            }
+            self.order.push_back(key.clone());
+            self.entries.insert(key, entry);
+            restored += 1;
+        }
+        while self.order.len() > CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        restored
+    }
 }
 
+static SHORT_QUERIES_ENABLED: AtomicBool = AtomicBool::new(false);
+
 static QUERY_CACHE: std::sync::OnceLock<std::sync::Mutex<QueryCache>> = std::sync::OnceLock::new();
+static LOADED_CACHE_DIRS: std::sync::OnceLock<std::sync::Mutex<HashSet<String>>> =
+    std::sync::OnceLock::new();
+// Zero until the first cached search, which starts the flush clock.
+static LAST_CACHE_FLUSH_MS: AtomicI64 = AtomicI64::new(0);
 
 fn query_cache() -> &'static std::sync::Mutex<QueryCache> {
     QUERY_CACHE.get_or_init(|| std::sync::Mutex::new(QueryCache::default()))
 }
 
+fn cache_data_dir(app: &AppHandle) -> String {
+    app.path()
+        .app_data_dir()
+        .map(|path| path_display(&path))
+        .unwrap_or_default()
+}
+
+fn current_root_fingerprint_ms(app: &AppHandle) -> CommandResult<i64> {
+    let connection = open_database(app)?;
+    root_fingerprint_ms(&connection)
+}
+
+fn load_persisted_query_cache(app: &AppHandle) -> CommandResult<usize> {
+    let path = index_meta_dir(app)?.join(QUERY_CACHE_FILE_NAME);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let raw = fs::read(&path).map_err(|error| {
+        format!(
+            "Could not read query cache '{}': {error}",
+            path_display(&path)
+        )
+    })?;
+    let persisted = serde_json::from_slice::<PersistedQueryCache>(&raw)
+        .map_err(|error| format!("Could not parse query cache: {error}"))?;
+    let fingerprint = current_root_fingerprint_ms(app)?;
+    let mut cache = query_cache()
+        .lock()
+        .map_err(|_| "Could not lock query cache".to_string())?;
+    Ok(cache.restore(persisted, fingerprint, now_ms()))
+}
+
+fn ensure_query_cache_loaded(app: &AppHandle) {
+    let newly_seen = LOADED_CACHE_DIRS
+        .get_or_init(|| std::sync::Mutex::new(HashSet::new()))
+        .lock()
+        .map(|mut loaded| loaded.insert(cache_data_dir(app)))
+        .unwrap_or(false);
+    if !newly_seen {
+        return;
+    }
+    if let Err(error) = load_persisted_query_cache(app) {
+        logging::warn(&format!("Discarding persisted query cache: {error}"));
+    }
+}
+
+fn cached_results(app: &AppHandle, key: &str) -> Option<Vec<SearchHit>> {
+    ensure_query_cache_loaded(app);
+    query_cache().lock().ok()?.get(key)
+}
+
+fn store_cached_results(app: &AppHandle, key: String, results: Vec<SearchHit>) {
+    if let Ok(mut cache) = query_cache().lock() {
+        cache.put(key, results);
+    }
+    let now = now_ms();
+    let last_flush =
+        match LAST_CACHE_FLUSH_MS.compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => now,
+            Err(last_flush) => last_flush,
+        };
+    if now - last_flush < CACHE_FLUSH_INTERVAL_MS {
+        return;
+    }
+    // Only the search that wins the swap schedules the write, off the search path.
+    if LAST_CACHE_FLUSH_MS
+        .compare_exchange(last_flush, now, Ordering::Relaxed, Ordering::Relaxed)
+        .is_err()
+    {
+        return;
+    }
+    let app = app.clone();
+    crate::async_runtime::spawn_blocking(move || {
+        if let Err(error) = write_query_cache(&app, now) {
+            logging::warn(&format!("Could not persist query cache: {error}"));
+        }
+    });
+}
+
+pub(crate) fn flush_query_cache(app: &AppHandle) -> CommandResult<usize> {
+    let now = now_ms();
+    LAST_CACHE_FLUSH_MS.store(now, Ordering::Relaxed);
+    write_query_cache(app, now)
+}
+
+fn write_query_cache(app: &AppHandle, now: i64) -> CommandResult<usize> {
+    let fingerprint = current_root_fingerprint_ms(app)?;
+    let key_prefix = format!("{}|", cache_data_dir(app));
+    let persisted = query_cache()
+        .lock()
+        .map_err(|_| "Could not lock query cache".to_string())?
+        .snapshot(&key_prefix, fingerprint, now);
+    let path = index_meta_dir(app)?.join(QUERY_CACHE_FILE_NAME);
+    let raw = serde_json::to_vec(&persisted)
+        .map_err(|error| format!("Could not serialize query cache: {error}"))?;
+    fs::write(&path, raw).map_err(|error| {
+        format!(
+            "Could not write query cache '{}': {error}",
+            path_display(&path)
+        )
+    })?;
+    Ok(persisted.entries.len())
+}
+
 pub(crate) fn clear_query_cache() {
     if let Ok(mut cache) = query_cache().lock() {
         cache.entries.clear();
@@ -72,42 +271,363 @@ pub(crate) fn clear_query_cache() {
     }
 }
 
-fn normalize_query(query: &str) -> String {
-    query
+pub(crate) fn configure_short_queries(enabled: bool) -> bool {
+    SHORT_QUERIES_ENABLED.store(enabled, Ordering::Relaxed);
+    clear_query_cache();
+    enabled
+}
+
+fn short_queries_enabled() -> bool {
+    SHORT_QUERIES_ENABLED.load(Ordering::Relaxed)
+}
+
+fn is_too_short(query: &str, short_queries: bool) -> bool {
+    let min_query_chars = if short_queries {
+        1
+    } else {
+        DEFAULT_MIN_QUERY_CHARS
+    };
+    query.chars().count() < min_query_chars
+}
+
+fn acronym_term(query: &str, short_queries: bool) -> Option<String> {
+    if !short_queries {
+        return None;
+    }
+    let length = query.chars().count();
+    let is_acronym = (1..=ACRONYM_MAX_CHARS).contains(&length)
+        && query
+            .chars()
+            .all(|character| character.is_uppercase() || character.is_ascii_digit())
+        && query.chars().any(char::is_uppercase);
+    is_acronym.then(|| query.to_lowercase())
+}
+
+fn normalize_query(query: &str, drop_stopwords: bool) -> String {
+    let capped = query
         .trim()
         .chars()
         .take(MAX_QUERY_CHARS)
-        .collect::<String>()
+        .collect::<String>();
+    if !drop_stopwords {
+        return capped;
+    }
+
+    let kept = capped
+        .split_whitespace()
+        .filter(|token| {
+            let word = token
+                .trim_matches(|character: char| !character.is_alphanumeric())
+                .to_lowercase();
+            !QUERY_STOPWORDS.contains(&word.as_str())
+        })
+        .collect::<Vec<&str>>();
+    if kept.is_empty() {
+        return capped;
+    }
+    kept.join(" ")
 }
 
 fn effective_limit(limit: Option<usize>) -> usize {
     limit.unwrap_or(DEFAULT_RESULT_LIMIT).clamp(10, 400)
 }
 
-fn resolve_requested_root_id(
+fn lookup_root_id(connection: &Connection, root_path: String) -> CommandResult<Option<i64>> {
+    let canonical = canonicalize_folder(&root_path)
+        .map(|path| path_display(&path))
+        .unwrap_or(root_path);
+    root_id(connection, &canonical)
+}
+
+fn resolve_requested_root_ids(
     app: &AppHandle,
     root_path: Option<String>,
-) -> CommandResult<Option<i64>> {
-    let Some(root_path) = root_path else {
+    root_paths: Option<Vec<String>>,
+) -> CommandResult<Vec<i64>> {
+    if root_path.is_none() && root_paths.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let connection = open_database(app)?;
+    let mut root_ids = Vec::new();
+    for path in root_path
+        .into_iter()
+        .chain(root_paths.into_iter().flatten())
+    {
+        if let Some(root_id) = lookup_root_id(&connection, path)? {
+            root_ids.push(root_id);
+        }
+    }
+    root_ids.sort_unstable();
+    root_ids.dedup();
+    Ok(root_ids)
+}
+
+fn resolve_file_scope(
+    app: &AppHandle,
+    scope: SearchScope,
+    requested_root_ids: &[i64],
+) -> CommandResult<Option<FileScope>> {
+    if scope == SearchScope::All {
         return Ok(None);
-    };
+    }
 
-    let canonical = canonicalize_folder(&root_path)
-        .map(|path| path_display(&path))
-        .unwrap_or(root_path);
     let connection = open_database(app)?;
-    root_id(&connection, &canonical)
+    let mut statement = connection
+        .prepare("SELECT id, root_id FROM files WHERE is_capture = 1")
+        .map_err(|error| format!("Could not prepare capture file query: {error}"))?;
+    let rows = statement
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+        .map_err(|error| format!("Could not query capture files: {error}"))?;
+    let mut file_ids = Vec::new();
+    for row in rows {
+        let (file_id, root_id) =
+            row.map_err(|error| format!("Could not parse capture file row: {error}"))?;
+        if requested_root_ids.is_empty() || requested_root_ids.contains(&root_id) {
+            file_ids.push(file_id);
+        }
+    }
+
+    Ok(Some(FileScope {
+        file_ids,
+        include: scope == SearchScope::Captures,
+    }))
 }
 
-fn cache_key(mode: &str, query: &str, root_id: Option<i64>, limit: usize) -> String {
+// Every parameter that can change the result set belongs here, so each distinct
+// combination gets its own cache entry.
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchCacheParams {
+    mode: &'static str,
+    query: String,
+    root_ids: Vec<i64>,
+    limit: usize,
+    explain: bool,
+    file_name_boost: f64,
+    semantic_fallback_only: bool,
+    near_duplicate_threshold: Option<f64>,
+    argument_type: Option<String>,
+    scope: SearchScope,
+}
+
+// Like normalize_for_search, but keeps `*` so wildcard queries do not share
+// an entry with their literal form.
+fn cache_query_text(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|token| {
+            token
+                .split('*')
+                .map(normalize_for_search)
+                .collect::<Vec<String>>()
+                .join("*")
+        })
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+impl SearchCacheParams {
+    fn new(mode: &'static str, query: &str, root_ids: &[i64], limit: usize) -> Self {
+        let mut root_ids = root_ids.to_vec();
+        root_ids.sort_unstable();
+        root_ids.dedup();
+        Self {
+            mode,
+            query: cache_query_text(query),
+            root_ids,
+            limit,
+            ..Self::default()
+        }
+    }
+}
+
+fn cache_key(app: &AppHandle, params: &SearchCacheParams) -> String {
+    let canonical = serde_json::to_string(params).unwrap_or_default();
     format!(
-        "{mode}|{}|{}|{}",
-        normalize_for_search(query),
-        root_id.unwrap_or(0),
-        limit
+        "{}|{}",
+        cache_data_dir(app),
+        blake3::hash(canonical.as_bytes()).to_hex()
     )
 }
 
+fn emit_search_batch(
+    app: &AppHandle,
+    stream_request_id: Option<&str>,
+    source: &str,
+    hits: &[SearchHit],
+) {
+    let Some(request_id) = stream_request_id else {
+        return;
+    };
+    let _ = app.emit(
+        SEARCH_RESULT_EVENT,
+        SearchResultBatch {
+            request_id: request_id.to_string(),
+            source: source.to_string(),
+            hits: hits.to_vec(),
+        },
+    );
+}
+
+fn load_heading_paths(
+    connection: &Connection,
+    file_ids: &BTreeSet<i64>,
+) -> CommandResult<HashMap<i64, HashMap<i64, Vec<String>>>> {
+    let id_list = file_ids
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT file_id, heading_order, level, text FROM headings
+             WHERE file_id IN ({id_list})
+             ORDER BY file_id ASC, heading_order ASC"
+        ))
+        .map_err(|error| format!("Could not prepare heading path query: {error}"))?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .map_err(|error| format!("Could not run heading path query: {error}"))?;
+
+    let mut paragraphs_by_file = HashMap::<i64, Vec<ParsedParagraph>>::new();
+    for row in rows {
+        let (file_id, order, level, text) =
+            row.map_err(|error| format!("Could not read heading path row: {error}"))?;
+        paragraphs_by_file
+            .entry(file_id)
+            .or_default()
+            .push(ParsedParagraph {
+                order,
+                text,
+                heading_level: Some(level),
+                style_label: None,
+                is_f8_cite: false,
+            });
+    }
+
+    Ok(paragraphs_by_file
+        .into_iter()
+        .map(|(file_id, paragraphs)| (file_id, heading_paths(&paragraphs)))
+        .collect())
+}
+
+fn attach_heading_paths(app: &AppHandle, hits: &mut [SearchHit]) {
+    let file_ids = hits
+        .iter()
+        .filter(|hit| hit.heading_order.is_some())
+        .filter(|hit| {
+            matches!(
+                hit.kind.as_str(),
+                "heading" | "chunk" | "paragraph" | "comment"
+            )
+        })
+        .map(|hit| hit.file_id)
+        .collect::<BTreeSet<i64>>();
+    if file_ids.is_empty() {
+        return;
+    }
+    let Ok(connection) = open_database(app) else {
+        return;
+    };
+    let paths = match load_heading_paths(&connection, &file_ids) {
+        Ok(paths) => paths,
+        Err(error) => {
+            logging::warn(&format!("Could not load heading paths: {error}"));
+            return;
+        }
+    };
+
+    for hit in hits.iter_mut() {
+        let Some(heading_order) = hit.heading_order else {
+            continue;
+        };
+        let ancestors = paths
+            .get(&hit.file_id)
+            .and_then(|file_paths| file_paths.get(&heading_order))
+            .cloned()
+            .unwrap_or_default();
+        hit.heading_path = match hit.kind.as_str() {
+            "heading" => Some(ancestors),
+            "chunk" | "paragraph" | "comment" => {
+                let mut path = ancestors;
+                path.extend(hit.heading_text.clone());
+                Some(path)
+            }
+            _ => None,
+        };
+    }
+}
+
+fn load_argument_types(
+    connection: &Connection,
+    file_ids: &BTreeSet<i64>,
+) -> CommandResult<HashMap<(i64, i64), String>> {
+    let id_list = file_ids
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT file_id, heading_order, argument_type FROM headings
+             WHERE file_id IN ({id_list}) AND argument_type IS NOT NULL"
+        ))
+        .map_err(|error| format!("Could not prepare argument type query: {error}"))?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|error| format!("Could not run argument type query: {error}"))?;
+
+    let mut types = HashMap::new();
+    for row in rows {
+        let (file_id, heading_order, argument_type) =
+            row.map_err(|error| format!("Could not read argument type row: {error}"))?;
+        types.insert((file_id, heading_order), argument_type);
+    }
+    Ok(types)
+}
+
+fn attach_argument_types(app: &AppHandle, hits: &mut [SearchHit]) {
+    let file_ids = hits
+        .iter()
+        .filter(|hit| hit.argument_type.is_none() && hit.heading_order.is_some())
+        .map(|hit| hit.file_id)
+        .collect::<BTreeSet<i64>>();
+    if file_ids.is_empty() {
+        return;
+    }
+    let Ok(connection) = open_database(app) else {
+        return;
+    };
+    let types = match load_argument_types(&connection, &file_ids) {
+        Ok(types) => types,
+        Err(error) => {
+            logging::warn(&format!("Could not load argument types: {error}"));
+            return;
+        }
+    };
+
+    for hit in hits.iter_mut().filter(|hit| hit.argument_type.is_none()) {
+        if let Some(heading_order) = hit.heading_order {
+            hit.argument_type = types.get(&(hit.file_id, heading_order)).cloned();
+        }
+    }
+}
+
 fn dedupe_key(hit: &SearchHit) -> String {
     format!(
         "{}:{}:{}:{}:{}",
@@ -122,21 +642,82 @@ fn dedupe_key(hit: &SearchHit) -> String {
 async fn run_lexical_search_task(
     app: AppHandle,
     query: String,
-    requested_root_id: Option<i64>,
-    limit: usize,
-    file_name_only: bool,
+    options: LexicalSearchOptions,
 ) -> CommandResult<Vec<SearchHit>> {
     crate::async_runtime::spawn_blocking(move || {
-        lexical::search(&app, &query, requested_root_id, limit, file_name_only)
+        let mut hits = match acronym_term(&query, short_queries_enabled()) {
+            Some(term) => lexical::search_exact_term(&app, &term, &options)?,
+            None => lexical::search(&app, &query, &options)?,
+        };
+        attach_heading_paths(&app, &mut hits);
+        Ok(hits)
     })
     .await
     .map_err(|error| format!("Lexical search task failed: {error}"))?
 }
 
+fn text_shingles(text: &str) -> HashSet<String> {
+    let normalized = normalize_for_search(text);
+    let words = normalized.split_whitespace().collect::<Vec<&str>>();
+    if words.len() <= NEAR_DUPLICATE_SHINGLE_WORDS {
+        return HashSet::from([words.join(" ")]);
+    }
+    words
+        .windows(NEAR_DUPLICATE_SHINGLE_WORDS)
+        .map(|window| window.join(" "))
+        .collect()
+}
+
+fn jaccard(left: &HashSet<String>, right: &HashSet<String>) -> f64 {
+    let union = left.union(right).count();
+    if union == 0 {
+        return 0.0;
+    }
+    left.intersection(right).count() as f64 / union as f64
+}
+
+// Hits must already be sorted best-first; each kept hit absorbs later hits
+// whose text shingles overlap it by at least `threshold`.
+fn collapse_near_duplicates(hits: Vec<SearchHit>, threshold: f64) -> Vec<SearchHit> {
+    let mut kept = Vec::<(HashSet<String>, SearchHit)>::with_capacity(hits.len());
+    for hit in hits {
+        let Some(text) = hit
+            .heading_text
+            .as_deref()
+            .filter(|text| !text.trim().is_empty())
+        else {
+            kept.push((HashSet::new(), hit));
+            continue;
+        };
+        let shingles = text_shingles(text);
+        if kept
+            .iter()
+            .any(|(existing, _)| jaccard(existing, &shingles) >= threshold)
+        {
+            continue;
+        }
+        kept.push((shingles, hit));
+    }
+    kept.into_iter().map(|(_, hit)| hit).collect()
+}
+
+fn near_duplicate_threshold(collapse: bool, threshold: Option<f64>) -> Option<f64> {
+    if !collapse {
+        return None;
+    }
+    Some(
+        threshold
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .map(|value| value.min(1.0))
+            .unwrap_or(DEFAULT_NEAR_DUPLICATE_THRESHOLD),
+    )
+}
+
 fn fuse_rrf(
     lexical_hits: &[SearchHit],
     semantic_hits: &[SearchHit],
     limit: usize,
+    near_duplicate_threshold: Option<f64>,
 ) -> Vec<SearchHit> {
     let mut scores = HashMap::<String, f64>::new();
     let mut by_key = HashMap::<String, SearchHit>::new();
@@ -197,6 +778,9 @@ fn fuse_rrf(
             )
             .then(left.kind.cmp(&right.kind))
     });
+    if let Some(threshold) = near_duplicate_threshold {
+        ranked = collapse_near_duplicates(ranked, threshold);
+    }
     ranked.truncate(limit);
     ranked
 }
@@ -208,35 +792,39 @@ pub(crate) fn search_lexical(
     limit: Option<usize>,
 ) -> CommandResult<Vec<SearchHit>> {
     let started = Instant::now();
-    let capped_query = normalize_query(query);
+    let capped_query = normalize_query(query, false);
     let cleaned_query = capped_query.trim();
-    if cleaned_query.len() < 2 {
+    if is_too_short(cleaned_query, short_queries_enabled()) {
         return Ok(Vec::new());
     }
     if normalize_for_search(cleaned_query).is_empty() {
         return Ok(Vec::new());
     }
 
-    let requested_root_id = resolve_requested_root_id(app, root_path)?;
+    let requested_root_ids = resolve_requested_root_ids(app, root_path, None)?;
     let limit = effective_limit(limit);
-    let key = cache_key("lexical", cleaned_query, requested_root_id, limit);
-    if let Ok(cache) = query_cache().lock() {
-        if let Some(cached) = cache.get(&key) {
-            return Ok(cached);
-        }
+    let key = cache_key(
+        app,
+        &SearchCacheParams::new("lexical", cleaned_query, &requested_root_ids, limit),
+    );
+    if let Some(cached) = cached_results(app, &key) {
+        return Ok(cached);
     }
 
-    let results = lexical::search(app, cleaned_query, requested_root_id, limit, false)?;
-    if let Ok(mut cache) = query_cache().lock() {
-        cache.put(key, results.clone());
-    }
+    let options = LexicalSearchOptions::new(&requested_root_ids, limit);
+    let mut results = match acronym_term(cleaned_query, short_queries_enabled()) {
+        Some(term) => lexical::search_exact_term(app, &term, &options)?,
+        None => lexical::search(app, cleaned_query, &options)?,
+    };
+    attach_heading_paths(app, &mut results);
+    store_cached_results(app, key, results.clone());
 
     if started.elapsed().as_millis() > u128::from(LEXICAL_SOFT_BUDGET_MS) {
-        eprintln!(
+        logging::warn(&format!(
             "Lexical query over budget ({}ms): '{}'",
             started.elapsed().as_millis(),
             normalize_for_search(cleaned_query)
-        );
+        ));
     }
 
     Ok(results)
@@ -248,7 +836,7 @@ pub(crate) async fn search_semantic(
     root_path: Option<String>,
     limit: Option<usize>,
 ) -> CommandResult<Vec<SearchHit>> {
-    let capped_query = normalize_query(query);
+    let capped_query = normalize_query(query, false);
     let cleaned_query = capped_query.trim();
     if cleaned_query.chars().count() < VECTOR_MIN_QUERY_CHARS {
         return Ok(Vec::new());
@@ -257,37 +845,166 @@ pub(crate) async fn search_semantic(
         return Ok(Vec::new());
     }
 
-    let requested_root_id = resolve_requested_root_id(app, root_path)?;
+    let requested_root_ids = resolve_requested_root_ids(app, root_path, None)?;
     vector::trigger_rebuild(app.clone(), false);
-    vector::search(
+    let mut hits = vector::search(
         app,
         cleaned_query,
-        requested_root_id,
+        &requested_root_ids,
         effective_limit(limit),
     )
-    .await
+    .await?;
+    attach_heading_paths(app, &mut hits);
+    attach_argument_types(app, &mut hits);
+    Ok(hits)
 }
 
-pub(crate) async fn search_hybrid(
+pub(crate) fn count_matches(
     app: &AppHandle,
     query: &str,
     root_path: Option<String>,
+) -> CommandResult<usize> {
+    let capped_query = normalize_query(query, false);
+    let cleaned_query = capped_query.trim();
+    if is_too_short(cleaned_query, short_queries_enabled()) {
+        return Ok(0);
+    }
+
+    let requested_root_ids = resolve_requested_root_ids(app, root_path, None)?;
+    lexical::count_matches(app, cleaned_query, &requested_root_ids)
+}
+
+pub(crate) async fn find_similar(
+    app: &AppHandle,
+    file_id: i64,
+    heading_order: i64,
     limit: Option<usize>,
-    file_name_only: bool,
-    semantic_enabled: bool,
 ) -> CommandResult<Vec<SearchHit>> {
+    vector::find_similar(app, file_id, heading_order, effective_limit(limit)).await
+}
+
+async fn semantic_within_timeout<F>(
+    semantic: F,
+    timeout_ms: Option<u64>,
+) -> (CommandResult<Vec<SearchHit>>, bool)
+where
+    F: Future<Output = CommandResult<Vec<SearchHit>>>,
+{
+    let Some(timeout_ms) = timeout_ms else {
+        return (semantic.await, false);
+    };
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), semantic).await {
+        Ok(result) => (result, false),
+        Err(_) => (Ok(Vec::new()), true),
+    }
+}
+
+// Runs the semantic half only when lexical returned fewer than half the
+// requested hits, so well-answered queries skip the embedding model.
+async fn semantic_fallback<F, Fut>(
+    lexical_count: usize,
+    limit: usize,
+    semantic: F,
+) -> Option<Fut::Output>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future,
+{
+    if lexical_count >= limit / 2 {
+        return None;
+    }
+    Some(semantic().await)
+}
+
+pub(crate) async fn search_hybrid(
+    app: &AppHandle,
+    query: &str,
+    options: &HybridSearchOptions,
+) -> CommandResult<SearchResponse> {
+    let started = Instant::now();
+    let result = run_hybrid_search(app, query, options, |query, root_ids, limit| {
+        let app = app.clone();
+        async move { vector::search(&app, &query, &root_ids, limit).await }
+    })
+    .await;
+
+    if let Some(request_id) = options.stream_request_id() {
+        let (total_hits, error, semantic_degraded) = match &result {
+            Ok(response) => (response.hits.len(), None, response.semantic_degraded),
+            Err(error) => (0, Some(error.clone()), false),
+        };
+        let _ = app.emit(
+            SEARCH_COMPLETE_EVENT,
+            SearchComplete {
+                request_id: request_id.to_string(),
+                total_hits,
+                elapsed_ms: i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX),
+                error,
+                semantic_degraded,
+            },
+        );
+    }
+
+    result
+}
+
+// `semantic` runs the vector half; tests swap in a stub to exercise degraded results.
+async fn run_hybrid_search<S, Fut>(
+    app: &AppHandle,
+    query: &str,
+    options: &HybridSearchOptions,
+    semantic: S,
+) -> CommandResult<SearchResponse>
+where
+    S: FnOnce(String, Vec<i64>, usize) -> Fut,
+    Fut: Future<Output = CommandResult<Vec<SearchHit>>>,
+{
     let started = Instant::now();
-    let capped_query = normalize_query(query);
+    let file_name_only = options.file_name_only.unwrap_or(false);
+    let file_name_boost = lexical::file_name_boost(options.file_name_boost);
+    let semantic_fallback_only = options.semantic_fallback_only.unwrap_or(false);
+    let near_duplicate_threshold = near_duplicate_threshold(
+        options.collapse_near_duplicates.unwrap_or(false),
+        options.near_duplicate_threshold,
+    );
+    let explain = options.explain.unwrap_or(false);
+    let argument_type = options.argument_type.clone();
+    let scope = options.scope.unwrap_or_default();
+    let timeout_ms = options.timeout_ms.filter(|value| *value > 0);
+    let stream_request_id = options.stream_request_id();
+
+    let capped_query = normalize_query(query, options.drop_stopwords.unwrap_or(false));
     let cleaned_query = capped_query.trim();
-    if cleaned_query.len() < 2 {
-        return Ok(Vec::new());
+    let short_queries = short_queries_enabled();
+    if is_too_short(cleaned_query, short_queries) {
+        return Ok(SearchResponse::default());
     }
+    let semantic_enabled = options.semantic_enabled.unwrap_or(true)
+        && vector::is_enabled()
+        && acronym_term(cleaned_query, short_queries).is_none();
     if normalize_for_search(cleaned_query).is_empty() {
-        return Ok(Vec::new());
+        return Ok(SearchResponse::default());
     }
 
-    let requested_root_id = resolve_requested_root_id(app, root_path)?;
-    let limit = effective_limit(limit);
+    let requested_specific_roots = options
+        .root_paths
+        .as_ref()
+        .is_some_and(|paths| !paths.is_empty());
+    let requested_root_ids =
+        resolve_requested_root_ids(app, options.root_path.clone(), options.root_paths.clone())?;
+    if requested_specific_roots && requested_root_ids.is_empty() {
+        return Ok(SearchResponse::default());
+    }
+    let file_scope = resolve_file_scope(app, scope, &requested_root_ids)?;
+    let limit = effective_limit(options.limit);
+    let lexical_options = LexicalSearchOptions {
+        argument_type: argument_type.clone(),
+        file_scope: file_scope.clone(),
+        file_name_only,
+        file_name_boost,
+        explain,
+        ..LexicalSearchOptions::new(&requested_root_ids, limit)
+    };
     let mode_key = if file_name_only {
         "hybrid_file_name_only"
     } else if semantic_enabled {
@@ -295,70 +1012,458 @@ pub(crate) async fn search_hybrid(
     } else {
         "lexical_only"
     };
-    let key = cache_key(mode_key, cleaned_query, requested_root_id, limit);
-    if let Ok(cache) = query_cache().lock() {
-        if let Some(cached) = cache.get(&key) {
-            return Ok(cached);
-        }
+    let key = cache_key(
+        app,
+        &SearchCacheParams {
+            explain,
+            file_name_boost,
+            semantic_fallback_only,
+            near_duplicate_threshold,
+            argument_type: argument_type.clone(),
+            scope,
+            ..SearchCacheParams::new(mode_key, cleaned_query, &requested_root_ids, limit)
+        },
+    );
+    if let Some(cached) = cached_results(app, &key) {
+        emit_search_batch(app, stream_request_id, "cache", &cached);
+        return Ok(SearchResponse {
+            hits: cached,
+            semantic_degraded: false,
+        });
     }
 
     if file_name_only {
-        let lexical_hits = run_lexical_search_task(
-            app.clone(),
-            cleaned_query.to_string(),
-            requested_root_id,
-            limit,
-            true,
-        )
-        .await?;
-        if let Ok(mut cache) = query_cache().lock() {
-            cache.put(key, lexical_hits.clone());
-        }
-        return Ok(lexical_hits);
+        let lexical_hits =
+            run_lexical_search_task(app.clone(), cleaned_query.to_string(), lexical_options)
+                .await?;
+        emit_search_batch(app, stream_request_id, "lexical", &lexical_hits);
+        store_cached_results(app, key, lexical_hits.clone());
+        return Ok(SearchResponse {
+            hits: lexical_hits,
+            semantic_degraded: false,
+        });
     }
 
     if !semantic_enabled {
-        let lexical_hits = run_lexical_search_task(
-            app.clone(),
-            cleaned_query.to_string(),
-            requested_root_id,
-            limit,
-            false,
-        )
-        .await?;
-        if let Ok(mut cache) = query_cache().lock() {
-            cache.put(key, lexical_hits.clone());
-        }
-        return Ok(lexical_hits);
+        let lexical_hits =
+            run_lexical_search_task(app.clone(), cleaned_query.to_string(), lexical_options)
+                .await?;
+        let lexical_hits = match near_duplicate_threshold {
+            Some(threshold) => collapse_near_duplicates(lexical_hits, threshold),
+            None => lexical_hits,
+        };
+        emit_search_batch(app, stream_request_id, "lexical", &lexical_hits);
+        store_cached_results(app, key, lexical_hits.clone());
+        return Ok(SearchResponse {
+            hits: lexical_hits,
+            semantic_degraded: false,
+        });
     }
 
     vector::trigger_rebuild(app.clone(), false);
 
-    let lexical_task = run_lexical_search_task(
-        app.clone(),
-        cleaned_query.to_string(),
-        requested_root_id,
+    let lexical_task = async {
+        let lexical_hits =
+            run_lexical_search_task(app.clone(), cleaned_query.to_string(), lexical_options).await;
+        if let Ok(hits) = &lexical_hits {
+            emit_search_batch(app, stream_request_id, "lexical", hits);
+        }
+        lexical_hits
+    };
+    let semantic_task = || {
+        semantic_within_timeout(
+            semantic(cleaned_query.to_string(), requested_root_ids.clone(), limit),
+            timeout_ms,
+        )
+    };
+    let (lexical_hits, semantic_result, semantic_timed_out) = if semantic_fallback_only {
+        let lexical_hits = lexical_task.await?;
+        let Some((semantic_result, semantic_timed_out)) =
+            semantic_fallback(lexical_hits.len(), limit, semantic_task).await
+        else {
+            let lexical_hits = match near_duplicate_threshold {
+                Some(threshold) => collapse_near_duplicates(lexical_hits, threshold),
+                None => lexical_hits,
+            };
+            store_cached_results(app, key, lexical_hits.clone());
+            return Ok(SearchResponse {
+                hits: lexical_hits,
+                semantic_degraded: false,
+            });
+        };
+        (lexical_hits, semantic_result, semantic_timed_out)
+    } else {
+        let (lexical_result, (semantic_result, semantic_timed_out)) =
+            future::join(lexical_task, semantic_task()).await;
+        (lexical_result?, semantic_result, semantic_timed_out)
+    };
+    if semantic_timed_out {
+        logging::warn(&format!(
+            "Semantic search timed out after {}ms; returning lexical results: '{}'",
+            timeout_ms.unwrap_or_default(),
+            normalize_for_search(cleaned_query)
+        ));
+    }
+    let (mut semantic_hits, semantic_degraded) =
+        semantic_hits_or_degraded(semantic_result, cleaned_query);
+    attach_heading_paths(app, &mut semantic_hits);
+    attach_argument_types(app, &mut semantic_hits);
+    if let Some(argument_type) = &argument_type {
+        semantic_hits.retain(|hit| hit.argument_type.as_deref() == Some(argument_type.as_str()));
+    }
+    if let Some(file_scope) = &file_scope {
+        semantic_hits
+            .retain(|hit| file_scope.file_ids.contains(&hit.file_id) == file_scope.include);
+    }
+    let fused = fuse_rrf(
+        &lexical_hits,
+        &semantic_hits,
         limit,
-        false,
+        near_duplicate_threshold,
     );
-    let semantic_task = vector::search(app, cleaned_query, requested_root_id, limit);
-    let (lexical_result, semantic_result) = future::join(lexical_task, semantic_task).await;
+    emit_search_batch(app, stream_request_id, "hybrid", &fused);
 
-    let lexical_hits = lexical_result?;
-    let semantic_hits = semantic_result.unwrap_or_default();
-    let fused = fuse_rrf(&lexical_hits, &semantic_hits, limit);
-
-    if let Ok(mut cache) = query_cache().lock() {
-        cache.put(key, fused.clone());
+    // A timed-out or degraded result is missing its semantic half, so it is not worth caching.
+    if !semantic_timed_out && !semantic_degraded {
+        store_cached_results(app, key, fused.clone());
     }
 
     if started.elapsed() > Duration::from_millis(HYBRID_SOFT_BUDGET_MS) {
-        eprintln!(
+        logging::warn(&format!(
             "Hybrid query over budget ({}ms): '{}'",
             started.elapsed().as_millis(),
             normalize_for_search(cleaned_query)
+        ));
+    }
+
+    Ok(SearchResponse {
+        hits: fused,
+        semantic_degraded,
+    })
+}
+
+// A failed vector search falls back to lexical-only results; the flag lets the UI say so.
+fn semantic_hits_or_degraded(
+    semantic_result: CommandResult<Vec<SearchHit>>,
+    query: &str,
+) -> (Vec<SearchHit>, bool) {
+    match semantic_result {
+        Ok(hits) => (hits, false),
+        Err(error) => {
+            logging::warn(&format!(
+                "Semantic search failed; returning lexical results: '{}': {error}",
+                normalize_for_search(query)
+            ));
+            (Vec::new(), true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    use futures::future;
+
+    use super::{
+        acronym_term, cache_key, fuse_rrf, is_too_short, near_duplicate_threshold, normalize_query,
+        run_hybrid_search, search_hybrid, semantic_fallback, semantic_within_timeout,
+        PersistedQueryCache, QueryCache, SearchCacheParams, CACHE_TTL_MS, SEARCH_COMPLETE_EVENT,
+        SEARCH_RESULT_EVENT,
+    };
+    use crate::db::open_database;
+    use crate::indexer::rebuild_lexical_index;
+    use crate::runtime::set_event_callback;
+    use crate::test_support::{insert_file_row, test_app, GLOBAL_SETTINGS_LOCK};
+    use crate::types::{HybridSearchOptions, SearchHit, SearchScope};
+    use crate::util::now_ms;
+
+    static STREAMED_EVENTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+    extern "C" fn capture_event(event: *const c_char, payload: *const c_char) {
+        let event = unsafe { CStr::from_ptr(event) }
+            .to_string_lossy()
+            .into_owned();
+        let payload = unsafe { CStr::from_ptr(payload) }
+            .to_string_lossy()
+            .into_owned();
+        STREAMED_EVENTS.lock().unwrap().push((event, payload));
+    }
+
+    #[test]
+    fn streaming_hybrid_search_emits_batches_then_completion() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("stream-search");
+        set_event_callback(Some(capture_event));
+        let options = HybridSearchOptions {
+            limit: Some(20),
+            stream: Some(true),
+            request_id: Some("stream-test-1".to_string()),
+            ..HybridSearchOptions::default()
+        };
+        let result =
+            crate::async_runtime::block_on(search_hybrid(&app, "midterms elections", &options));
+        set_event_callback(None);
+        assert!(result.is_ok());
+
+        let events = STREAMED_EVENTS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, payload)| payload.contains("\"requestId\":\"stream-test-1\""))
+            .map(|(event, _)| event.clone())
+            .collect::<Vec<String>>();
+        assert!(events.len() >= 2);
+        assert_eq!(
+            events.first().map(String::as_str),
+            Some(SEARCH_RESULT_EVENT)
+        );
+        assert_eq!(
+            events.last().map(String::as_str),
+            Some(SEARCH_COMPLETE_EVENT)
+        );
+    }
+
+    #[test]
+    fn stopword_filtering_is_opt_in_and_never_empties_query() {
+        assert_eq!(
+            normalize_query("the solvency of the plan", true),
+            "solvency plan"
+        );
+        assert_eq!(
+            normalize_query("the solvency of the plan", false),
+            "the solvency of the plan"
+        );
+        assert_eq!(normalize_query("The Of", true), "The Of");
+    }
+
+    #[test]
+    fn slow_semantic_times_out_to_lexical_results() {
+        let lexical_hit = SearchHit {
+            source: "lexical".to_string(),
+            kind: "heading".to_string(),
+            file_id: 1,
+            file_name: "Aff.docx".to_string(),
+            relative_path: "Aff.docx".to_string(),
+            absolute_path: "/debate/Aff.docx".to_string(),
+            heading_level: Some(1),
+            heading_text: Some("Midterms DA".to_string()),
+            heading_order: Some(1),
+            score: 1.0,
+            explanation: None,
+            content_hash: None,
+            heading_path: None,
+            argument_type: None,
+        };
+        let slow_semantic = async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(vec![lexical_hit.clone()])
+        };
+
+        let started = Instant::now();
+        let (lexical_result, (semantic_result, timed_out)) =
+            crate::async_runtime::block_on(future::join(
+                async { Ok::<_, String>(vec![lexical_hit.clone()]) },
+                semantic_within_timeout(slow_semantic, Some(50)),
+            ));
+        let fused = fuse_rrf(
+            &lexical_result.unwrap(),
+            &semantic_result.unwrap(),
+            10,
+            None,
         );
+
+        assert!(timed_out);
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].heading_text.as_deref(), Some("Midterms DA"));
+    }
+
+    #[test]
+    fn failing_semantic_search_degrades_to_lexical_results() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("degraded-search");
+        let connection = open_database(&app).unwrap();
+        let (_, file_id) = insert_file_row(&connection, "/degraded", "Aff.docx");
+        connection
+            .execute_batch(&format!(
+                "
+                INSERT INTO headings(file_id, heading_order, level, text, normalized, file_name, relative_path)
+                VALUES({file_id}, 1, 1, 'Midterms DA', 'midterms da', 'Aff.docx', 'Aff.docx');
+                "
+            ))
+            .unwrap();
+        rebuild_lexical_index(&app).unwrap();
+
+        let response = crate::async_runtime::block_on(run_hybrid_search(
+            &app,
+            "midterms",
+            &HybridSearchOptions::default(),
+            |_, _, _| async { Err::<Vec<SearchHit>, String>("vector store offline".to_string()) },
+        ))
+        .unwrap();
+
+        assert!(response.semantic_degraded);
+        assert!(response
+            .hits
+            .iter()
+            .any(|hit| hit.heading_text.as_deref() == Some("Midterms DA")));
+    }
+
+    #[test]
+    fn semantic_fallback_skips_semantic_when_lexical_is_sufficient() {
+        let semantic_calls = AtomicUsize::new(0);
+        let semantic = || {
+            semantic_calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<Vec<SearchHit>, String>(Vec::new()) }
+        };
+
+        let skipped = crate::async_runtime::block_on(semantic_fallback(12, 20, semantic));
+        assert!(skipped.is_none());
+        assert_eq!(semantic_calls.load(Ordering::SeqCst), 0);
+
+        let ran = crate::async_runtime::block_on(semantic_fallback(3, 20, semantic));
+        assert!(ran.is_some());
+        assert_eq!(semantic_calls.load(Ordering::SeqCst), 1);
     }
 
-    Ok(fused)
+    #[test]
+    fn near_identical_hits_collapse_into_best_scored() {
+        let hit = |file_id: i64, text: &str| SearchHit {
+            source: "lexical".to_string(),
+            kind: "heading".to_string(),
+            file_id,
+            file_name: format!("File{file_id}.docx"),
+            relative_path: format!("File{file_id}.docx"),
+            absolute_path: format!("/debate/File{file_id}.docx"),
+            heading_level: Some(4),
+            heading_text: Some(text.to_string()),
+            heading_order: Some(1),
+            score: file_id as f64,
+            explanation: None,
+            content_hash: None,
+            heading_path: None,
+            argument_type: None,
+        };
+        let lexical = vec![
+            hit(
+                1,
+                "Midterms DA - Republicans win the House and block the plan funding next session",
+            ),
+            hit(
+                2,
+                "Midterms DA: Republicans win the House and block the plan funding next session now.",
+            ),
+            hit(3, "Econ DA - growth is steady"),
+        ];
+
+        let plain = fuse_rrf(&lexical, &[], 10, None);
+        let collapsed = fuse_rrf(&lexical, &[], 10, near_duplicate_threshold(true, None));
+
+        assert_eq!(plain.len(), 3);
+        assert_eq!(collapsed.len(), 2);
+        assert_eq!(collapsed[0].file_id, 1);
+        assert_eq!(collapsed[1].file_id, 3);
+        assert_eq!(near_duplicate_threshold(false, Some(0.5)), None);
+    }
+
+    #[test]
+    fn short_query_override_allows_single_chars_and_acronyms() {
+        assert!(is_too_short("A", false));
+        assert_eq!(acronym_term("CP", false), None);
+
+        assert!(!is_too_short("A", true));
+        assert_eq!(
+            ["CP", "DA", "Cp", "COVID19", "K2"].map(|query| acronym_term(query, true)),
+            [
+                Some("cp".to_string()),
+                Some("da".to_string()),
+                None,
+                None,
+                Some("k2".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn query_cache_round_trips_through_serialization() {
+        let hit = SearchHit {
+            source: "lexical".to_string(),
+            kind: "heading".to_string(),
+            file_id: 7,
+            file_name: "Econ DA.docx".to_string(),
+            relative_path: "Neg/Econ DA.docx".to_string(),
+            absolute_path: "/debate/Neg/Econ DA.docx".to_string(),
+            heading_level: Some(2),
+            heading_text: Some("Uniqueness".to_string()),
+            heading_order: Some(3),
+            score: 4.5,
+            explanation: None,
+            content_hash: None,
+            heading_path: Some(vec!["Econ DA".to_string()]),
+            argument_type: Some("card".to_string()),
+        };
+        let mut cache = QueryCache::default();
+        cache.put("/data|hybrid|uniqueness|0|120".to_string(), vec![hit]);
+        cache.put("/other|hybrid|uniqueness|0|120".to_string(), Vec::new());
+        let now = now_ms();
+
+        let raw = serde_json::to_vec(&cache.snapshot("/data|", 42, now)).unwrap();
+        let persisted = || serde_json::from_slice::<PersistedQueryCache>(&raw).unwrap();
+
+        let mut restored = QueryCache::default();
+        assert_eq!(restored.restore(persisted(), 42, now), 1);
+        let hits = restored.get("/data|hybrid|uniqueness|0|120").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].heading_text.as_deref(), Some("Uniqueness"));
+        assert_eq!(hits[0].heading_path, Some(vec!["Econ DA".to_string()]));
+        assert!(restored.get("/other|hybrid|uniqueness|0|120").is_none());
+
+        assert_eq!(QueryCache::default().restore(persisted(), 43, now), 0);
+        assert_eq!(
+            QueryCache::default().restore(persisted(), 42, now + CACHE_TTL_MS + 1),
+            0
+        );
+    }
+
+    #[test]
+    fn searches_differing_by_a_filter_use_separate_cache_keys() {
+        let app = test_app("cache-key");
+        let base = || SearchCacheParams::new("hybrid", "Midterms  Link", &[3, 1], 20);
+        let plain = cache_key(&app, &base());
+
+        assert_eq!(
+            plain,
+            cache_key(
+                &app,
+                &SearchCacheParams::new("hybrid", "midterms link", &[1, 3, 3], 20)
+            )
+        );
+        for filtered in [
+            SearchCacheParams {
+                argument_type: Some("card".to_string()),
+                ..base()
+            },
+            SearchCacheParams {
+                scope: SearchScope::Captures,
+                ..base()
+            },
+            SearchCacheParams {
+                explain: true,
+                ..base()
+            },
+            SearchCacheParams {
+                file_name_boost: 2.0,
+                ..base()
+            },
+            SearchCacheParams::new("hybrid", "midterms link", &[1], 20),
+            SearchCacheParams::new("hybrid", "mid*terms link", &[1, 3], 20),
+        ] {
+            assert_ne!(cache_key(&app, &filtered), plain);
+        }
+    }
 }
@@ -1,6 +1,35 @@
 pub(crate) const MAX_QUERY_CHARS: usize = 512;
 
+fn typographic_replacement(character: char) -> Option<&'static str> {
+    let replacement = match character {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => "\"",
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
+        | '\u{2212}' => "-",
+        '\u{FB00}' => "ff",
+        '\u{FB01}' => "fi",
+        '\u{FB02}' => "fl",
+        '\u{FB03}' => "ffi",
+        '\u{FB04}' => "ffl",
+        '\u{FB05}' | '\u{FB06}' => "st",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+pub(crate) fn fold_typography(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for character in text.chars() {
+        match typographic_replacement(character) {
+            Some(replacement) => folded.push_str(replacement),
+            None => folded.push(character),
+        }
+    }
+    folded
+}
+
 pub(crate) fn normalize_for_search(text: &str) -> String {
+    let text = fold_typography(text);
     let mut normalized = String::with_capacity(text.len());
     let mut previous_space = false;
     for character in text.chars() {
@@ -19,7 +48,7 @@ pub(crate) fn normalize_for_search(text: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::normalize_for_search;
+    use super::{fold_typography, normalize_for_search};
 
     #[test]
     fn normalizes_case_and_punctuation() {
@@ -32,4 +61,29 @@ mod tests {
         let normalized = normalize_for_search("A&B---C///D");
         assert_eq!(normalized, "a b c d");
     }
+
+    #[test]
+    fn curly_quotes_match_straight_quotes() {
+        assert_eq!(
+            fold_typography("don\u{2019}t \u{201C}nuke\u{201D}"),
+            "don't \"nuke\""
+        );
+        assert_eq!(
+            normalize_for_search("Don\u{2019}t"),
+            normalize_for_search("don't")
+        );
+    }
+
+    #[test]
+    fn em_dashes_and_ligatures_match_ascii() {
+        assert_eq!(fold_typography("pre\u{2014}emption"), "pre-emption");
+        assert_eq!(
+            normalize_for_search("pre\u{2013}emption"),
+            normalize_for_search("pre-emption")
+        );
+        assert_eq!(
+            normalize_for_search("\u{FB01}nal o\u{FB03}ce"),
+            "final office"
+        );
+    }
 }
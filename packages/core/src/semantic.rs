@@ -1,7 +1,8 @@
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, RwLock};
 
 use arrow_array::types::Float32Type;
 use arrow_array::{
@@ -19,20 +20,110 @@ use rusqlite::params;
 use crate::runtime::AppHandle;
 use tokenizers::Tokenizer;
 
-use crate::db::{index_meta_dir, index_vector_dir, open_database};
-use crate::types::{SearchHit, SemanticCandidate, SemanticIndexMeta, SemanticRuntime};
+use crate::db::{
+    app_data_dir, index_meta_dir, index_vector_dir, open_database, remove_path_if_exists,
+};
+use crate::logging;
+use crate::types::{
+    PoolingStrategy, SearchHit, SemanticCandidate, SemanticEmbeddingLimits, SemanticIndexMeta,
+    SemanticModelSelection, SemanticReadiness, SemanticRuntime,
+};
 use crate::util::{file_name_from_relative, now_ms, path_display};
 use crate::CommandResult;
 
 pub(crate) const SEMANTIC_TABLE_NAME: &str = "semantic_hits_v2";
 pub(crate) const SEMANTIC_META_FILE_NAME: &str = "semantic-index-meta-v2.json";
+const SEMANTIC_MODEL_FILE_NAME: &str = "semantic-model.txt";
 pub(crate) const SEMANTIC_MAX_DOCUMENTS: usize = 2_000_000;
 pub(crate) const SEMANTIC_EMBED_BATCH: usize = 24;
 pub(crate) const SEMANTIC_MAX_TOKENS: usize = 192;
+pub(crate) const SEMANTIC_MAX_CHARS: usize = 720;
+// Position embedding limit of the bundled BERT-style model.
+const SEMANTIC_MODEL_MAX_TOKENS: usize = 512;
+const SEMANTIC_MIN_TOKENS: usize = 8;
+const SEMANTIC_MIN_CHARS: usize = 64;
+const SEMANTIC_MODEL_MAX_CHARS: usize = SEMANTIC_MODEL_MAX_TOKENS * 8;
 pub(crate) const SEMANTIC_MIN_QUERY_CHARS: usize = 3;
+pub(crate) const DEFAULT_SEMANTIC_MODEL: &str = "model";
+const POOLING_CONFIG_FILE_NAME: &str = "pooling.json";
+
+type SemanticRuntimeSlot = Option<(PathBuf, Arc<Mutex<SemanticRuntime>>)>;
+
+// Keyed by the resolved model path. The vector table and meta file are resolved from the app
+// data dir on every call, so per-command data dir overrides still get their own semantic index.
+static SEMANTIC_RUNTIME: RwLock<SemanticRuntimeSlot> = RwLock::new(None);
+// Keyed by semantic index dir, so rebuilds for different data dirs neither block nor cancel
+// each other.
+static SEMANTIC_REBUILDS_IN_FLIGHT: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+static SEMANTIC_REBUILDS_CANCELLED: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+static SEMANTIC_REBUILD_CLEARS_PENDING: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+static SEMANTIC_ENABLED: AtomicBool = AtomicBool::new(true);
+static SEMANTIC_LAST_ERROR: RwLock<Option<String>> = RwLock::new(None);
+static SEMANTIC_TOKEN_CAP: AtomicUsize = AtomicUsize::new(SEMANTIC_MAX_TOKENS);
+static SEMANTIC_CHAR_CAP: AtomicUsize = AtomicUsize::new(SEMANTIC_MAX_CHARS);
+static SEMANTIC_MODEL_NAME: RwLock<Option<String>> = RwLock::new(None);
+
+pub(crate) fn set_semantic_enabled(enabled: bool) -> bool {
+    SEMANTIC_ENABLED.store(enabled, AtomicOrdering::SeqCst);
+    enabled
+}
+
+pub(crate) fn semantic_enabled() -> bool {
+    SEMANTIC_ENABLED.load(AtomicOrdering::SeqCst)
+}
+
+pub(crate) fn configure_embedding_limits(
+    max_tokens: Option<usize>,
+    max_chars: Option<usize>,
+) -> SemanticEmbeddingLimits {
+    if let Some(value) = max_tokens {
+        SEMANTIC_TOKEN_CAP.store(
+            value.clamp(SEMANTIC_MIN_TOKENS, SEMANTIC_MODEL_MAX_TOKENS),
+            AtomicOrdering::Relaxed,
+        );
+    }
+    if let Some(value) = max_chars {
+        SEMANTIC_CHAR_CAP.store(
+            value.clamp(SEMANTIC_MIN_CHARS, SEMANTIC_MODEL_MAX_CHARS),
+            AtomicOrdering::Relaxed,
+        );
+    }
+    embedding_limits()
+}
 
-static SEMANTIC_RUNTIME: OnceLock<Mutex<SemanticRuntime>> = OnceLock::new();
-static SEMANTIC_REBUILD_IN_FLIGHT: AtomicBool = AtomicBool::new(false);
+pub(crate) fn embedding_limits() -> SemanticEmbeddingLimits {
+    SemanticEmbeddingLimits {
+        max_tokens: SEMANTIC_TOKEN_CAP.load(AtomicOrdering::Relaxed),
+        max_chars: SEMANTIC_CHAR_CAP.load(AtomicOrdering::Relaxed),
+    }
+}
+
+pub(crate) fn active_semantic_model() -> String {
+    SEMANTIC_MODEL_NAME
+        .read()
+        .ok()
+        .and_then(|reader| reader.clone())
+        .unwrap_or_else(|| DEFAULT_SEMANTIC_MODEL.to_string())
+}
+
+// The default model keeps the original `model.onnx` + `tokenizer.json` file names.
+fn semantic_resource_files(model_name: &str) -> [String; 2] {
+    if model_name == DEFAULT_SEMANTIC_MODEL {
+        return ["model.onnx".to_string(), "tokenizer.json".to_string()];
+    }
+    [
+        format!("{model_name}.onnx"),
+        format!("{model_name}.tokenizer.json"),
+    ]
+}
+
+fn meta_model_name(meta: &SemanticIndexMeta) -> &str {
+    meta.model_name.as_deref().unwrap_or(DEFAULT_SEMANTIC_MODEL)
+}
+
+fn semantic_model_mismatch(meta: &SemanticIndexMeta) -> bool {
+    meta.updated_at_ms > 0 && meta_model_name(meta) != active_semantic_model()
+}
 
 pub(crate) fn semantic_db_dir(app: &AppHandle) -> CommandResult<PathBuf> {
     index_vector_dir(app)
@@ -42,30 +133,116 @@ fn semantic_meta_path(app: &AppHandle) -> CommandResult<PathBuf> {
     Ok(index_meta_dir(app)?.join(SEMANTIC_META_FILE_NAME))
 }
 
-fn resolve_semantic_resource_path(app: &AppHandle, file_name: &str) -> CommandResult<PathBuf> {
-    let mut candidates = Vec::new();
+fn semantic_model_path(app: &AppHandle) -> CommandResult<PathBuf> {
+    Ok(app_data_dir(app)?.join(SEMANTIC_MODEL_FILE_NAME))
+}
+
+fn manifest_resources_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources")
+}
+
+fn semantic_resource_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
     if let Ok(resource_dir) = app.path().resource_dir() {
-        candidates.push(resource_dir.join(file_name));
-        candidates.push(resource_dir.join("resources").join(file_name));
+        let nested = resource_dir.join("resources");
+        dirs.push(resource_dir);
+        dirs.push(nested);
     }
-    let manifest_resources = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources");
-    candidates.push(manifest_resources.join(file_name));
+    dirs.push(manifest_resources_dir());
+    dirs
+}
 
-    for path in candidates {
-        if path.exists() {
-            return Ok(path);
-        }
+fn find_semantic_resource(dirs: &[PathBuf], file_name: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(file_name))
+        .find(|path| path.exists())
+}
+
+fn resolve_semantic_resource_path(app: &AppHandle, file_name: &str) -> CommandResult<PathBuf> {
+    find_semantic_resource(&semantic_resource_dirs(app), file_name).ok_or_else(|| {
+        format!(
+            "Missing semantic resource '{file_name}'. Expected it under the app resource directory or '{}'",
+            path_display(&manifest_resources_dir())
+        )
+    })
+}
+
+fn readiness_for_dirs(dirs: &[PathBuf]) -> SemanticReadiness {
+    let model_name = active_semantic_model();
+    let missing = semantic_resource_files(&model_name)
+        .iter()
+        .filter(|file_name| find_semantic_resource(dirs, file_name).is_none())
+        .map(|file_name| file_name.to_string())
+        .collect::<Vec<String>>();
+    let error = if missing.is_empty() {
+        SEMANTIC_LAST_ERROR
+            .read()
+            .ok()
+            .and_then(|reader| reader.clone())
+    } else {
+        None
+    };
+
+    SemanticReadiness {
+        ready: missing.is_empty() && error.is_none(),
+        missing,
+        error,
+        model_name,
     }
+}
 
-    Err(format!(
-        "Missing semantic resource '{file_name}'. Expected it under the app resource directory or '{}'",
-        path_display(&manifest_resources)
-    ))
+pub(crate) fn semantic_readiness(app: &AppHandle) -> SemanticReadiness {
+    readiness_for_dirs(&semantic_resource_dirs(app))
+}
+
+fn record_semantic_error(error: Option<String>) {
+    if let Ok(mut writer) = SEMANTIC_LAST_ERROR.write() {
+        *writer = error;
+    }
+}
+
+fn parse_pooling_config(config: &serde_json::Value) -> PoolingStrategy {
+    let named_cls = config
+        .get("pooling")
+        .and_then(|value| value.as_str())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("cls"));
+    let sentence_transformers_cls = config
+        .get("pooling_mode_cls_token")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if named_cls || sentence_transformers_cls {
+        PoolingStrategy::Cls
+    } else {
+        PoolingStrategy::Mean
+    }
+}
+
+fn read_pooling_strategy(model_path: &Path) -> PoolingStrategy {
+    let Some(config_path) = model_path
+        .parent()
+        .map(|dir| dir.join(POOLING_CONFIG_FILE_NAME))
+    else {
+        return PoolingStrategy::default();
+    };
+    let Ok(raw) = fs::read_to_string(&config_path) else {
+        return PoolingStrategy::default();
+    };
+    match serde_json::from_str::<serde_json::Value>(&raw) {
+        Ok(config) => parse_pooling_config(&config),
+        Err(error) => {
+            logging::warn(&format!(
+                "Ignoring invalid pooling config '{}': {error}",
+                path_display(&config_path)
+            ));
+            PoolingStrategy::default()
+        }
+    }
 }
 
 fn build_semantic_runtime(app: &AppHandle) -> CommandResult<SemanticRuntime> {
-    let model_path = resolve_semantic_resource_path(app, "model.onnx")?;
-    let tokenizer_path = resolve_semantic_resource_path(app, "tokenizer.json")?;
+    let [model_file, tokenizer_file] = semantic_resource_files(&active_semantic_model());
+    let model_path = resolve_semantic_resource_path(app, &model_file)?;
+    let tokenizer_path = resolve_semantic_resource_path(app, &tokenizer_file)?;
     let tokenizer = Tokenizer::from_file(&tokenizer_path).map_err(|error| {
         format!(
             "Could not load tokenizer '{}': {error}",
@@ -100,29 +277,110 @@ fn build_semantic_runtime(app: &AppHandle) -> CommandResult<SemanticRuntime> {
         tokenizer,
         session,
         output_name,
+        pooling: read_pooling_strategy(&model_path),
     })
 }
 
-fn load_semantic_runtime(app: &AppHandle) -> CommandResult<&'static Mutex<SemanticRuntime>> {
-    if let Some(runtime) = SEMANTIC_RUNTIME.get() {
-        return Ok(runtime);
+fn load_semantic_runtime(app: &AppHandle) -> CommandResult<Arc<Mutex<SemanticRuntime>>> {
+    let [model_file, _] = semantic_resource_files(&active_semantic_model());
+    let model_path = resolve_semantic_resource_path(app, &model_file)?;
+    if let Ok(reader) = SEMANTIC_RUNTIME.read() {
+        if let Some((loaded_path, runtime)) = reader.as_ref() {
+            if *loaded_path == model_path {
+                return Ok(Arc::clone(runtime));
+            }
+        }
     }
 
-    let runtime = build_semantic_runtime(app)?;
-    let _ = SEMANTIC_RUNTIME.set(Mutex::new(runtime));
-    SEMANTIC_RUNTIME
-        .get()
-        .ok_or_else(|| "Could not initialize semantic runtime".to_string())
+    let runtime = match build_semantic_runtime(app) {
+        Ok(runtime) => Arc::new(Mutex::new(runtime)),
+        Err(error) => {
+            record_semantic_error(Some(error.clone()));
+            return Err(error);
+        }
+    };
+    record_semantic_error(None);
+    let mut writer = SEMANTIC_RUNTIME
+        .write()
+        .map_err(|_| "Could not update semantic runtime".to_string())?;
+    *writer = Some((model_path, Arc::clone(&runtime)));
+    Ok(runtime)
 }
 
-fn semantic_root_fingerprint_ms(connection: &rusqlite::Connection) -> CommandResult<i64> {
+pub(crate) fn reset_semantic_runtime() {
+    if let Ok(mut writer) = SEMANTIC_RUNTIME.write() {
+        *writer = None;
+    }
+}
+
+pub(crate) fn set_semantic_model(
+    app: &AppHandle,
+    name: &str,
+) -> CommandResult<SemanticModelSelection> {
+    let model_name = name.trim();
+    let model_name = if model_name.is_empty() {
+        DEFAULT_SEMANTIC_MODEL
+    } else {
+        model_name
+    };
+    if !is_valid_model_name(model_name) {
+        return Err(format!("Invalid semantic model name '{model_name}'."));
+    }
+    for file_name in semantic_resource_files(model_name) {
+        resolve_semantic_resource_path(app, &file_name)?;
+    }
+
+    let model_path = semantic_model_path(app)?;
+    fs::write(&model_path, model_name).map_err(|error| {
+        format!(
+            "Could not save semantic model '{}': {error}",
+            path_display(&model_path)
+        )
+    })?;
+    {
+        let mut writer = SEMANTIC_MODEL_NAME
+            .write()
+            .map_err(|_| "Could not update semantic model".to_string())?;
+        *writer = (model_name != DEFAULT_SEMANTIC_MODEL).then(|| model_name.to_string());
+    }
+    reset_semantic_runtime();
+    record_semantic_error(None);
+
+    Ok(SemanticModelSelection {
+        model_name: model_name.to_string(),
+        rebuild_required: semantic_index_is_stale(app)?,
+    })
+}
+
+fn is_valid_model_name(model_name: &str) -> bool {
+    !model_name.contains(['/', '\\']) && !model_name.starts_with('.')
+}
+
+// Restores the model chosen in an earlier session; without a saved choice the default is used.
+pub(crate) fn load_semantic_model(app: &AppHandle) {
+    let saved = semantic_model_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok());
+    let model_name = saved
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != DEFAULT_SEMANTIC_MODEL)
+        .filter(|name| is_valid_model_name(name))
+        .map(str::to_string);
+    if let Ok(mut writer) = SEMANTIC_MODEL_NAME.write() {
+        *writer = model_name;
+    }
+    reset_semantic_runtime();
+}
+
+pub(crate) fn root_fingerprint_ms(connection: &rusqlite::Connection) -> CommandResult<i64> {
     connection
         .query_row(
             "SELECT COALESCE(MAX(last_indexed_ms), 0) FROM roots",
             [],
             |row| row.get::<_, i64>(0),
         )
-        .map_err(|error| format!("Could not read semantic index fingerprint: {error}"))
+        .map_err(|error| format!("Could not read index fingerprint: {error}"))
 }
 
 fn read_semantic_meta(app: &AppHandle) -> CommandResult<SemanticIndexMeta> {
@@ -144,6 +402,34 @@ fn read_semantic_meta(app: &AppHandle) -> CommandResult<SemanticIndexMeta> {
     })
 }
 
+pub(crate) fn semantic_item_count(app: &AppHandle) -> CommandResult<usize> {
+    Ok(read_semantic_meta(app)?.item_count)
+}
+
+pub(crate) fn semantic_root_coverage(
+    app: &AppHandle,
+    root_id: i64,
+    root_last_indexed_ms: i64,
+) -> CommandResult<f64> {
+    let meta = read_semantic_meta(app).unwrap_or_default();
+    if semantic_model_mismatch(&meta) || meta.root_fingerprint_ms < root_last_indexed_ms {
+        return Ok(0.0);
+    }
+    let connection = open_database(app)?;
+    let chunk_count = connection
+        .query_row(
+            "SELECT COUNT(*) FROM chunks WHERE root_id = ?1 AND trim(chunk_text) <> ''",
+            params![root_id],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|error| format!("Could not count semantic candidates: {error}"))?;
+    if chunk_count <= 0 {
+        return Ok(1.0);
+    }
+    let embedded_count = meta.root_item_counts.get(&root_id).copied().unwrap_or(0);
+    Ok((embedded_count as f64 / chunk_count as f64).min(1.0))
+}
+
 fn write_semantic_meta(app: &AppHandle, meta: &SemanticIndexMeta) -> CommandResult<()> {
     let path = semantic_meta_path(app)?;
     let raw = serde_json::to_vec_pretty(meta)
@@ -158,22 +444,52 @@ fn write_semantic_meta(app: &AppHandle, meta: &SemanticIndexMeta) -> CommandResu
 
 fn semantic_index_is_stale(app: &AppHandle) -> CommandResult<bool> {
     let connection = open_database(app)?;
-    let fingerprint = semantic_root_fingerprint_ms(&connection)?;
+    let fingerprint = root_fingerprint_ms(&connection)?;
     if fingerprint == 0 {
         return Ok(false);
     }
     let meta = read_semantic_meta(app).unwrap_or_default();
-    Ok(meta.root_fingerprint_ms < fingerprint)
+    Ok(meta.root_fingerprint_ms < fingerprint || semantic_model_mismatch(&meta))
+}
+
+fn ensure_semantic_model_matches(app: &AppHandle) -> CommandResult<()> {
+    let meta = read_semantic_meta(app).unwrap_or_default();
+    if !semantic_model_mismatch(&meta) {
+        return Ok(());
+    }
+    Err(format!(
+        "Semantic index was built with model '{}' but '{}' is active; rebuild the semantic index first.",
+        meta_model_name(&meta),
+        active_semantic_model()
+    ))
 }
 
 fn semantic_embedding_text(text: &str) -> String {
+    let max_chars = embedding_limits().max_chars;
     let mut value = text.trim().to_string();
-    if value.chars().count() > 720 {
-        value = value.chars().take(720).collect();
+    if value.chars().count() > max_chars {
+        value = value.chars().take(max_chars).collect();
     }
     value
 }
 
+fn semantic_section_text(
+    heading_text: Option<&str>,
+    author_text: Option<&str>,
+    chunk_text: &str,
+    relative_path: &str,
+    file_name: &str,
+) -> String {
+    semantic_embedding_text(&format!(
+        "heading: {}\nauthor: {}\nchunk: {}\npath: {}\nfile: {}",
+        heading_text.unwrap_or_default(),
+        author_text.unwrap_or_default(),
+        chunk_text,
+        relative_path,
+        file_name
+    ))
+}
+
 fn load_semantic_candidates(
     connection: &rusqlite::Connection,
     max_documents: usize,
@@ -199,7 +515,8 @@ fn load_semantic_candidates(
                   heading_text,
                   heading_order,
                   author_text,
-                  chunk_text
+                  chunk_text,
+                  kind
                 FROM chunks
                 ORDER BY root_id ASC, file_id ASC, chunk_order ASC
                 LIMIT ?1
@@ -222,6 +539,7 @@ fn load_semantic_candidates(
                     row.get::<_, Option<i64>>(7)?,
                     row.get::<_, Option<String>>(8)?,
                     row.get::<_, String>(9)?,
+                    row.get::<_, String>(10)?,
                 ))
             })
             .map_err(|error| format!("Could not run semantic chunk candidates query: {error}"))?;
@@ -241,6 +559,7 @@ fn load_semantic_candidates(
                 heading_order,
                 author_text,
                 chunk_text,
+                chunk_kind,
             ) =
                 row.map_err(|error| format!("Could not parse semantic chunk candidate: {error}"))?;
 
@@ -249,15 +568,16 @@ fn load_semantic_candidates(
                 continue;
             }
 
-            let semantic_text = semantic_embedding_text(&format!(
-                "heading: {}\nauthor: {}\nchunk: {}\npath: {}\nfile: {}",
-                heading_text.clone().unwrap_or_default(),
-                author_text.clone().unwrap_or_default(),
+            let semantic_text = semantic_section_text(
+                heading_text.as_deref(),
+                author_text.as_deref(),
                 trimmed_chunk,
-                relative_path,
-                file_name
-            ));
-            let kind = if author_text.is_some() {
+                &relative_path,
+                &file_name,
+            );
+            let kind = if chunk_kind == "paragraph" || chunk_kind == "comment" {
+                chunk_kind
+            } else if author_text.is_some() {
                 "author".to_string()
             } else if heading_text.is_some() {
                 "heading".to_string()
@@ -356,7 +676,7 @@ fn encode_semantic_batch(
     if batch_size == 0 {
         return Ok((Vec::new(), Vec::new(), Vec::new(), 0, 0));
     }
-    let seq_len = max_tokens.max(8);
+    let seq_len = max_tokens.max(SEMANTIC_MIN_TOKENS);
     let mut input_ids = Vec::with_capacity(batch_size.saturating_mul(seq_len));
     let mut attention_mask = Vec::with_capacity(batch_size.saturating_mul(seq_len));
     let mut token_type_ids = Vec::with_capacity(batch_size.saturating_mul(seq_len));
@@ -426,6 +746,7 @@ pub(crate) fn embed_semantic_texts(
         .lock()
         .map_err(|_| "Could not lock semantic runtime".to_string())?;
     let output_name = runtime.output_name.clone();
+    let pooling = runtime.pooling;
     let expects_token_type_ids = runtime
         .session
         .inputs()
@@ -433,7 +754,7 @@ pub(crate) fn embed_semantic_texts(
         .any(|entry| entry.name() == "token_type_ids");
 
     let (input_ids, attention_mask, token_type_ids, batch_size, seq_len) =
-        encode_semantic_batch(&runtime.tokenizer, texts, SEMANTIC_MAX_TOKENS)?;
+        encode_semantic_batch(&runtime.tokenizer, texts, embedding_limits().max_tokens)?;
     if batch_size == 0 || seq_len == 0 {
         return Ok(Vec::new());
     }
@@ -486,35 +807,73 @@ pub(crate) fn embed_semantic_texts(
     }
 
     let mut vectors = Vec::with_capacity(batch_size);
+    let max_steps = output_seq.min(seq_len);
     for batch_index in 0..batch_size {
-        let mut pooled = vec![0.0_f32; embedding_dim];
-        let mut token_count = 0.0_f32;
-        let max_steps = output_seq.min(seq_len);
+        let mask_start = batch_index * seq_len;
+        let mut pooled = pool_token_embeddings(
+            pooling,
+            &attention_mask[mask_start..mask_start + max_steps],
+            embedding_dim,
+            |token_index, dim| output[[batch_index, token_index, dim]],
+        );
+        normalize_vector_l2(&mut pooled);
+        vectors.push(pooled);
+    }
+
+    Ok(vectors)
+}
+
+pub(crate) fn semantic_text_similarity(app: &AppHandle, a: &str, b: &str) -> CommandResult<f32> {
+    let (a, b) = (a.trim(), b.trim());
+    if a.is_empty() || b.is_empty() {
+        return Err("Both texts must be non-empty to compare.".to_string());
+    }
+    if !semantic_enabled() {
+        return Err("Semantic search is disabled.".to_string());
+    }
+    let embeddings = embed_semantic_texts(app, &[a.to_string(), b.to_string()])?;
+    let [first, second] = embeddings.as_slice() else {
+        return Err("Semantic model did not return an embedding for both texts.".to_string());
+    };
+    // Embeddings are L2-normalized, so the dot product is the cosine similarity.
+    Ok(first
+        .iter()
+        .zip(second.iter())
+        .map(|(left, right)| left * right)
+        .sum())
+}
 
-        for token_index in 0..max_steps {
-            if attention_mask[batch_index * seq_len + token_index] == 0 {
+// CLS pooling, and mean pooling over an all-masked row, both take the first token's vector.
+fn pool_token_embeddings(
+    pooling: PoolingStrategy,
+    attention_mask: &[i64],
+    embedding_dim: usize,
+    value_at: impl Fn(usize, usize) -> f32,
+) -> Vec<f32> {
+    let mut pooled = vec![0.0_f32; embedding_dim];
+    let mut token_count = 0.0_f32;
+    if pooling == PoolingStrategy::Mean {
+        for (token_index, mask) in attention_mask.iter().enumerate() {
+            if *mask == 0 {
                 continue;
             }
             token_count += 1.0;
-            for dim in 0..embedding_dim {
-                pooled[dim] += output[[batch_index, token_index, dim]];
+            for (dim, value) in pooled.iter_mut().enumerate() {
+                *value += value_at(token_index, dim);
             }
         }
+    }
 
-        if token_count <= 0.0 {
-            for dim in 0..embedding_dim {
-                pooled[dim] = output[[batch_index, 0, dim]];
-            }
-        } else {
-            for value in &mut pooled {
-                *value /= token_count;
-            }
+    if token_count <= 0.0 {
+        for (dim, value) in pooled.iter_mut().enumerate() {
+            *value = value_at(0, dim);
+        }
+    } else {
+        for value in &mut pooled {
+            *value /= token_count;
         }
-        normalize_vector_l2(&mut pooled);
-        vectors.push(pooled);
     }
-
-    Ok(vectors)
+    pooled
 }
 
 fn semantic_schema(embedding_dim: usize) -> Arc<Schema> {
@@ -624,16 +983,21 @@ fn semantic_record_batch(
 }
 
 async fn rebuild_semantic_index(app: AppHandle, force: bool) -> CommandResult<()> {
+    let semantic_dir = semantic_db_dir(&app)?;
     let connection = open_database(&app)?;
-    let root_fingerprint_ms = semantic_root_fingerprint_ms(&connection)?;
+    let root_fingerprint_ms = root_fingerprint_ms(&connection)?;
     if root_fingerprint_ms <= 0 {
         return Ok(());
     }
 
     let previous_meta = read_semantic_meta(&app).unwrap_or_default();
-    if !force && previous_meta.root_fingerprint_ms >= root_fingerprint_ms {
+    if !force
+        && previous_meta.root_fingerprint_ms >= root_fingerprint_ms
+        && !semantic_model_mismatch(&previous_meta)
+    {
         return Ok(());
     }
+    let model_name = Some(active_semantic_model());
 
     let candidates = load_semantic_candidates(&connection, SEMANTIC_MAX_DOCUMENTS)?;
     if candidates.is_empty() {
@@ -642,6 +1006,8 @@ async fn rebuild_semantic_index(app: AppHandle, force: bool) -> CommandResult<()
             item_count: 0,
             embedding_dim: 0,
             updated_at_ms: now_ms(),
+            model_name,
+            root_item_counts: HashMap::new(),
         };
         write_semantic_meta(&app, &meta)?;
         return Ok(());
@@ -650,8 +1016,12 @@ async fn rebuild_semantic_index(app: AppHandle, force: bool) -> CommandResult<()
     let mut schema: Option<Arc<Schema>> = None;
     let mut batches = Vec::new();
     let mut embedding_dim = 0_usize;
+    let mut root_item_counts = HashMap::<i64, usize>::new();
 
     for chunk in candidates.chunks(SEMANTIC_EMBED_BATCH) {
+        if take_rebuild_cancellation(&semantic_dir) {
+            return Ok(());
+        }
         let texts = chunk
             .iter()
             .map(|candidate| candidate.semantic_text.clone())
@@ -685,13 +1055,15 @@ async fn rebuild_semantic_index(app: AppHandle, force: bool) -> CommandResult<()
             embedding_dim,
         )?;
         batches.push(batch);
+        for candidate in chunk {
+            *root_item_counts.entry(candidate.root_id).or_default() += 1;
+        }
     }
 
-    if batches.is_empty() || embedding_dim == 0 {
+    if batches.is_empty() || embedding_dim == 0 || take_rebuild_cancellation(&semantic_dir) {
         return Ok(());
     }
 
-    let semantic_dir = semantic_db_dir(&app)?;
     fs::create_dir_all(&semantic_dir).map_err(|error| {
         format!(
             "Could not create semantic DB directory '{}': {error}",
@@ -726,28 +1098,97 @@ async fn rebuild_semantic_index(app: AppHandle, force: bool) -> CommandResult<()
         item_count: candidates.len(),
         embedding_dim,
         updated_at_ms: now_ms(),
+        model_name,
+        root_item_counts,
     };
     write_semantic_meta(&app, &meta)?;
     Ok(())
 }
 
-pub(crate) fn trigger_semantic_rebuild(app: AppHandle, force: bool) {
+pub(crate) fn trigger_semantic_rebuild(app: AppHandle, force: bool) -> bool {
+    if !semantic_enabled() {
+        return false;
+    }
     let should_rebuild = force || semantic_index_is_stale(&app).unwrap_or(false);
     if !should_rebuild {
-        return;
+        return false;
     }
-    if SEMANTIC_REBUILD_IN_FLIGHT
-        .compare_exchange(false, true, AtomicOrdering::SeqCst, AtomicOrdering::SeqCst)
-        .is_err()
-    {
-        return;
+    let Ok(semantic_dir) = semantic_db_dir(&app) else {
+        return false;
+    };
+    let started = SEMANTIC_REBUILDS_IN_FLIGHT
+        .lock()
+        .is_ok_and(|mut in_flight| in_flight.insert(semantic_dir.clone()));
+    if !started {
+        return false;
+    }
+    if let Ok(mut cancelled) = SEMANTIC_REBUILDS_CANCELLED.lock() {
+        cancelled.remove(&semantic_dir);
     }
     crate::async_runtime::spawn(async move {
         if let Err(error) = rebuild_semantic_index(app.clone(), force).await {
-            eprintln!("Semantic index rebuild failed: {error}");
+            logging::error(&format!("Semantic index rebuild failed: {error}"));
         }
-        SEMANTIC_REBUILD_IN_FLIGHT.store(false, AtomicOrdering::SeqCst);
+        finish_semantic_rebuild(&app, &semantic_dir);
     });
+    true
+}
+
+// Runs a clear requested mid-rebuild only once the rebuild has stopped writing.
+fn finish_semantic_rebuild(app: &AppHandle, semantic_dir: &Path) {
+    let Ok(mut in_flight) = SEMANTIC_REBUILDS_IN_FLIGHT.lock() else {
+        return;
+    };
+    let clear_requested = SEMANTIC_REBUILD_CLEARS_PENDING
+        .lock()
+        .is_ok_and(|mut pending| pending.remove(semantic_dir));
+    if clear_requested {
+        if let Err(error) = clear_semantic_index(app) {
+            logging::error(&format!("Could not clear semantic index: {error}"));
+        }
+    }
+    in_flight.remove(semantic_dir);
+}
+
+fn clear_semantic_index(app: &AppHandle) -> CommandResult<()> {
+    remove_path_if_exists(&semantic_db_dir(app)?)?;
+    remove_path_if_exists(&semantic_meta_path(app)?)
+}
+
+fn take_rebuild_cancellation(semantic_dir: &Path) -> bool {
+    let cancelled = SEMANTIC_REBUILDS_CANCELLED
+        .lock()
+        .is_ok_and(|mut cancelled| cancelled.remove(semantic_dir));
+    if cancelled {
+        logging::warn("Semantic index rebuild cancelled; keeping the previous index.");
+    }
+    cancelled
+}
+
+// The running rebuild stops at its next embedding batch and exits through the normal path,
+// which clears the in-flight flag. A clear is handed to that exit path, since the rebuild
+// may still be writing the table it would delete.
+pub(crate) fn cancel_semantic_rebuild(app: &AppHandle, clear: bool) -> CommandResult<bool> {
+    let semantic_dir = semantic_db_dir(app)?;
+    let in_flight = SEMANTIC_REBUILDS_IN_FLIGHT
+        .lock()
+        .map_err(|_| "Could not lock semantic rebuild state".to_string())?;
+    let was_running = in_flight.contains(&semantic_dir);
+    if was_running {
+        SEMANTIC_REBUILDS_CANCELLED
+            .lock()
+            .map_err(|_| "Could not lock semantic rebuild state".to_string())?
+            .insert(semantic_dir.clone());
+        if clear {
+            SEMANTIC_REBUILD_CLEARS_PENDING
+                .lock()
+                .map_err(|_| "Could not lock semantic rebuild state".to_string())?
+                .insert(semantic_dir);
+        }
+    } else if clear {
+        clear_semantic_index(app)?;
+    }
+    Ok(was_running)
 }
 
 pub(crate) fn semantic_hits_from_batches(
@@ -830,16 +1271,20 @@ pub(crate) fn semantic_hits_from_batches(
                 heading_text,
                 heading_order,
                 score: 7000.0 + (distance * 1000.0),
+                explanation: None,
+                content_hash: None,
+                heading_path: None,
+                argument_type: None,
             });
         }
     }
     Ok(hits)
 }
 
-pub(crate) async fn semantic_search(
+async fn nearest_semantic_hits(
     app: &AppHandle,
-    query: &str,
-    requested_root_id: Option<i64>,
+    embedding: &[f32],
+    filter: Option<String>,
     limit: usize,
 ) -> CommandResult<Vec<SearchHit>> {
     let semantic_dir = semantic_db_dir(app)?;
@@ -856,20 +1301,9 @@ pub(crate) async fn semantic_search(
         Err(_) => return Ok(Vec::new()),
     };
 
-    let app_for_embedding = app.clone();
-    let query_text = query.to_string();
-    let query_embedding = crate::async_runtime::spawn_blocking(move || {
-        embed_semantic_texts(&app_for_embedding, &[query_text])
-    })
-    .await
-    .map_err(|error| format!("Semantic query embedding task failed: {error}"))??;
-    if query_embedding.is_empty() || query_embedding[0].is_empty() {
-        return Ok(Vec::new());
-    }
-
     let mut vector_query = table
         .query()
-        .nearest_to(query_embedding[0].as_slice())
+        .nearest_to(embedding)
         .map_err(|error| format!("Could not build semantic vector query: {error}"))?
         .limit(limit.saturating_mul(2))
         .select(Select::columns(&[
@@ -885,8 +1319,8 @@ pub(crate) async fn semantic_search(
         .nprobes(18)
         .refine_factor(2);
 
-    if let Some(root_id) = requested_root_id {
-        vector_query = vector_query.only_if(format!("root_id = {root_id}"));
+    if let Some(filter) = filter {
+        vector_query = vector_query.only_if(filter);
     }
 
     let batches = vector_query
@@ -899,3 +1333,496 @@ pub(crate) async fn semantic_search(
 
     semantic_hits_from_batches(&batches, limit)
 }
+
+async fn embed_single_text(app: &AppHandle, text: String) -> CommandResult<Option<Vec<f32>>> {
+    let app_for_embedding = app.clone();
+    let embeddings = crate::async_runtime::spawn_blocking(move || {
+        embed_semantic_texts(&app_for_embedding, &[text])
+    })
+    .await
+    .map_err(|error| format!("Semantic query embedding task failed: {error}"))??;
+    Ok(embeddings
+        .into_iter()
+        .next()
+        .filter(|embedding| !embedding.is_empty()))
+}
+
+fn semantic_root_filter(requested_root_ids: &[i64]) -> Option<String> {
+    match requested_root_ids {
+        [] => None,
+        [root_id] => Some(format!("root_id = {root_id}")),
+        root_ids => Some(format!(
+            "root_id IN ({})",
+            root_ids
+                .iter()
+                .map(i64::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        )),
+    }
+}
+
+pub(crate) async fn semantic_search(
+    app: &AppHandle,
+    query: &str,
+    requested_root_ids: &[i64],
+    limit: usize,
+) -> CommandResult<Vec<SearchHit>> {
+    if !semantic_enabled() || !semantic_db_dir(app)?.exists() {
+        return Ok(Vec::new());
+    }
+    ensure_semantic_model_matches(app)?;
+    let Some(query_embedding) = embed_single_text(app, query.to_string()).await? else {
+        return Ok(Vec::new());
+    };
+
+    nearest_semantic_hits(
+        app,
+        &query_embedding,
+        semantic_root_filter(requested_root_ids),
+        limit,
+    )
+    .await
+}
+
+fn load_heading_section_text(
+    connection: &rusqlite::Connection,
+    file_id: i64,
+    heading_order: i64,
+) -> CommandResult<Option<String>> {
+    let mut statement = connection
+        .prepare(
+            "
+            SELECT heading_text, author_text, chunk_text, relative_path, file_name
+            FROM chunks
+            WHERE file_id = ?1 AND heading_order = ?2 AND kind = 'chunk'
+            ORDER BY chunk_order ASC
+            ",
+        )
+        .map_err(|error| format!("Could not prepare similar heading query: {error}"))?;
+    let rows = statement
+        .query_map(params![file_id, heading_order], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })
+        .map_err(|error| format!("Could not read similar heading chunks: {error}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("Could not parse similar heading chunk: {error}"))?;
+
+    let Some((heading_text, author_text, _, relative_path, file_name)) = rows.first() else {
+        return Ok(None);
+    };
+    let chunk_text = rows
+        .iter()
+        .map(|(_, _, chunk_text, _, _)| chunk_text.trim())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    Ok(Some(semantic_section_text(
+        heading_text.as_deref(),
+        author_text.as_deref(),
+        &chunk_text,
+        relative_path,
+        file_name,
+    )))
+}
+
+pub(crate) async fn semantic_find_similar(
+    app: &AppHandle,
+    file_id: i64,
+    heading_order: i64,
+    limit: usize,
+) -> CommandResult<Vec<SearchHit>> {
+    let section_text = {
+        let connection = open_database(app)?;
+        load_heading_section_text(&connection, file_id, heading_order)?
+    }
+    .ok_or_else(|| format!("Heading {heading_order} in file {file_id} is not indexed."))?;
+
+    if !semantic_enabled() || !semantic_db_dir(app)?.exists() {
+        return Ok(Vec::new());
+    }
+    ensure_semantic_model_matches(app)?;
+    let Some(embedding) = embed_single_text(app, section_text).await? else {
+        return Ok(Vec::new());
+    };
+
+    let hits = nearest_semantic_hits(
+        app,
+        &embedding,
+        Some(format!(
+            "NOT (file_id = {file_id} AND heading_order = {heading_order})"
+        )),
+        limit.saturating_add(1),
+    )
+    .await?;
+    Ok(hits
+        .into_iter()
+        .filter(|hit| !(hit.file_id == file_id && hit.heading_order == Some(heading_order)))
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rusqlite::params;
+
+    use super::{
+        active_semantic_model, cancel_semantic_rebuild, configure_embedding_limits,
+        embedding_limits, encode_semantic_batch, finish_semantic_rebuild, load_semantic_model,
+        manifest_resources_dir, parse_pooling_config, pool_token_embeddings, readiness_for_dirs,
+        rebuild_semantic_index, resolve_semantic_resource_path, semantic_db_dir,
+        semantic_find_similar, semantic_index_is_stale, semantic_meta_path, semantic_root_coverage,
+        semantic_search, semantic_text_similarity, set_semantic_enabled, set_semantic_model,
+        trigger_semantic_rebuild, write_semantic_meta, DEFAULT_SEMANTIC_MODEL, SEMANTIC_MAX_CHARS,
+        SEMANTIC_MAX_TOKENS, SEMANTIC_MODEL_NAME, SEMANTIC_REBUILDS_CANCELLED,
+        SEMANTIC_REBUILDS_IN_FLIGHT, SEMANTIC_TABLE_NAME,
+    };
+    use crate::db::open_database;
+    use crate::runtime::AppHandle;
+    use crate::test_support::{insert_file_row, temp_dir, test_app, GLOBAL_SETTINGS_LOCK};
+    use crate::types::{PoolingStrategy, SemanticIndexMeta};
+
+    #[test]
+    fn disabled_semantic_skips_rebuild_trigger() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("semantic-disabled");
+
+        set_semantic_enabled(false);
+        let triggered = trigger_semantic_rebuild(app, true);
+        set_semantic_enabled(true);
+
+        assert!(!triggered);
+    }
+
+    #[test]
+    fn cls_pooling_returns_first_token_vector() {
+        // Shape [batch = 2, seq = 3, dim = 2], flattened row-major.
+        let output = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, //
+            7.0, 8.0, 9.0, 10.0, 11.0, 12.0_f32,
+        ];
+        let value_at =
+            |batch: usize| move |token: usize, dim: usize| output[(batch * 3 + token) * 2 + dim];
+        let mask = [1_i64, 1, 0];
+
+        assert_eq!(
+            pool_token_embeddings(PoolingStrategy::Cls, &mask, 2, value_at(1)),
+            vec![7.0, 8.0]
+        );
+        assert_eq!(
+            pool_token_embeddings(PoolingStrategy::Mean, &mask, 2, value_at(1)),
+            vec![8.0, 9.0]
+        );
+        assert_eq!(
+            parse_pooling_config(&serde_json::json!({ "pooling_mode_cls_token": true })),
+            PoolingStrategy::Cls
+        );
+        assert_eq!(
+            parse_pooling_config(&serde_json::json!({})),
+            PoolingStrategy::Mean
+        );
+    }
+
+    #[test]
+    fn configured_token_cap_sets_encoded_sequence_length() {
+        let tokenizer =
+            tokenizers::Tokenizer::from_file(manifest_resources_dir().join("tokenizer.json"))
+                .expect("load bundled tokenizer");
+        let long_card =
+            "Warming causes extinction through cascading ecosystem collapse. ".repeat(80);
+
+        let applied = configure_embedding_limits(Some(384), None);
+        let (input_ids, attention_mask, _, batch_size, seq_len) =
+            encode_semantic_batch(&tokenizer, &[long_card], embedding_limits().max_tokens)
+                .expect("encode batch");
+        let clamped = configure_embedding_limits(Some(10_000), Some(1));
+        configure_embedding_limits(Some(SEMANTIC_MAX_TOKENS), Some(SEMANTIC_MAX_CHARS));
+
+        assert_eq!(applied.max_tokens, 384);
+        assert_eq!((batch_size, seq_len), (1, 384));
+        assert_eq!(input_ids.len(), 384);
+        assert!(attention_mask.iter().all(|mask| *mask == 1));
+        assert_eq!(clamped.max_tokens, 512);
+        assert_eq!(clamped.max_chars, 64);
+    }
+
+    #[test]
+    fn readiness_lists_missing_model_files() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let empty_resources = temp_dir("semantic-readiness");
+        let readiness = readiness_for_dirs(&[empty_resources.clone()]);
+        let _ = std::fs::remove_dir_all(&empty_resources);
+
+        assert!(!readiness.ready);
+        assert_eq!(readiness.missing, vec!["model.onnx", "tokenizer.json"]);
+        assert!(readiness.error.is_none());
+    }
+
+    #[test]
+    fn cancelled_rebuild_stops_without_writing_table() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("semantic-cancel");
+        let connection = open_database(&app).unwrap();
+        let (root_id, file_id) = insert_file_row(&connection, "/debate", "Impacts.docx");
+        connection
+            .execute(
+                "UPDATE roots SET last_indexed_ms = 1 WHERE id = ?1",
+                params![root_id],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO chunks(chunk_id, root_id, file_id, chunk_order, heading_order, heading_text, chunk_text, file_name, relative_path, absolute_path)
+                 VALUES(?1, ?2, ?3, 1, 1, 'Warming causes extinction', 'Runaway climate change ends civilization.', 'Impacts.docx', 'Impacts.docx', '/debate/Impacts.docx')",
+                params![format!("{file_id}:1"), root_id, file_id],
+            )
+            .unwrap();
+
+        let semantic_dir = semantic_db_dir(&app).unwrap();
+        let other_dir = semantic_db_dir(&test_app("semantic-cancel-other")).unwrap();
+        SEMANTIC_REBUILDS_CANCELLED
+            .lock()
+            .unwrap()
+            .extend([semantic_dir.clone(), other_dir.clone()]);
+        crate::async_runtime::block_on(rebuild_semantic_index(app.clone(), true)).unwrap();
+        let table_dir = semantic_dir.join(format!("{SEMANTIC_TABLE_NAME}.lance"));
+        let other_still_cancelled = SEMANTIC_REBUILDS_CANCELLED
+            .lock()
+            .unwrap()
+            .remove(&other_dir);
+
+        assert!(!SEMANTIC_REBUILDS_CANCELLED
+            .lock()
+            .unwrap()
+            .contains(&semantic_dir));
+        assert!(other_still_cancelled);
+        assert!(!table_dir.exists());
+        assert!(!semantic_meta_path(&app).unwrap().exists());
+    }
+
+    #[test]
+    fn cancel_with_clear_waits_for_running_rebuild() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("semantic-cancel-clear");
+        let semantic_dir = semantic_db_dir(&app).unwrap();
+        std::fs::create_dir_all(&semantic_dir).unwrap();
+        SEMANTIC_REBUILDS_IN_FLIGHT
+            .lock()
+            .unwrap()
+            .insert(semantic_dir.clone());
+
+        let was_running = cancel_semantic_rebuild(&app, true).unwrap();
+        let kept_while_running = semantic_dir.exists();
+        finish_semantic_rebuild(&app, &semantic_dir);
+        SEMANTIC_REBUILDS_CANCELLED
+            .lock()
+            .unwrap()
+            .remove(&semantic_dir);
+
+        assert!(was_running);
+        assert!(kept_while_running);
+        assert!(!semantic_dir.exists());
+        assert!(!SEMANTIC_REBUILDS_IN_FLIGHT
+            .lock()
+            .unwrap()
+            .contains(&semantic_dir));
+    }
+
+    #[test]
+    fn find_similar_excludes_source_heading() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("find-similar");
+        if resolve_semantic_resource_path(&app, "model.onnx").is_err()
+            || resolve_semantic_resource_path(&app, "tokenizer.json").is_err()
+        {
+            return;
+        }
+
+        let connection = open_database(&app).unwrap();
+        let (root_id, file_id) = insert_file_row(&connection, "/debate", "Impacts.docx");
+        connection
+            .execute(
+                "UPDATE roots SET last_indexed_ms = 1 WHERE id = ?1",
+                params![root_id],
+            )
+            .unwrap();
+        for (order, heading, text) in [
+            (
+                1,
+                "Warming causes extinction",
+                "Runaway climate change ends civilization.",
+            ),
+            (
+                2,
+                "Warming impact extension",
+                "Rising temperatures collapse food systems.",
+            ),
+            (
+                3,
+                "Midterms link",
+                "The plan costs political capital before elections.",
+            ),
+        ] {
+            connection
+                .execute(
+                    "INSERT INTO chunks(chunk_id, root_id, file_id, chunk_order, heading_order, heading_text, chunk_text, file_name, relative_path, absolute_path)
+                     VALUES(?1, ?2, ?3, ?4, ?4, ?5, ?6, 'Impacts.docx', 'Impacts.docx', '/debate/Impacts.docx')",
+                    params![format!("{file_id}:{order}"), root_id, file_id, order, heading, text],
+                )
+                .unwrap();
+        }
+
+        crate::async_runtime::block_on(rebuild_semantic_index(app.clone(), true)).unwrap();
+        let hits =
+            crate::async_runtime::block_on(semantic_find_similar(&app, file_id, 1, 5)).unwrap();
+
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|hit| hit.heading_order != Some(1)));
+        assert_eq!(hits[0].heading_order, Some(2));
+    }
+
+    #[test]
+    fn identical_texts_are_most_similar() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("text-similarity");
+        if resolve_semantic_resource_path(&app, "model.onnx").is_err()
+            || resolve_semantic_resource_path(&app, "tokenizer.json").is_err()
+        {
+            return;
+        }
+
+        let card = "Runaway climate change ends civilization.";
+        let identical = semantic_text_similarity(&app, card, card).unwrap();
+        let unrelated =
+            semantic_text_similarity(&app, card, "The recipe calls for two cups of flour.")
+                .unwrap();
+
+        assert!((identical - 1.0).abs() < 1e-3);
+        assert!(unrelated < identical);
+        assert!(semantic_text_similarity(&app, card, "  ").is_err());
+    }
+
+    #[test]
+    fn switching_models_requires_rebuild_before_querying() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let resources = temp_dir("semantic-model-resources");
+        std::fs::write(resources.join("alt.onnx"), b"stub").unwrap();
+        std::fs::copy(
+            manifest_resources_dir().join("tokenizer.json"),
+            resources.join("alt.tokenizer.json"),
+        )
+        .unwrap();
+        let app = AppHandle::new(temp_dir("semantic-model"), Some(resources.clone()));
+        let connection = open_database(&app).unwrap();
+        let (root_id, _) = insert_file_row(&connection, "/debate", "Impacts.docx");
+        connection
+            .execute(
+                "UPDATE roots SET last_indexed_ms = 1 WHERE id = ?1",
+                params![root_id],
+            )
+            .unwrap();
+        write_semantic_meta(
+            &app,
+            &SemanticIndexMeta {
+                root_fingerprint_ms: 1,
+                item_count: 1,
+                embedding_dim: 384,
+                updated_at_ms: 1,
+                model_name: None,
+                root_item_counts: HashMap::new(),
+            },
+        )
+        .unwrap();
+        std::fs::create_dir_all(semantic_db_dir(&app).unwrap()).unwrap();
+        let stale_before = semantic_index_is_stale(&app).unwrap();
+
+        let missing = set_semantic_model(&app, "absent");
+        let selection = set_semantic_model(&app, "alt").unwrap();
+        let stale_after = semantic_index_is_stale(&app).unwrap();
+        let query = crate::async_runtime::block_on(semantic_search(&app, "warming", &[], 5));
+        set_semantic_model(&app, "model").unwrap();
+        let stale_restored = semantic_index_is_stale(&app).unwrap();
+        let _ = std::fs::remove_dir_all(&resources);
+
+        assert!(!stale_before);
+        assert!(missing.is_err());
+        assert_eq!(selection.model_name, "alt");
+        assert!(selection.rebuild_required);
+        assert!(stale_after);
+        assert!(query.unwrap_err().contains("rebuild the semantic index"));
+        assert!(!stale_restored);
+    }
+
+    #[test]
+    fn root_coverage_only_counts_that_roots_vectors() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let app = test_app("semantic-root-coverage");
+        let connection = open_database(&app).unwrap();
+        let mut root_ids = Vec::new();
+        for root_path in ["/embedded", "/pending"] {
+            let (root_id, file_id) = insert_file_row(&connection, root_path, "Impacts.docx");
+            for order in 1..=2 {
+                connection
+                    .execute(
+                        "INSERT INTO chunks(chunk_id, root_id, file_id, chunk_order, chunk_text, file_name, relative_path, absolute_path)
+                         VALUES(?1, ?2, ?3, ?4, 'Warming causes extinction.', 'Impacts.docx', 'Impacts.docx', ?5)",
+                        params![
+                            format!("{file_id}:{order}"),
+                            root_id,
+                            file_id,
+                            order,
+                            format!("{root_path}/Impacts.docx")
+                        ],
+                    )
+                    .unwrap();
+            }
+            root_ids.push(root_id);
+        }
+        write_semantic_meta(
+            &app,
+            &SemanticIndexMeta {
+                root_fingerprint_ms: 10,
+                item_count: 2,
+                embedding_dim: 384,
+                updated_at_ms: 10,
+                model_name: None,
+                root_item_counts: HashMap::from([(root_ids[0], 2)]),
+            },
+        )
+        .unwrap();
+
+        let embedded = semantic_root_coverage(&app, root_ids[0], 1).unwrap();
+        let pending = semantic_root_coverage(&app, root_ids[1], 1).unwrap();
+
+        assert_eq!(embedded, 1.0);
+        assert_eq!(pending, 0.0);
+    }
+
+    #[test]
+    fn selected_model_is_restored_on_startup() {
+        let _guard = GLOBAL_SETTINGS_LOCK.lock().unwrap();
+        let resources = temp_dir("semantic-model-restore-resources");
+        std::fs::write(resources.join("alt.onnx"), b"stub").unwrap();
+        std::fs::write(resources.join("alt.tokenizer.json"), b"{}").unwrap();
+        let app = AppHandle::new(temp_dir("semantic-model-restore"), Some(resources.clone()));
+
+        set_semantic_model(&app, "alt").unwrap();
+        // A fresh process starts on the default model until the saved choice is loaded.
+        *SEMANTIC_MODEL_NAME.write().unwrap() = None;
+        load_semantic_model(&app);
+        let restored = active_semantic_model();
+        load_semantic_model(&test_app("semantic-model-unset"));
+        let unset = active_semantic_model();
+        let _ = std::fs::remove_dir_all(&resources);
+
+        assert_eq!(restored, "alt");
+        assert_eq!(unset, DEFAULT_SEMANTIC_MODEL);
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::db::add_or_get_root_id;
+use crate::runtime::AppHandle;
+
+static NEXT_TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Held by tests that flip or depend on process-wide settings (semantic toggle, model choice),
+// so the parallel test runner never mixes them.
+pub(crate) static GLOBAL_SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+
+const ROOT_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+pub(crate) fn temp_dir(label: &str) -> PathBuf {
+    let id = NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed);
+    let path = std::env::temp_dir().join(format!("blockfile-{label}-{}-{id}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).expect("create test temp dir");
+    path
+}
+
+pub(crate) fn test_app(label: &str) -> AppHandle {
+    AppHandle::new(temp_dir(label), None)
+}
+
+pub(crate) fn insert_file_row(
+    connection: &Connection,
+    root_path: &str,
+    relative_path: &str,
+) -> (i64, i64) {
+    let root_id = add_or_get_root_id(connection, root_path).expect("insert test root");
+    connection
+        .execute(
+            "INSERT INTO files(root_id, relative_path, absolute_path, modified_ms, size)
+             VALUES(?1, ?2, ?3, 0, 0)",
+            params![
+                root_id,
+                relative_path,
+                format!("{root_path}/{relative_path}")
+            ],
+        )
+        .expect("insert test file");
+    (root_id, connection.last_insert_rowid())
+}
+
+pub(crate) fn heading_xml(level: i64, text: &str) -> String {
+    format!(
+        "<w:p><w:pPr><w:pStyle w:val=\"Heading{level}\"/></w:pPr><w:r><w:t>{text}</w:t></w:r></w:p>"
+    )
+}
+
+pub(crate) fn paragraph_xml(text: &str) -> String {
+    format!("<w:p><w:r><w:t xml:space=\"preserve\">{text}</w:t></w:r></w:p>")
+}
+
+pub(crate) fn document_xml(body_xml: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?><w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>{body_xml}<w:sectPr/></w:body></w:document>"
+    )
+}
+
+pub(crate) fn write_docx(path: &Path, body_xml: &str) {
+    write_docx_parts(path, &[("word/document.xml", document_xml(body_xml))]);
+}
+
+pub(crate) fn write_docx_parts(path: &Path, parts: &[(&str, String)]) {
+    let file = File::create(path).expect("create test docx");
+    let mut writer = ZipWriter::new(file);
+    let mut entries = vec![
+        ("[Content_Types].xml", CONTENT_TYPES_XML.to_string()),
+        ("_rels/.rels", ROOT_RELS_XML.to_string()),
+    ];
+    entries.extend(parts.iter().map(|(name, xml)| (*name, xml.clone())));
+
+    for (name, xml) in entries {
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .expect("start test docx entry");
+        writer
+            .write_all(xml.as_bytes())
+            .expect("write test docx entry");
+    }
+    writer.finish().expect("finish test docx");
+}
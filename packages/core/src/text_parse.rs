@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::Path;
+
+use crate::docx_parse::parse_docx_paragraphs;
+use crate::types::ParsedParagraph;
+use crate::util::path_display;
+use crate::CommandResult;
+
+fn extension_matches(path: &Path, expected: &[&str]) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| {
+            expected
+                .iter()
+                .any(|candidate| extension.eq_ignore_ascii_case(candidate))
+        })
+        .unwrap_or(false)
+}
+
+pub(crate) fn is_markdown_path(path: &Path) -> bool {
+    extension_matches(path, &["md", "markdown"])
+}
+
+pub(crate) fn is_plain_text_path(path: &Path) -> bool {
+    extension_matches(path, &["txt", "md", "markdown"])
+}
+
+pub(crate) fn is_indexable_path(path: &Path, include_text_files: bool) -> bool {
+    extension_matches(path, &["docx"]) || (include_text_files && is_plain_text_path(path))
+}
+
+fn markdown_heading(line: &str) -> Option<(i64, String)> {
+    let hashes = line
+        .chars()
+        .take_while(|character| *character == '#')
+        .count();
+    if hashes == 0 || hashes > 6 {
+        return None;
+    }
+    let rest = &line[hashes..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end().to_string();
+    Some((i64::try_from(hashes).unwrap_or(1), text))
+}
+
+pub(crate) fn parse_text_paragraphs(text: &str, markdown: bool) -> Vec<ParsedParagraph> {
+    let mut paragraphs = Vec::new();
+    let mut pending_lines: Vec<&str> = Vec::new();
+
+    let flush = |pending_lines: &mut Vec<&str>, paragraphs: &mut Vec<ParsedParagraph>| {
+        if pending_lines.is_empty() {
+            return;
+        }
+        paragraphs.push(ParsedParagraph {
+            order: i64::try_from(paragraphs.len()).unwrap_or(0) + 1,
+            text: pending_lines.join(" "),
+            heading_level: None,
+            style_label: None,
+            is_f8_cite: false,
+        });
+        pending_lines.clear();
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            flush(&mut pending_lines, &mut paragraphs);
+            continue;
+        }
+
+        if markdown {
+            if let Some((level, heading_text)) = markdown_heading(trimmed) {
+                flush(&mut pending_lines, &mut paragraphs);
+                paragraphs.push(ParsedParagraph {
+                    order: i64::try_from(paragraphs.len()).unwrap_or(0) + 1,
+                    text: heading_text,
+                    heading_level: Some(level),
+                    style_label: Some(format!("Heading {level}")),
+                    is_f8_cite: false,
+                });
+                continue;
+            }
+        }
+
+        pending_lines.push(trimmed);
+    }
+    flush(&mut pending_lines, &mut paragraphs);
+
+    paragraphs
+}
+
+pub(crate) fn parse_source_paragraphs(file_path: &Path) -> CommandResult<Vec<ParsedParagraph>> {
+    if !is_plain_text_path(file_path) {
+        return parse_docx_paragraphs(file_path);
+    }
+
+    let bytes = fs::read(file_path)
+        .map_err(|error| format!("Could not read '{}': {error}", path_display(file_path)))?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(parse_text_paragraphs(&text, is_markdown_path(file_path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_indexable_path, parse_text_paragraphs};
+    use std::path::Path;
+
+    #[test]
+    fn markdown_headings_become_heading_paragraphs() {
+        let paragraphs = parse_text_paragraphs(
+            "# Econ DA\n\n## Uniqueness\nGrowth is steady\nacross sectors.\n\n#hashtag line",
+            true,
+        );
+
+        let summary = paragraphs
+            .iter()
+            .map(|paragraph| {
+                (
+                    paragraph.order,
+                    paragraph.heading_level,
+                    paragraph.text.as_str(),
+                )
+            })
+            .collect::<Vec<(i64, Option<i64>, &str)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (1, Some(1), "Econ DA"),
+                (2, Some(2), "Uniqueness"),
+                (3, None, "Growth is steady across sectors."),
+                (4, None, "#hashtag line"),
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_text_files_are_gated_by_option() {
+        assert!(is_indexable_path(Path::new("Aff/Plan.DOCX"), false));
+        assert!(!is_indexable_path(Path::new("notes.md"), false));
+        assert!(is_indexable_path(Path::new("notes.md"), true));
+        assert!(is_indexable_path(Path::new("notes.txt"), true));
+        assert!(!is_indexable_path(Path::new("notes.pdf"), true));
+    }
+}
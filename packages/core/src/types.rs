@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 
 use ort::session::Session as OrtSession;
@@ -13,6 +13,106 @@ pub(crate) struct RootSummary {
     pub heading_count: i64,
     pub added_at_ms: i64,
     pub last_indexed_ms: i64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GlobalStats {
+    pub root_count: i64,
+    pub file_count: i64,
+    pub heading_count: i64,
+    pub author_count: i64,
+    pub chunk_count: i64,
+    pub capture_target_count: i64,
+    pub total_indexed_bytes: i64,
+    pub semantic_item_count: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LexicalRebuildStats {
+    pub document_count: usize,
+    pub elapsed_ms: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MetadataRefreshStats {
+    pub file_id: i64,
+    pub author_count: usize,
+    pub updated_headings: usize,
+    pub updated_chunks: usize,
+    pub lexical_document_count: usize,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct IndexOptions {
+    // `None` keeps what the root was last indexed with (off for a new root).
+    pub index_paragraphs: Option<bool>,
+    pub include_text_files: bool,
+    pub benchmark: bool,
+    pub rebuild_semantic: bool,
+    pub merge_short_headings: Option<bool>,
+    pub index_comments: Option<bool>,
+    pub collapse_whitespace: Option<bool>,
+    pub max_indexable_bytes: Option<u64>,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            index_paragraphs: None,
+            include_text_files: false,
+            benchmark: false,
+            rebuild_semantic: true,
+            merge_short_headings: None,
+            index_comments: None,
+            collapse_whitespace: None,
+            max_indexable_bytes: None,
+        }
+    }
+}
+
+// Bump when parsing starts storing something new per file, so every root is reparsed once.
+const INDEX_PARSER_VERSION: u32 = 2;
+
+impl IndexOptions {
+    // Options that change which chunk rows a file produces. When these differ from the
+    // last run, unchanged files are reparsed too.
+    pub(crate) fn chunk_fingerprint(&self) -> String {
+        format!(
+            "parser={INDEX_PARSER_VERSION};paragraphs={};merge_short_headings={};comments={};collapse_whitespace={}",
+            self.index_paragraphs.unwrap_or(false),
+            self.merge_short_headings.unwrap_or(false),
+            self.index_comments.unwrap_or(false),
+            self.collapse_whitespace.unwrap_or(false)
+        )
+    }
+
+    // Fills the chunk options left unset from a root's stored `chunk_fingerprint`.
+    pub(crate) fn with_stored_chunk_options(self, stored_fingerprint: &str) -> Self {
+        let stored = |key: &str| {
+            stored_fingerprint.split(';').find_map(|pair| {
+                pair.strip_prefix(key)?
+                    .strip_prefix('=')?
+                    .parse::<bool>()
+                    .ok()
+            })
+        };
+        Self {
+            index_paragraphs: self.index_paragraphs.or_else(|| stored("paragraphs")),
+            merge_short_headings: self
+                .merge_short_headings
+                .or_else(|| stored("merge_short_headings")),
+            index_comments: self.index_comments.or_else(|| stored("comments")),
+            collapse_whitespace: self
+                .collapse_whitespace
+                .or_else(|| stored("collapse_whitespace")),
+            ..self
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -24,9 +124,49 @@ pub(crate) struct IndexStats {
     pub removed: usize,
     pub headings_extracted: usize,
     pub elapsed_ms: i64,
+    pub size_buckets: Vec<IndexSizeBucket>,
+    pub failed: Vec<IndexFailure>,
+    pub skipped_too_large: Vec<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndexFailure {
+    pub relative_path: String,
+    pub kind: String,
+    pub error: String,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndexSizeBucket {
+    pub label: String,
+    pub file_count: usize,
+    pub total_bytes: i64,
+    pub mean_parse_ms: f64,
+    pub mean_insert_ms: f64,
+    pub mean_total_ms: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndexDriftEntry {
+    pub relative_path: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RootHealth {
+    pub score: u8,
+    pub file_count: usize,
+    pub parse_error_files: usize,
+    pub out_of_date_files: usize,
+    pub zero_heading_files: usize,
+    pub semantic_coverage: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct FolderEntry {
     pub path: String,
@@ -36,7 +176,7 @@ pub(crate) struct FolderEntry {
     pub file_count: usize,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct IndexedFile {
     pub id: i64,
@@ -47,7 +187,7 @@ pub(crate) struct IndexedFile {
     pub heading_count: i64,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct IndexSnapshot {
     pub root_path: String,
@@ -56,7 +196,74 @@ pub(crate) struct IndexSnapshot {
     pub files: Vec<IndexedFile>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SearchScope {
+    #[default]
+    All,
+    Captures,
+    Sources,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FileScope {
+    pub file_ids: Vec<i64>,
+    pub include: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct HybridSearchOptions {
+    pub root_path: Option<String>,
+    pub root_paths: Option<Vec<String>>,
+    pub limit: Option<usize>,
+    pub file_name_only: Option<bool>,
+    pub file_name_boost: Option<f64>,
+    pub semantic_enabled: Option<bool>,
+    pub semantic_fallback_only: Option<bool>,
+    pub collapse_near_duplicates: Option<bool>,
+    pub near_duplicate_threshold: Option<f64>,
+    pub explain: Option<bool>,
+    pub drop_stopwords: Option<bool>,
+    pub argument_type: Option<String>,
+    #[serde(alias = "corpus")]
+    pub scope: Option<SearchScope>,
+    pub timeout_ms: Option<u64>,
+    pub stream: Option<bool>,
+    pub request_id: Option<String>,
+}
+
+impl HybridSearchOptions {
+    pub(crate) fn stream_request_id(&self) -> Option<&str> {
+        if self.stream.unwrap_or(false) {
+            self.request_id.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CitationStyle {
+    #[default]
+    Debate,
+    Mla,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChunkContextEntry {
+    pub chunk_order: i64,
+    pub heading_order: Option<i64>,
+    pub heading_level: Option<i64>,
+    pub heading_text: Option<String>,
+    pub author_text: Option<String>,
+    pub chunk_text: String,
+    pub is_match: bool,
+}
+
+#[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct FileHeading {
     pub id: i64,
@@ -64,10 +271,103 @@ pub(crate) struct FileHeading {
     pub level: i64,
     pub text: String,
     pub copy_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_path: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_id: Option<String>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
+pub(crate) struct HeadingNeighbors {
+    pub parent: Option<FileHeading>,
+    pub previous_sibling: Option<FileHeading>,
+    pub next_sibling: Option<FileHeading>,
+    pub first_child: Option<FileHeading>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HeadingXml {
+    pub file_id: i64,
+    pub heading_order: i64,
+    pub heading_text: String,
+    pub level: i64,
+    pub xml: String,
+    pub namespace_declarations: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OutlineHeading {
+    pub order: i64,
+    pub level: i64,
+    pub text: String,
+    pub children: Vec<OutlineHeading>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HighlightedBlock {
+    pub heading_order: Option<i64>,
+    pub text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DuplicateFile {
+    pub file_id: i64,
+    pub root_path: String,
+    pub relative_path: String,
+    pub absolute_path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DuplicateFileCluster {
+    pub file_hash: String,
+    pub size: i64,
+    pub files: Vec<DuplicateFile>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PreviewDebugStats {
+    pub paragraph_count: usize,
+    pub run_count: usize,
+    pub bold_runs: usize,
+    pub italic_runs: usize,
+    pub underline_runs: usize,
+    pub highlight_runs: usize,
+    pub heading_levels: BTreeMap<i64, usize>,
+    pub f8_cite_blocks: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileStyleUsage {
+    pub style_id: String,
+    pub name: String,
+    pub usage_count: usize,
+    pub heading_level: Option<i64>,
+    pub is_f8_cite: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileDiff {
+    pub file_id_a: i64,
+    pub file_id_b: i64,
+    pub relative_path_a: String,
+    pub relative_path_b: String,
+    pub common_headings: Vec<String>,
+    pub removed_headings: Vec<String>,
+    pub added_headings: Vec<String>,
+    pub similarity: f64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct TaggedBlock {
     pub order: i64,
     pub style_label: String,
@@ -84,9 +384,11 @@ pub(crate) struct FilePreview {
     pub heading_count: i64,
     pub headings: Vec<FileHeading>,
     pub f8_cites: Vec<TaggedBlock>,
+    pub word_count: i64,
+    pub estimated_read_ms: i64,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct SearchHit {
     pub source: String,
@@ -99,6 +401,23 @@ pub(crate) struct SearchHit {
     pub heading_text: Option<String>,
     pub heading_order: Option<i64>,
     pub score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_path: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_type: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CapturePreview {
+    pub paragraph_xml: Vec<String>,
+    pub used_source_xml: bool,
+    pub style_ids: Vec<String>,
+    pub relationship_ids: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -109,6 +428,31 @@ pub(crate) struct CaptureInsertResult {
     pub target_relative_path: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureRecord {
+    pub id: i64,
+    pub marker: String,
+    pub source_path: String,
+    pub section_title: String,
+    pub target_relative_path: String,
+    pub heading_level: Option<i64>,
+    pub created_at_ms: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResolvedCaptureMarker {
+    pub marker: String,
+    pub root_path: String,
+    pub source_path: String,
+    pub target_relative_path: String,
+    pub target_path: String,
+    pub target_exists: bool,
+    pub section_title: String,
+    pub created_at_ms: i64,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CaptureTarget {
@@ -116,6 +460,33 @@ pub(crate) struct CaptureTarget {
     pub absolute_path: String,
     pub exists: bool,
     pub entry_count: i64,
+    pub modified_ms: i64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CaptureTargetSort {
+    #[default]
+    Name,
+    Modified,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureRepairReport {
+    pub target_relative_path: String,
+    pub repaired: bool,
+    pub repaired_parts: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrashedHeading {
+    pub id: i64,
+    pub target_relative_path: String,
+    pub heading_level: i64,
+    pub heading_text: String,
+    pub deleted_at_ms: i64,
 }
 
 #[derive(Serialize)]
@@ -128,6 +499,46 @@ pub(crate) struct CaptureTargetPreview {
     pub headings: Vec<FileHeading>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureTargetStats {
+    pub relative_path: String,
+    pub exists: bool,
+    pub heading_count: i64,
+    pub paragraph_count: i64,
+    pub word_count: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SemanticEmbeddingLimits {
+    pub max_tokens: usize,
+    pub max_chars: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PreviewLimits {
+    pub max_chars_per_heading: usize,
+    pub max_total_chars: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LexicalTokenizerConfig {
+    pub prefix_min_gram: usize,
+    pub prefix_max_gram: usize,
+    pub ngram_min_gram: usize,
+    pub ngram_max_gram: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureHeadingTransfer {
+    pub source: CaptureTargetPreview,
+    pub destination: CaptureTargetPreview,
+}
+
 #[derive(Clone)]
 pub(crate) struct ExistingFileMeta {
     pub id: i64,
@@ -152,6 +563,26 @@ pub(crate) struct ParsedParagraph {
     pub is_f8_cite: bool,
 }
 
+#[derive(Clone)]
+pub(crate) struct CommentBody {
+    pub author: Option<String>,
+    pub text: String,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct DocumentProperties {
+    pub title: Option<String>,
+    pub creator: Option<String>,
+    pub keywords: Option<String>,
+}
+
+#[derive(Clone)]
+pub(crate) struct ParsedComment {
+    pub paragraph_order: i64,
+    pub author: Option<String>,
+    pub text: String,
+}
+
 #[derive(Clone)]
 pub(crate) struct HeadingRange {
     pub order: i64,
@@ -182,11 +613,15 @@ pub(crate) struct ParsedIndexCandidate {
     pub headings: Vec<ParsedHeading>,
     pub authors: Vec<(i64, String)>,
     pub chunks: Vec<ParsedChunk>,
+    pub argument_types: HashMap<i64, String>,
+    pub properties: DocumentProperties,
+    pub parse_ms: f64,
 }
 
 #[derive(Clone)]
 pub(crate) struct ParsedChunk {
     pub chunk_order: i64,
+    pub kind: String,
     pub heading_order: Option<i64>,
     pub heading_level: Option<i64>,
     pub heading_text: Option<String>,
@@ -216,12 +651,65 @@ pub(crate) struct SemanticIndexMeta {
     pub item_count: usize,
     pub embedding_dim: usize,
     pub updated_at_ms: i64,
+    #[serde(default)]
+    pub model_name: Option<String>,
+    #[serde(default)]
+    pub root_item_counts: HashMap<i64, usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SemanticReadiness {
+    pub ready: bool,
+    pub missing: Vec<String>,
+    pub error: Option<String>,
+    pub model_name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SemanticModelSelection {
+    pub model_name: String,
+    pub rebuild_required: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum PoolingStrategy {
+    #[default]
+    Mean,
+    Cls,
 }
 
 pub(crate) struct SemanticRuntime {
     pub tokenizer: Tokenizer,
     pub session: OrtSession,
     pub output_name: String,
+    pub pooling: PoolingStrategy,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchResultBatch {
+    pub request_id: String,
+    pub source: String,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchResponse {
+    pub hits: Vec<SearchHit>,
+    pub semantic_degraded: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchComplete {
+    pub request_id: String,
+    pub total_hits: usize,
+    pub elapsed_ms: i64,
+    pub error: Option<String>,
+    pub semantic_degraded: bool,
 }
 
 #[derive(Clone, Serialize)]
@@ -235,6 +723,7 @@ pub(crate) struct IndexProgress {
     pub updated: usize,
     pub skipped: usize,
     pub removed: usize,
+    pub percent: f64,
     pub elapsed_ms: i64,
     pub current_file: Option<String>,
 }
@@ -310,3 +799,19 @@ pub(crate) struct RelationshipDef {
     pub target: String,
     pub target_mode: Option<String>,
 }
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct CaptureSettings {
+    pub banner_text: Option<String>,
+    pub default_target: Option<String>,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            banner_text: Some(crate::docx_capture::DEFAULT_CAPTURE_BANNER_TEXT.to_string()),
+            default_target: None,
+        }
+    }
+}
@@ -2,9 +2,11 @@ use std::collections::HashSet;
 use std::fs;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::runtime::AppHandle;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use walkdir::DirEntry;
 
 use crate::search::normalize_for_search;
@@ -13,7 +15,11 @@ use crate::CommandResult;
 use crate::DEFAULT_CAPTURE_TARGET;
 
 pub(crate) const INDEX_PROGRESS_EVENT: &str = "index-progress";
+pub(crate) const INDEX_WARNING_EVENT: &str = "index-warning";
 pub(crate) const INDEX_PROGRESS_EMIT_INTERVAL_MS: i64 = 120;
+pub(crate) const INDEX_PROGRESS_INDETERMINATE: f64 = -1.0;
+
+static INDEX_THREAD_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
 
 pub(crate) fn now_ms() -> i64 {
     epoch_ms(SystemTime::now())
@@ -40,6 +46,45 @@ pub(crate) fn suggested_parse_chunk_size() -> usize {
         .clamp(2, 12)
 }
 
+pub(crate) fn configure_index_thread_pool(threads: Option<usize>) -> CommandResult<usize> {
+    let pool = threads
+        .map(|threads| {
+            ThreadPoolBuilder::new()
+                .num_threads(threads.max(1))
+                .thread_name(|index| format!("blockfile-index-{index}"))
+                .build()
+                .map(Arc::new)
+                .map_err(|error| format!("Could not build index thread pool: {error}"))
+        })
+        .transpose()?;
+    let applied = pool
+        .as_ref()
+        .map(|pool| pool.current_num_threads())
+        .unwrap_or_else(rayon::current_num_threads);
+    if let Ok(mut writer) = INDEX_THREAD_POOL.write() {
+        *writer = pool;
+    }
+    Ok(applied)
+}
+
+pub(crate) fn with_index_pool<R: Send>(operation: impl FnOnce() -> R + Send) -> R {
+    let pool = INDEX_THREAD_POOL
+        .read()
+        .ok()
+        .and_then(|reader| reader.clone());
+    match pool {
+        Some(pool) => pool.install(operation),
+        None => operation(),
+    }
+}
+
+pub(crate) fn phase_percent(done: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    done.min(total) as f64 * 100.0 / total as f64
+}
+
 pub(crate) fn emit_index_progress(
     app: &AppHandle,
     started_at: i64,
@@ -72,10 +117,17 @@ pub(crate) fn root_index_marker_path(root: &Path) -> PathBuf {
 }
 
 pub(crate) fn normalize_capture_target_path(target_path: Option<&str>) -> CommandResult<String> {
+    normalize_capture_target_path_with_default(target_path, DEFAULT_CAPTURE_TARGET)
+}
+
+pub(crate) fn normalize_capture_target_path_with_default(
+    target_path: Option<&str>,
+    default_target: &str,
+) -> CommandResult<String> {
     let raw = target_path
         .map(str::trim)
         .filter(|value| !value.is_empty())
-        .unwrap_or(DEFAULT_CAPTURE_TARGET);
+        .unwrap_or(default_target);
 
     let candidate = Path::new(raw);
     let mut normalized = if candidate.is_absolute() {
@@ -119,6 +171,15 @@ pub(crate) fn capture_marker(entry_id: i64) -> String {
     format!("BF-{entry_id:06}")
 }
 
+pub(crate) fn parse_capture_marker(marker: &str) -> CommandResult<i64> {
+    marker
+        .trim()
+        .strip_prefix("BF-")
+        .and_then(|digits| digits.parse::<i64>().ok())
+        .filter(|entry_id| *entry_id > 0)
+        .ok_or_else(|| format!("Invalid capture marker '{}'.", marker.trim()))
+}
+
 pub(crate) fn write_root_index_marker(root: &Path, last_indexed_ms: i64) -> CommandResult<()> {
     let marker_path = root_index_marker_path(root);
     let marker = serde_json::json!({
@@ -160,8 +221,27 @@ pub(crate) fn fast_file_hash(path: &Path) -> CommandResult<String> {
     })?;
     hasher.update(&buffer[..front_bytes]);
 
-    if file_len > u64::try_from(WINDOW_BYTES).unwrap_or(0) {
-        let start = file_len.saturating_sub(u64::try_from(WINDOW_BYTES).unwrap_or(0));
+    // Cards are usually inserted mid-document, so large files also hash a window at the midpoint.
+    let window_len = u64::try_from(WINDOW_BYTES).unwrap_or(0);
+    if file_len > window_len.saturating_mul(2) {
+        let start = (file_len / 2).saturating_sub(window_len / 2);
+        file.seek(SeekFrom::Start(start)).map_err(|error| {
+            format!(
+                "Could not seek hash midpoint for '{}': {error}",
+                path_display(path)
+            )
+        })?;
+        let middle_bytes = file.read(&mut buffer).map_err(|error| {
+            format!(
+                "Could not read hash midpoint for '{}': {error}",
+                path_display(path)
+            )
+        })?;
+        hasher.update(&buffer[..middle_bytes]);
+    }
+
+    if file_len > window_len {
+        let start = file_len.saturating_sub(window_len);
         file.seek(SeekFrom::Start(start)).map_err(|error| {
             format!(
                 "Could not seek hash suffix for '{}': {error}",
@@ -180,6 +260,12 @@ pub(crate) fn fast_file_hash(path: &Path) -> CommandResult<String> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
+pub(crate) fn chunk_content_hash(text: &str) -> String {
+    blake3::hash(normalize_for_search(text).as_bytes())
+        .to_hex()
+        .to_string()
+}
+
 pub(crate) fn file_name_from_relative(relative_path: &str) -> String {
     Path::new(relative_path)
         .file_name()
@@ -194,6 +280,43 @@ pub(crate) fn folder_from_relative(relative_path: &str) -> String {
         .unwrap_or_default()
 }
 
+// Windows paths are detected by shape (drive letter, UNC, or `\\?\` verbatim prefix) rather
+// than by target, so the same stored path yields the same URI on every host.
+pub(crate) fn file_uri(path: &str) -> String {
+    let path = if let Some(share) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{share}")
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    };
+    let bytes = path.as_bytes();
+    let is_windows_path = path.starts_with(r"\\")
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':');
+    let path = if is_windows_path {
+        path.replace('\\', "/")
+    } else {
+        path
+    };
+
+    let (host, path) = match path.strip_prefix("//") {
+        Some(share) => match share.split_once('/') {
+            Some((host, rest)) => (host.to_string(), format!("/{rest}")),
+            None => (share.to_string(), String::new()),
+        },
+        None if path.starts_with('/') => (String::new(), path),
+        None => (String::new(), format!("/{path}")),
+    };
+
+    let mut uri = format!("file://{host}");
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
 pub(crate) fn is_visible_entry(entry: &DirEntry) -> bool {
     let name = entry.file_name().to_string_lossy();
     !name.starts_with('.')
@@ -206,18 +329,15 @@ pub(crate) fn relative_path(root: &Path, file_path: &Path) -> CommandResult<Stri
     Ok(relative.to_string_lossy().replace('\\', "/"))
 }
 
-pub(crate) fn contains_year_token(text: &str) -> bool {
-    for token in text
-        .split(|character: char| !character.is_ascii_digit())
+pub(crate) fn first_year_token(text: &str) -> Option<i32> {
+    text.split(|character: char| !character.is_ascii_digit())
         .filter(|token| token.len() == 4)
-    {
-        if let Ok(year) = token.parse::<i32>() {
-            if (1900..=2099).contains(&year) {
-                return true;
-            }
-        }
-    }
-    false
+        .filter_map(|token| token.parse::<i32>().ok())
+        .find(|year| (1900..=2099).contains(year))
+}
+
+pub(crate) fn contains_year_token(text: &str) -> bool {
+    first_year_token(text).is_some()
 }
 
 pub(crate) fn is_probable_author_line(text: &str) -> bool {
@@ -275,7 +395,11 @@ pub(crate) fn extract_author_candidates(paragraphs: &[ParsedParagraph]) -> Vec<(
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_author_candidates, normalize_capture_target_path};
+    use super::{
+        extract_author_candidates, fast_file_hash, file_uri, normalize_capture_target_path,
+        phase_percent,
+    };
+    use crate::test_support::temp_dir;
     use crate::types::ParsedParagraph;
     use crate::DEFAULT_CAPTURE_TARGET;
 
@@ -298,6 +422,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn file_uri_percent_encodes_spaces_and_unicode() {
+        assert_eq!(
+            file_uri("/Users/deb/Camp Files/Aff – Heg.docx"),
+            "file:///Users/deb/Camp%20Files/Aff%20%E2%80%93%20Heg.docx"
+        );
+        assert_eq!(
+            file_uri("/home/deb/Débat/#1 50%.docx"),
+            "file:///home/deb/D%C3%A9bat/%231%2050%25.docx"
+        );
+        assert_eq!(
+            file_uri(r"C:\Users\deb\My Files\Neg.docx"),
+            "file:///C:/Users/deb/My%20Files/Neg.docx"
+        );
+        assert_eq!(
+            file_uri(r"\\?\C:\Debate\Aff.docx"),
+            "file:///C:/Debate/Aff.docx"
+        );
+        assert_eq!(
+            file_uri(r"\\server\team share\Aff.docx"),
+            "file://server/team%20share/Aff.docx"
+        );
+    }
+
     #[test]
     fn extract_author_candidates_deduplicates_normalized_lines() {
         let paragraphs = vec![
@@ -332,4 +480,35 @@ mod tests {
             "Smith, J., Doe, A. (2021). Journal of Testing"
         );
     }
+
+    #[test]
+    fn indexing_percent_tracks_processed_over_changed() {
+        let changed = 8;
+        let percents = (0..=changed)
+            .map(|processed| phase_percent(processed, changed))
+            .collect::<Vec<f64>>();
+
+        assert_eq!(
+            percents,
+            vec![0.0, 12.5, 25.0, 37.5, 50.0, 62.5, 75.0, 87.5, 100.0]
+        );
+        assert_eq!(phase_percent(0, 0), 100.0);
+        assert_eq!(phase_percent(9, changed), 100.0);
+    }
+
+    #[test]
+    fn middle_only_edit_changes_fast_hash() {
+        let dir = temp_dir("fast-hash-middle");
+        let path = dir.join("Large.docx");
+        let mut bytes = vec![b'a'; 512 * 1024];
+        std::fs::write(&path, &bytes).unwrap();
+        let before = fast_file_hash(&path).unwrap();
+
+        bytes[256 * 1024] = b'b';
+        std::fs::write(&path, &bytes).unwrap();
+        let after = fast_file_hash(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_ne!(before, after);
+    }
 }
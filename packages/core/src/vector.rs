@@ -1,20 +1,77 @@
 use crate::runtime::AppHandle;
 
-use crate::semantic::{semantic_search, trigger_semantic_rebuild, SEMANTIC_MIN_QUERY_CHARS};
-use crate::types::SearchHit;
+use crate::semantic::{
+    cancel_semantic_rebuild, configure_embedding_limits, semantic_enabled, semantic_find_similar,
+    semantic_item_count, semantic_readiness, semantic_root_coverage, semantic_search,
+    semantic_text_similarity, set_semantic_enabled, set_semantic_model, trigger_semantic_rebuild,
+    SEMANTIC_MIN_QUERY_CHARS,
+};
+use crate::types::{SearchHit, SemanticEmbeddingLimits, SemanticModelSelection, SemanticReadiness};
 use crate::CommandResult;
 
 pub(crate) const VECTOR_MIN_QUERY_CHARS: usize = SEMANTIC_MIN_QUERY_CHARS;
 
-pub(crate) fn trigger_rebuild(app: AppHandle, force: bool) {
-    trigger_semantic_rebuild(app, force);
+pub(crate) fn trigger_rebuild(app: AppHandle, force: bool) -> bool {
+    trigger_semantic_rebuild(app, force)
+}
+
+pub(crate) fn cancel_rebuild(app: &AppHandle, clear: bool) -> CommandResult<bool> {
+    cancel_semantic_rebuild(app, clear)
+}
+
+pub(crate) fn set_enabled(enabled: bool) -> bool {
+    set_semantic_enabled(enabled)
+}
+
+pub(crate) fn set_model(app: &AppHandle, name: &str) -> CommandResult<SemanticModelSelection> {
+    set_semantic_model(app, name)
+}
+
+pub(crate) fn text_similarity(app: &AppHandle, a: &str, b: &str) -> CommandResult<f32> {
+    semantic_text_similarity(app, a, b)
+}
+
+pub(crate) fn configure_limits(
+    max_tokens: Option<usize>,
+    max_chars: Option<usize>,
+) -> SemanticEmbeddingLimits {
+    configure_embedding_limits(max_tokens, max_chars)
+}
+
+pub(crate) fn item_count(app: &AppHandle) -> CommandResult<usize> {
+    semantic_item_count(app)
+}
+
+pub(crate) fn root_coverage(
+    app: &AppHandle,
+    root_id: i64,
+    root_last_indexed_ms: i64,
+) -> CommandResult<f64> {
+    semantic_root_coverage(app, root_id, root_last_indexed_ms)
+}
+
+pub(crate) fn is_enabled() -> bool {
+    semantic_enabled()
+}
+
+pub(crate) fn readiness(app: &AppHandle) -> SemanticReadiness {
+    semantic_readiness(app)
 }
 
 pub(crate) async fn search(
     app: &AppHandle,
     query: &str,
-    requested_root_id: Option<i64>,
+    requested_root_ids: &[i64],
+    limit: usize,
+) -> CommandResult<Vec<SearchHit>> {
+    semantic_search(app, query, requested_root_ids, limit).await
+}
+
+pub(crate) async fn find_similar(
+    app: &AppHandle,
+    file_id: i64,
+    heading_order: i64,
     limit: usize,
 ) -> CommandResult<Vec<SearchHit>> {
-    semantic_search(app, query, requested_root_id, limit).await
+    semantic_find_similar(app, file_id, heading_order, limit).await
 }
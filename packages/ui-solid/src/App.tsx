@@ -33,6 +33,7 @@ import type {
   IndexStats,
   RootSummary,
   SearchHit,
+  SearchResponse,
   SidePreview,
   TreeRow,
 } from "./lib/types";
@@ -1513,7 +1514,7 @@ function App() {
     setIsSearchingLocal(true);
     const requestId = ++searchRequestSeq;
     const timer = setTimeout(() => {
-      const invocation = invokeTyped<SearchHit[]>("search_index_hybrid", {
+      const invocation = invokeTyped<SearchResponse>("search_index_hybrid", {
         query,
         rootPath: searchRootPath,
         limit: 120,
@@ -1524,12 +1525,15 @@ function App() {
           if (requestId === searchRequestSeq) {
             setStatus(`Search failed: ${String(error)}`);
           }
-          return [] as SearchHit[];
+          return { hits: [], semanticDegraded: false } as SearchResponse;
         })
-        .then((results) => {
+        .then((response) => {
           if (requestId === searchRequestSeq) {
+            if (response.semanticDegraded) {
+              setStatus("Semantic results unavailable; showing keyword matches only.");
+            }
             startTransition(() => {
-              setSearchResults(results);
+              setSearchResults(response.hits);
             });
           }
         })
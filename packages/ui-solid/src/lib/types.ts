@@ -67,6 +67,11 @@ export type SearchHit = {
   score: number;
 };
 
+export type SearchResponse = {
+  hits: SearchHit[];
+  semanticDegraded: boolean;
+};
+
 export type IndexStats = {
   scanned: number;
   updated: number;
@@ -118,6 +123,7 @@ export type CaptureTarget = {
   absolutePath: string;
   exists: boolean;
   entryCount: number;
+  modifiedMs: number;
 };
 
 export type CaptureTargetPreview = {